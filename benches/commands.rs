@@ -0,0 +1,117 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use futures::{future, prelude::*};
+use redis::{Client, ControlFlow};
+use redis_ac::{bench_support, Commands, PubSubCommands};
+use tokio::runtime::current_thread::block_on_all;
+
+const KEYS: usize = 10_000;
+const SETS: usize = 100;
+const MESSAGES: usize = 1000;
+
+fn client() -> Client {
+    let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1".into());
+    Client::open(url.as_ref()).unwrap()
+}
+
+fn scan(c: &mut Criterion) {
+    let client = client();
+    let mut con = client.get_connection().unwrap();
+    bench_support::flush(&mut con).unwrap();
+    bench_support::seed_keys(&mut con, "key", KEYS).unwrap();
+
+    c.bench_function("scan_match 10k keys", |b| {
+        b.iter(|| {
+            let (_, n) = block_on_all(
+                client
+                    .get_async_connection()
+                    .and_then(|con| con.count_matching("key:*")),
+            )
+            .unwrap();
+            // The scan completes with all the keys.
+            assert_eq!(n, KEYS);
+        })
+    });
+}
+
+fn sets(c: &mut Criterion) {
+    let client = client();
+
+    c.bench_function("serial set 100 keys", |b| {
+        b.iter(|| {
+            block_on_all(client.get_async_connection().and_then(|con| {
+                futures::stream::iter_ok(0..SETS).fold(con, |con, i| {
+                    con.set(format!("set:{}", i), i)
+                        .map(|(con, ()): (_, ())| con)
+                })
+            }))
+            .unwrap();
+        })
+    });
+
+    c.bench_function("pipelined set 100 keys", |b| {
+        b.iter(|| {
+            block_on_all(client.get_async_connection().and_then(|con| {
+                let mut p = redis::pipe();
+                for i in 0..SETS {
+                    p.cmd("SET").arg(format!("set:{}", i)).arg(i).ignore();
+                }
+                p.query_async(con).map(|(con, ()): (_, ())| con)
+            }))
+            .unwrap();
+        })
+    });
+}
+
+fn pubsub(c: &mut Criterion) {
+    let client = client();
+
+    c.bench_function("pubsub 1000 messages", |b| {
+        b.iter(|| {
+            let mut received = 0;
+            let subscribe = client.get_async_connection().and_then(move |con| {
+                con.subscribe("bench", move |msg| {
+                    // Skip the messages probing the subscription.
+                    if msg.get_payload_bytes() == b"msg" {
+                        received += 1;
+                    }
+                    if received == MESSAGES {
+                        Ok(ControlFlow::Break(()))
+                    } else {
+                        Ok(ControlFlow::Continue)
+                    }
+                })
+            });
+
+            let publish = client.get_async_connection().and_then(|con| {
+                // Wait for the subscriber to be ready.
+                future::loop_fn(con, |con| {
+                    con.publish("bench", "probe").map(|(con, n): (_, usize)| {
+                        if n > 0 {
+                            future::Loop::Break(con)
+                        } else {
+                            future::Loop::Continue(con)
+                        }
+                    })
+                })
+                .and_then(|con| {
+                    let mut p = redis::pipe();
+                    for _ in 0..MESSAGES {
+                        p.cmd("PUBLISH").arg("bench").arg("msg").ignore();
+                    }
+                    p.query_async(con).map(|(_, ()): (_, ())| ())
+                })
+            });
+
+            let ((_, res), ()): ((_, Result<(), ()>), _) =
+                block_on_all(subscribe.join(publish)).unwrap();
+            res.unwrap();
+        })
+    });
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = scan, sets, pubsub
+}
+criterion_main!(benches);
@@ -27,7 +27,7 @@ fn main() {
     let f = client
         .get_async_connection()
         .and_then(move |con| {
-            let mut count = opt.count.clone();
+            let mut count = opt.count;
 
             let f = move |msg| {
                 println!("{:?}", msg);
@@ -19,12 +19,14 @@ fn main() {
     let f = client
         .get_shared_async_connection()
         .and_then(move |con| {
-            con.scan_match(opt.pattern)
-                .all()
-                .map(|(_, v): (_, Vec<String>)| v)
-        })
-        .map(|res| {
-            println!("{:?}", res);
+            // The shared connection doesn't need to be given back.
+            con.clone()
+                .scan_match(opt.pattern)
+                .items()
+                .for_each(|key: String| {
+                    println!("{}", key);
+                    Ok(())
+                })
         })
         .map_err(|e| println!("{}", e));
 
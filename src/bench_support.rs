@@ -0,0 +1,50 @@
+//! Helpers to seed test data for benchmarks and tests.
+//!
+//! This is enabled with the `bench-support` feature. The helpers use a blocking
+//! [`redis::Connection`][1] and pipeline the writes, so that a large data set
+//! can be prepared quickly before measuring the asynchronous commands.
+//!
+//! [1]: https://docs.rs/redis/0.13.0/redis/struct.Connection.html
+
+use redis::{Connection, RedisResult};
+
+/// The number of commands pipelined at once while seeding.
+const BATCH: usize = 1000;
+
+/// Removes all the keys of all the databases.
+pub fn flush(con: &mut Connection) -> RedisResult<()> {
+    redis::cmd("FLUSHALL").query(con)
+}
+
+/// Sets `n` string keys named `prefix:0` to `prefix:{n - 1}`, and returns the keys.
+///
+/// The value of each key is the same as its name.
+pub fn seed_keys(con: &mut Connection, prefix: &str, n: usize) -> RedisResult<Vec<String>> {
+    let keys: Vec<String> = (0..n).map(|i| format!("{}:{}", prefix, i)).collect();
+
+    for chunk in keys.chunks(BATCH) {
+        let mut p = redis::pipe();
+        for key in chunk {
+            p.cmd("SET").arg(key).arg(key).ignore();
+        }
+        p.query::<()>(con)?;
+    }
+
+    Ok(keys)
+}
+
+/// Sets `n` fields named `field:0` to `field:{n - 1}` in the hash at `key`,
+/// and returns the fields.
+pub fn seed_hash(con: &mut Connection, key: &str, n: usize) -> RedisResult<Vec<String>> {
+    let fields: Vec<String> = (0..n).map(|i| format!("field:{}", i)).collect();
+
+    for chunk in fields.chunks(BATCH) {
+        let mut p = redis::pipe();
+        for field in chunk {
+            p.cmd("HSET").arg(key).arg(field).arg(field).ignore();
+        }
+        p.query::<()>(con)?;
+    }
+
+    Ok(fields)
+}
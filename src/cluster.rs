@@ -0,0 +1,93 @@
+use redis::{ErrorKind, RedisError, RedisResult};
+use std::str::FromStr;
+
+/// A node of a cluster, parsed from a line of `CLUSTER NODES`.
+///
+/// This is returned by [`cluster_nodes`][1].
+///
+/// [1]: ./trait.Commands.html#method.cluster_nodes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClusterNode {
+    /// The node id.
+    pub id: String,
+    /// The address of the node as `ip:port`, without the cluster bus port.
+    pub addr: String,
+    /// The flags such as `myself`, `master`, `slave` and `fail`.
+    pub flags: Vec<String>,
+    /// The id of the master if the node is a replica.
+    pub master: Option<String>,
+    /// Whether the link to the node is connected.
+    pub connected: bool,
+    /// The ranges of the slots served by the node, both ends inclusive.
+    ///
+    /// Slots being imported or migrated are not included.
+    pub slots: Vec<(u16, u16)>,
+}
+
+impl ClusterNode {
+    /// Returns `true` if the node has the `master` flag.
+    pub fn is_master(&self) -> bool {
+        self.flags.iter().any(|f| f == "master")
+    }
+
+    /// Parses the whole reply of `CLUSTER NODES`.
+    pub(crate) fn parse_all(text: &str) -> RedisResult<Vec<Self>> {
+        text.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(str::parse)
+            .collect()
+    }
+}
+
+fn invalid(line: &str) -> RedisError {
+    (
+        ErrorKind::ResponseError,
+        "Invalid CLUSTER NODES line",
+        line.to_string(),
+    )
+        .into()
+}
+
+fn parse_slots(s: &str) -> Option<(u16, u16)> {
+    let mut range = s.splitn(2, '-');
+    let start = range.next()?.parse().ok()?;
+    let end = match range.next() {
+        Some(end) => end.parse().ok()?,
+        None => start,
+    };
+    Some((start, end))
+}
+
+impl FromStr for ClusterNode {
+    type Err = RedisError;
+
+    /// Parses a line of `CLUSTER NODES`, which is
+    /// `<id> <ip:port@cport[,hostname]> <flags> <master> <ping-sent> <pong-recv> <config-epoch> <link-state> <slot>...`.
+    fn from_str(line: &str) -> RedisResult<Self> {
+        let fields: Vec<_> = line.split_whitespace().collect();
+        if fields.len() < 8 {
+            return Err(invalid(line));
+        }
+
+        let addr = fields[1].split(['@', ',']).next().unwrap_or_default();
+        let master = match fields[3] {
+            "-" => None,
+            id => Some(id.to_string()),
+        };
+        let slots = fields[8..]
+            .iter()
+            // `[slot->-id]` and `[slot-<-id]` are slots being migrated or imported.
+            .filter(|s| !s.starts_with('['))
+            .map(|s| parse_slots(s).ok_or_else(|| invalid(line)))
+            .collect::<RedisResult<_>>()?;
+
+        Ok(Self {
+            id: fields[0].to_string(),
+            addr: addr.to_string(),
+            flags: fields[2].split(',').map(|f| f.to_string()).collect(),
+            master,
+            connected: fields[7] == "connected",
+            slots,
+        })
+    }
+}
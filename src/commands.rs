@@ -30,14 +30,47 @@
 // (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
 // OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use futures::{
+    future::{self, Either, Loop},
+    prelude::*,
+};
 use redis::aio::ConnectionLike;
-use redis::{cmd, FromRedisValue, NumericBehavior, RedisFuture, ToRedisArgs};
+use redis::{
+    cmd, FromRedisValue, InfoDict, NumericBehavior, RedisError, RedisFuture, ToRedisArgs, Value,
+};
 
 #[cfg(feature = "geospatial")]
 use redis::geo;
 
-use crate::stream::stream;
-pub use crate::stream::{RedisScanAll, RedisScanStream};
+use crate::cluster::ClusterNode;
+#[cfg(feature = "compression")]
+use crate::compression::Compression;
+use crate::consumer::RedisStreamConsumer;
+use crate::delete::PreparedDelete;
+use crate::future::CommandFuture;
+use crate::grid;
+use crate::hash::{FromRedisHash, RedisHash};
+use crate::info::ServerInfo;
+use crate::limits::{bulk_sum, ArgLimits};
+#[cfg(feature = "geospatial")]
+use crate::options::GeoSearchOptions;
+#[cfg(feature = "debug-commands")]
+use crate::options::ObjectMeta;
+use crate::options::{
+    BitFieldOps, BitPosOptions, ClientKillFilter, CopyOptions, Direction, ExpireOption,
+    GetExOptions, MinMax, RestoreOptions, SetOptions, SizeMetric, SortOptions, ZRangeOptions,
+    ZaddOptions,
+};
+use crate::pubsub::{self, Msg};
+use crate::scripts;
+use crate::snapshot::Record;
+use crate::stream::{stream, stream_then};
+pub use crate::stream::{RedisScanAll, RedisScanItems, RedisScanStream};
+use crate::value::{read_typed, RedisKeyType, Role, TypedValue};
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::io::{Read, Write};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 impl<T> Commands for T where T: ConnectionLike + Send + Sized + 'static {}
 
@@ -61,10 +94,19 @@ macro_rules! implement_commands {
             $(
                 $(#[$attr])*
                 #[inline]
-                fn $name<$($tyargs: $ty,)* RV: FromRedisValue+Send+'static>(self $(, $argname: $argty)*) -> RedisFuture<(Self, RV)>
-                    { ($body).query_async(self) }
+                fn $name<$($tyargs: $ty,)* RV: FromRedisValue+Send+'static>(self $(, $argname: $argty)*) -> CommandFuture<Self, RV>
+                    { CommandFuture::new(self, ($body).get_packed_command()) }
             )*
 
+            /// Consume the new entries of a stream as a consumer of a consumer group with `XREADGROUP`.
+            ///
+            /// Every read blocks up to `block` milliseconds. See
+            /// [`RedisStreamConsumer`](./struct.RedisStreamConsumer.html) for details.
+            #[inline]
+            fn xread_group_stream<K: ToRedisArgs, G: ToRedisArgs, N: ToRedisArgs>(self, key: K, group: G, consumer: N, block: usize) -> RedisStreamConsumer<Self> {
+                RedisStreamConsumer::new(self, key, group, consumer, block)
+            }
+
             /// Incrementally iterate the keys space.
             #[inline]
             fn scan<RV: FromRedisValue+Send+'static>(self) -> RedisScanStream<Self, RV> {
@@ -75,6 +117,19 @@ macro_rules! implement_commands {
                 })
             }
 
+            /// Incrementally iterate the keys space.
+            ///
+            /// `count` is passed as the `COUNT` hint of every iteration,
+            /// which saves round trips on a large collection.
+            #[inline]
+            fn scan_count<RV: FromRedisValue+Send+'static>(self, count: usize) -> RedisScanStream<Self, RV> {
+                stream(self, move |cur| {
+                    let mut c = cmd("SCAN");
+                    c.arg(cur).arg("COUNT").arg(count);
+                    c
+                })
+            }
+
             /// Incrementally iterate the keys space for keys matching a pattern.
             #[inline]
             fn scan_match<P: ToRedisArgs+Clone+Send+'static, RV: FromRedisValue+Send+'static>(self, pattern: P) -> RedisScanStream<Self, RV> {
@@ -87,7 +142,330 @@ macro_rules! implement_commands {
                 })
             }
 
+            /// Incrementally iterate the keys space for keys matching a pattern.
+            ///
+            /// `count` is passed as the `COUNT` hint of every iteration,
+            /// which saves round trips on a large collection.
+            #[inline]
+            fn scan_match_count<P: ToRedisArgs+Clone+Send+'static, RV: FromRedisValue+Send+'static>(self, pattern: P, count: usize) -> RedisScanStream<Self, RV> {
+                stream(self, move |cur| {
+                    let mut c = cmd("SCAN");
+                    c.arg(cur).arg("MATCH").arg(pattern.clone()).arg("COUNT").arg(count);
+                    c
+                })
+            }
+
+            /// Incrementally iterate the keys space for keys of a type
+            /// such as `"hash"`, with `SCAN ... TYPE` of Redis 6.0.
+            ///
+            /// Keys are filtered by the server, so keys of the other types aren't sent back.
+            #[inline]
+            fn scan_type<T: ToRedisArgs+Clone+Send+'static, RV: FromRedisValue+Send+'static>(self, ty: T) -> RedisScanStream<Self, RV> {
+                stream(self, move |cur| {
+                    let mut c = cmd("SCAN");
+                    c.arg(cur)
+                        .arg("TYPE")
+                        .arg(ty.clone());
+                    c
+                })
+            }
+
+            /// Incrementally iterate the keys space for keys of a type matching a pattern,
+            /// with `SCAN ... TYPE` of Redis 6.0.
+            #[inline]
+            fn scan_match_type<P: ToRedisArgs+Clone+Send+'static, T: ToRedisArgs+Clone+Send+'static, RV: FromRedisValue+Send+'static>
+                    (self, pattern: P, ty: T) -> RedisScanStream<Self, RV> {
+                stream(self, move |cur| {
+                    let mut c = cmd("SCAN");
+                    c.arg(cur)
+                        .arg("MATCH")
+                        .arg(pattern.clone())
+                        .arg("TYPE")
+                        .arg(ty.clone());
+                    c
+                })
+            }
+
+            /// Collect all the keys matching a pattern by scanning the keys space,
+            /// and return them sorted.
+            ///
+            /// Keys returned more than once by the scan are deduplicated, so that the
+            /// result is stable regardless of the scan order, e.g. to compare in tests.
+            #[inline]
+            fn scan_sorted<P: ToRedisArgs+Clone+Send+'static>(self, pattern: P) -> RedisFuture<(Self, Vec<String>)> {
+                Box::new(self.scan_match(pattern).all().map(|(con, mut keys): (_, Vec<String>)| {
+                    keys.sort();
+                    keys.dedup();
+                    (con, keys)
+                }))
+            }
+
+            /// Count the keys matching a pattern by scanning the keys space.
+            ///
+            /// Unlike `DBSIZE`, only the keys matching the pattern are counted.
+            /// Note that this takes O(N) as it iterates over the whole keys space
+            /// although only the matching keys are sent back from the server.
+            #[inline]
+            fn count_matching<P: ToRedisArgs+Clone+Send+'static>(self, pattern: P) -> RedisFuture<(Self, usize)> {
+                self.scan_match::<P, Value>(pattern).count()
+            }
+
+            /// Prepare deleting the keys matching a pattern, counting them by scanning the keys space.
+            ///
+            /// The keys are deleted once the count is confirmed and
+            /// [`PreparedDelete::commit`](./struct.PreparedDelete.html#method.commit) is called.
+            #[inline]
+            fn prepare_delete<P: ToRedisArgs>(self, pattern: P) -> RedisFuture<PreparedDelete<Self>> {
+                PreparedDelete::prepare(self, pattern.to_redis_args())
+            }
+
+            /// Sum up `BITCOUNT` of the keys matching a pattern by scanning the keys space.
+            ///
+            /// `BITCOUNT` of each batch of the scanned keys is pipelined.
+            /// All the matching keys must be strings, otherwise it fails.
+            #[inline]
+            fn bitcount_scan<P: ToRedisArgs+Clone+Send+'static>(self, pattern: P) -> RedisFuture<(Self, u64)> {
+                let counts: RedisScanStream<Self, u64> = stream_then(self, move |cur| {
+                    let mut c = cmd("SCAN");
+                    c.arg(cur)
+                        .arg("MATCH")
+                        .arg(pattern.clone());
+                    c
+                }, |con, keys: Vec<Vec<u8>>| {
+                    let mut p = redis::pipe();
+                    for key in keys {
+                        p.cmd("BITCOUNT").arg(key);
+                    }
+                    p.query_async(con)
+                });
+
+                counts.fold_items(0, |n, count| Ok(n + count))
+            }
+
+            /// Incrementally iterate the keys matching a pattern which have no expiration.
+            ///
+            /// `TTL` of each batch of the scanned keys is pipelined, and only the keys
+            /// whose `TTL` is `-1` are returned. This is useful to find keys leaking memory.
+            #[inline]
+            fn scan_without_ttl<P: ToRedisArgs+Clone+Send+'static>(self, pattern: P) -> RedisScanStream<Self, String> {
+                stream_then(self, move |cur| {
+                    let mut c = cmd("SCAN");
+                    c.arg(cur)
+                        .arg("MATCH")
+                        .arg(pattern.clone());
+                    c
+                }, |con, keys: Vec<String>| {
+                    let mut p = redis::pipe();
+                    for key in &keys {
+                        p.cmd("TTL").arg(key);
+                    }
+                    Box::new(p.query_async(con).map(move |(con, ttls): (_, Vec<i64>)| {
+                        let keys = keys
+                            .into_iter()
+                            .zip(ttls)
+                            .filter(|(_, ttl)| *ttl == -1)
+                            .map(|(key, _)| key)
+                            .collect();
+                        (con, keys)
+                    }))
+                })
+            }
+
+            /// Cap the TTLs of the keys matching a pattern to `max_seconds`, and return
+            /// the number of keys adjusted.
+            ///
+            /// `TTL` of each batch of the scanned keys is pipelined, and `EXPIRE` is
+            /// pipelined for the keys whose TTL exceeds the cap or which have no TTL.
+            /// This isn't atomic; a TTL changed between `TTL` and `EXPIRE` is overwritten.
+            ///
+            /// The future fails with `InvalidClientConfig` if `max_seconds` is zero,
+            /// since `EXPIRE` with zero seconds deletes the keys.
+            #[inline]
+            fn cap_ttls<P: ToRedisArgs+Clone+Send+'static>(self, pattern: P, max_seconds: usize) -> RedisFuture<(Self, usize)> {
+                if max_seconds == 0 {
+                    return Box::new(future::err(RedisError::from((
+                        redis::ErrorKind::InvalidClientConfig,
+                        "TTL cap must be positive",
+                    ))));
+                }
+
+                let counts: RedisScanStream<Self, usize> = stream_then(self, move |cur| {
+                    let mut c = cmd("SCAN");
+                    c.arg(cur)
+                        .arg("MATCH")
+                        .arg(pattern.clone());
+                    c
+                }, move |con, keys: Vec<Vec<u8>>| {
+                    let mut p = redis::pipe();
+                    for key in &keys {
+                        p.cmd("TTL").arg(&key[..]);
+                    }
+                    Box::new(p.query_async(con).and_then(move |(con, ttls): (_, Vec<i64>)| {
+                        let mut p = redis::pipe();
+                        let mut n = 0;
+                        for (key, ttl) in keys.into_iter().zip(ttls) {
+                            // `-2` means the key is gone.
+                            if ttl == -1 || ttl > max_seconds as i64 {
+                                p.cmd("EXPIRE").arg(key).arg(max_seconds).ignore();
+                                n += 1;
+                            }
+                        }
+
+                        if n == 0 {
+                            Either::A(Ok((con, vec![0])).into_future())
+                        } else {
+                            Either::B(p.query_async(con).map(move |(con, ()): (_, ())| (con, vec![n])))
+                        }
+                    }))
+                });
+
+                counts.fold_items(0, |n, count| Ok(n + count))
+            }
+
+            /// Make a histogram of the memory usage of the keys matching a pattern.
+            ///
+            /// `buckets` are the ascending upper bounds of the buckets in bytes, both
+            /// inclusive. Returns the number of keys in each bucket, followed by the
+            /// number of keys larger than the last bound. `MEMORY USAGE` of each batch
+            /// of the scanned keys is pipelined, which requires Redis 4.0 or later.
+            /// Use [`size_histogram_by`](#method.size_histogram_by) with
+            /// [`SizeMetric::Strlen`](./enum.SizeMetric.html) on servers without it.
+            /// Keys removed while scanning are skipped.
+            #[inline]
+            fn size_histogram<P: ToRedisArgs+Clone+Send+'static>(self, pattern: P, buckets: &[usize]) -> RedisFuture<(Self, Vec<usize>)> {
+                self.size_histogram_by(pattern, buckets, SizeMetric::MemoryUsage)
+            }
+
+            /// Make a histogram of the sizes of the keys matching a pattern like
+            /// [`size_histogram`](#method.size_histogram), measured by `metric`.
+            ///
+            /// The command of the metric is pipelined for each batch of the scanned keys.
+            #[inline]
+            fn size_histogram_by<P: ToRedisArgs+Clone+Send+'static>(self, pattern: P, buckets: &[usize], metric: SizeMetric) -> RedisFuture<(Self, Vec<usize>)> {
+                let buckets = buckets.to_vec();
+                let sizes: RedisScanStream<Self, usize> = stream_then(self, move |cur| {
+                    let mut c = cmd("SCAN");
+                    c.arg(cur)
+                        .arg("MATCH")
+                        .arg(pattern.clone());
+                    c
+                }, move |con, keys: Vec<Vec<u8>>| {
+                    let mut p = redis::pipe();
+                    for key in keys {
+                        match metric {
+                            SizeMetric::MemoryUsage => p.cmd("MEMORY").arg("USAGE").arg(key),
+                            SizeMetric::Strlen => p.cmd("STRLEN").arg(key),
+                        };
+                    }
+                    Box::new(p.query_async(con).map(|(con, sizes): (_, Vec<Option<usize>>)| {
+                        (con, sizes.into_iter().flatten().collect())
+                    }))
+                });
+
+                let counts = vec![0; buckets.len() + 1];
+
+                sizes.fold_items(counts, move |mut counts, size| {
+                    let i = buckets.iter().position(|&b| size <= b).unwrap_or(buckets.len());
+                    counts[i] += 1;
+                    Ok(counts)
+                })
+            }
+
+            /// Delete the hashes matching a pattern whose `expires_at` field is past `now`.
+            ///
+            /// This is for logical expiration managed by applications. Hashes are scanned
+            /// with `SCAN ... TYPE hash`, which requires Redis 6.0 or later. `HGET` of
+            /// `expires_at` is pipelined for each batch, and the expired hashes are
+            /// removed with `UNLINK`. Hashes without `expires_at` are kept. Returns the
+            /// number of removed hashes.
+            ///
+            /// This isn't atomic; a hash updated between `HGET` and `UNLINK` is removed
+            /// as well.
+            #[inline]
+            fn sweep_expired<P: ToRedisArgs+Clone+Send+'static>(self, pattern: P, now: u64) -> RedisFuture<(Self, usize)> {
+                let counts: RedisScanStream<Self, usize> = stream_then(self, move |cur| {
+                    let mut c = cmd("SCAN");
+                    c.arg(cur)
+                        .arg("MATCH")
+                        .arg(pattern.clone())
+                        .arg("TYPE")
+                        .arg("hash");
+                    c
+                }, move |con, keys: Vec<Vec<u8>>| {
+                    let mut p = redis::pipe();
+                    for key in &keys {
+                        p.cmd("HGET").arg(&key[..]).arg("expires_at");
+                    }
+                    Box::new(p.query_async(con).and_then(move |(con, expiry): (_, Vec<Option<u64>>)| {
+                        let expired: Vec<_> = keys
+                            .into_iter()
+                            .zip(expiry)
+                            .filter(|(_, at)| at.map(|at| at < now).unwrap_or(false))
+                            .map(|(key, _)| key)
+                            .collect();
+
+                        if expired.is_empty() {
+                            Either::A(Ok((con, vec![0])).into_future())
+                        } else {
+                            Either::B(cmd("UNLINK").arg(expired).query_async(con)
+                                .map(|(con, n): (_, usize)| (con, vec![n])))
+                        }
+                    }))
+                });
+
+                counts.fold_items(0, |n, count| Ok(n + count))
+            }
+
+            /// Incrementally iterate the keys matching a pattern together with their values.
+            ///
+            /// `TYPE` of each batch of the scanned keys is pipelined, and then the
+            /// commands to read the values of the types are pipelined. Keys removed
+            /// while scanning are skipped.
+            #[inline]
+            fn scan_typed<P: ToRedisArgs+Clone+Send+'static>(self, pattern: P) -> RedisScanStream<Self, (String, TypedValue)> {
+                stream_then(self, move |cur| {
+                    let mut c = cmd("SCAN");
+                    c.arg(cur)
+                        .arg("MATCH")
+                        .arg(pattern.clone());
+                    c
+                }, read_typed)
+            }
+
+            /// Incrementally iterate the keys matching a pattern together with their types.
+            ///
+            /// `SCAN ... TYPE` only filters the keys by a type, so `TYPE` of each batch
+            /// of the scanned keys is pipelined instead, which costs one more round trip
+            /// per batch. Keys removed while scanning are skipped.
+            #[inline]
+            fn scan_key_types<P: ToRedisArgs+Clone+Send+'static>(self, pattern: P) -> RedisScanStream<Self, (String, RedisKeyType)> {
+                stream_then(self, move |cur| {
+                    let mut c = cmd("SCAN");
+                    c.arg(cur)
+                        .arg("MATCH")
+                        .arg(pattern.clone());
+                    c
+                }, |con, keys: Vec<String>| {
+                    let mut p = redis::pipe();
+                    for key in &keys {
+                        p.cmd("TYPE").arg(key);
+                    }
+                    Box::new(p.query_async(con).map(move |(con, types): (_, Vec<RedisKeyType>)| {
+                        let keys = keys
+                            .into_iter()
+                            .zip(types)
+                            .filter(|(_, ty)| *ty != RedisKeyType::None)
+                            .collect();
+                        (con, keys)
+                    }))
+                })
+            }
+
             /// Incrementally iterate hash fields and associated values.
+            ///
+            /// Every reply of `HSCAN` holds whole pairs of a field and its value, so
+            /// pairs such as `(String, String)` never straddle batches. A reply which
+            /// can't be framed into pairs fails the stream rather than misaligning them.
             #[inline]
             fn hscan<K: ToRedisArgs+Clone+Send+'static, RV: FromRedisValue+Send+'static>(self, key: K) -> RedisScanStream<Self, RV> {
                 stream(self, move |cur| {
@@ -97,8 +475,25 @@ macro_rules! implement_commands {
                 })
             }
 
+            /// Incrementally iterate hash fields and associated values.
+            ///
+            /// `count` is passed as the `COUNT` hint of every iteration,
+            /// which saves round trips on a large collection.
+            #[inline]
+            fn hscan_count<K: ToRedisArgs+Clone+Send+'static, RV: FromRedisValue+Send+'static>(self, key: K, count: usize) -> RedisScanStream<Self, RV> {
+                stream(self, move |cur| {
+                    let mut c = cmd("HSCAN");
+                    c.arg(key.clone()).arg(cur).arg("COUNT").arg(count);
+                    c
+                })
+            }
+
             /// Incrementally iterate hash fields and associated values for
             /// field names matching a pattern.
+            ///
+            /// Every reply of `HSCAN` holds whole pairs of a field and its value, so
+            /// pairs such as `(String, String)` never straddle batches. A reply which
+            /// can't be framed into pairs fails the stream rather than misaligning them.
             #[inline]
             fn hscan_match<K: ToRedisArgs+Clone+Send+'static, P: ToRedisArgs+Clone+Send+'static, RV: FromRedisValue+Send+'static>
                     (self, key: K, pattern: P) -> RedisScanStream<Self, RV> {
@@ -109,6 +504,21 @@ macro_rules! implement_commands {
                 })
             }
 
+            /// Incrementally iterate hash fields and associated values for
+            /// field names matching a pattern.
+            ///
+            /// `count` is passed as the `COUNT` hint of every iteration,
+            /// which saves round trips on a large collection.
+            #[inline]
+            fn hscan_match_count<K: ToRedisArgs+Clone+Send+'static, P: ToRedisArgs+Clone+Send+'static, RV: FromRedisValue+Send+'static>
+                    (self, key: K, pattern: P, count: usize) -> RedisScanStream<Self, RV> {
+                stream(self, move |cur| {
+                    let mut c = cmd("HSCAN");
+                    c.arg(key.clone()).arg(cur).arg("MATCH").arg(pattern.clone()).arg("COUNT").arg(count);
+                    c
+                })
+            }
+
             /// Incrementally iterate set elements.
             #[inline]
             fn sscan<K: ToRedisArgs+Clone+Send+'static, RV: FromRedisValue+Send+'static>(self, key: K) -> RedisScanStream<Self, RV> {
@@ -119,6 +529,19 @@ macro_rules! implement_commands {
                 })
             }
 
+            /// Incrementally iterate set elements.
+            ///
+            /// `count` is passed as the `COUNT` hint of every iteration,
+            /// which saves round trips on a large collection.
+            #[inline]
+            fn sscan_count<K: ToRedisArgs+Clone+Send+'static, RV: FromRedisValue+Send+'static>(self, key: K, count: usize) -> RedisScanStream<Self, RV> {
+                stream(self, move |cur| {
+                    let mut c = cmd("SSCAN");
+                    c.arg(key.clone()).arg(cur).arg("COUNT").arg(count);
+                    c
+                })
+            }
+
             /// Incrementally iterate set elements for elements matching a pattern.
             #[inline]
             fn sscan_match<K: ToRedisArgs+Clone+Send+'static, P: ToRedisArgs+Clone+Send+'static, RV: FromRedisValue+Send+'static>
@@ -130,7 +553,56 @@ macro_rules! implement_commands {
                 })
             }
 
+            /// Incrementally iterate set elements for elements matching a pattern.
+            ///
+            /// `count` is passed as the `COUNT` hint of every iteration,
+            /// which saves round trips on a large collection.
+            #[inline]
+            fn sscan_match_count<K: ToRedisArgs+Clone+Send+'static, P: ToRedisArgs+Clone+Send+'static, RV: FromRedisValue+Send+'static>
+                    (self, key: K, pattern: P, count: usize) -> RedisScanStream<Self, RV> {
+                stream(self, move |cur| {
+                    let mut c = cmd("SSCAN");
+                    c.arg(key.clone()).arg(cur).arg("MATCH").arg(pattern.clone()).arg("COUNT").arg(count);
+                    c
+                })
+            }
+
+            /// Incrementally iterate the members of a set whose keys don't exist.
+            ///
+            /// The key of a member is the member prefixed by `key_prefix`. `EXISTS` of
+            /// the keys of each batch of the scanned members is pipelined, and only the
+            /// members whose keys are missing are returned. This is useful to check the
+            /// consistency of an index.
+            #[inline]
+            fn scan_orphans<K: ToRedisArgs+Clone+Send+'static, P: ToRedisArgs>(self, set_key: K, key_prefix: P) -> RedisScanStream<Self, String> {
+                let prefix = key_prefix.to_redis_args().concat();
+
+                stream_then(self, move |cur| {
+                    let mut c = cmd("SSCAN");
+                    c.arg(set_key.clone()).arg(cur);
+                    c
+                }, move |con, members: Vec<String>| {
+                    let mut p = redis::pipe();
+                    for member in &members {
+                        p.cmd("EXISTS").arg([&prefix[..], member.as_bytes()].concat());
+                    }
+                    Box::new(p.query_async(con).map(move |(con, exists): (_, Vec<bool>)| {
+                        let orphans = members
+                            .into_iter()
+                            .zip(exists)
+                            .filter(|(_, exists)| !exists)
+                            .map(|(member, _)| member)
+                            .collect();
+                        (con, orphans)
+                    }))
+                })
+            }
+
             /// Incrementally iterate sorted set elements.
+            ///
+            /// Every reply of `ZSCAN` holds whole pairs of a member and its score, so
+            /// pairs such as `(String, f64)` never straddle batches. A reply which
+            /// can't be framed into pairs fails the stream rather than misaligning them.
             #[inline]
             fn zscan<K: ToRedisArgs+Clone+Send+'static, RV: FromRedisValue+Send+'static>(self, key: K) -> RedisScanStream<Self, RV> {
                 stream(self, move |cur| {
@@ -140,7 +612,24 @@ macro_rules! implement_commands {
                 })
             }
 
+            /// Incrementally iterate sorted set elements.
+            ///
+            /// `count` is passed as the `COUNT` hint of every iteration,
+            /// which saves round trips on a large collection.
+            #[inline]
+            fn zscan_count<K: ToRedisArgs+Clone+Send+'static, RV: FromRedisValue+Send+'static>(self, key: K, count: usize) -> RedisScanStream<Self, RV> {
+                stream(self, move |cur| {
+                    let mut c = cmd("ZSCAN");
+                    c.arg(key.clone()).arg(cur).arg("COUNT").arg(count);
+                    c
+                })
+            }
+
             /// Incrementally iterate sorted set elements for elements matching a pattern.
+            ///
+            /// Every reply of `ZSCAN` holds whole pairs of a member and its score, so
+            /// pairs such as `(String, f64)` never straddle batches. A reply which
+            /// can't be framed into pairs fails the stream rather than misaligning them.
             #[inline]
             fn zscan_match<K: ToRedisArgs+Clone+Send+'static, P: ToRedisArgs+Clone+Send+'static, RV: FromRedisValue+Send+'static>
                     (self, key: K, pattern: P) -> RedisScanStream<Self, RV> {
@@ -150,6 +639,726 @@ macro_rules! implement_commands {
                     c
                 })
             }
+
+            /// Incrementally iterate sorted set elements for elements matching a pattern.
+            ///
+            /// `count` is passed as the `COUNT` hint of every iteration,
+            /// which saves round trips on a large collection.
+            #[inline]
+            fn zscan_match_count<K: ToRedisArgs+Clone+Send+'static, P: ToRedisArgs+Clone+Send+'static, RV: FromRedisValue+Send+'static>
+                    (self, key: K, pattern: P, count: usize) -> RedisScanStream<Self, RV> {
+                stream(self, move |cur| {
+                    let mut c = cmd("ZSCAN");
+                    c.arg(key.clone()).arg(cur).arg("MATCH").arg(pattern.clone()).arg("COUNT").arg(count);
+                    c
+                })
+            }
+
+            // explicitly typed variants
+
+            /// Get all the fields of a hash with `HGETALL`, and convert them into `T`.
+            ///
+            /// The future fails if the conversion fails, e.g. a required field is missing.
+            #[inline]
+            fn hget_struct<K: ToRedisArgs, T: FromRedisHash+Send+'static>(self, key: K) -> RedisFuture<(Self, T)> {
+                Box::new(cmd("HGETALL").arg(key).query_async(self).and_then(|(con, hash): (Self, RedisHash)| {
+                    Ok((con, T::from_redis_hash(&hash)?))
+                }))
+            }
+
+            /// Add one or more members to a set and return the number of members added.
+            ///
+            /// Members which already exist in the set are not counted, so re-adding an
+            /// existing member returns 0.
+            #[inline]
+            fn sadd_count<K: ToRedisArgs, M: ToRedisArgs>(self, key: K, member: M) -> CommandFuture<Self, usize> {
+                self.sadd(key, member)
+            }
+
+            /// Remove one or more members from a set and return the number of members removed.
+            ///
+            /// Members which don't exist in the set are not counted.
+            #[inline]
+            fn srem_count<K: ToRedisArgs, M: ToRedisArgs>(self, key: K, member: M) -> CommandFuture<Self, usize> {
+                self.srem(key, member)
+            }
+
+            /// Subtract multiple sets, store the resulting set in a key and
+            /// return the number of members in it.
+            #[inline]
+            fn sdiffstore_count<K: ToRedisArgs>(self, dstkey: K, keys: K) -> CommandFuture<Self, usize> {
+                self.sdiffstore(dstkey, keys)
+            }
+
+            /// Intersect multiple sets, store the resulting set in a key and
+            /// return the number of members in it.
+            #[inline]
+            fn sinterstore_count<K: ToRedisArgs>(self, dstkey: K, keys: K) -> CommandFuture<Self, usize> {
+                self.sinterstore(dstkey, keys)
+            }
+
+            /// Add multiple sets, store the resulting set in a key and
+            /// return the number of members in it.
+            #[inline]
+            fn sunionstore_count<K: ToRedisArgs>(self, dstkey: K, keys: K) -> CommandFuture<Self, usize> {
+                self.sunionstore(dstkey, keys)
+            }
+
+            /// Add a member to the bucket of the grid cell containing a point, and return the key of the bucket.
+            ///
+            /// The cell is a geohash of `precision` characters, from 1 to 12, and the bucket
+            /// is a set at `{prefix}:{geohash}` to which the member is added by `SADD`.
+            /// Nearby points share a bucket at a low precision, which is a lightweight
+            /// alternative to the geospatial commands. Note that points close to the
+            /// border of a cell may fall into different buckets at any precision.
+            #[inline]
+            fn grid_add<M: ToRedisArgs>(self, prefix: &str, lat: f64, lon: f64, member: M, precision: u8) -> RedisFuture<(Self, String)> {
+                let key = format!("{}:{}", prefix, grid::geohash(lat, lon, precision));
+                Box::new(cmd("SADD").arg(&key).arg(member).query_async(self)
+                    .map(move |(con, _): (Self, usize)| (con, key)))
+            }
+
+            /// Set the string value of a hash field, and return whether the field is newly created.
+            #[inline]
+            fn hset_created<K: ToRedisArgs, F: ToRedisArgs, V: ToRedisArgs>(self, key: K, field: F, value: V) -> CommandFuture<Self, bool> {
+                self.hset(key, field, value)
+            }
+
+            /// Get all the members in a set as a `HashSet`.
+            #[inline]
+            fn smembers_set<K: ToRedisArgs, M: FromRedisValue+Eq+Hash+Send+'static>(self, key: K) -> CommandFuture<Self, HashSet<M>> {
+                self.smembers(key)
+            }
+
+            /// Remove and get the first element in a list, or block until one is available.
+            ///
+            /// Returns the key popped from and the element. `None` means the timeout
+            /// expired with all the lists empty, so it doesn't need to be told apart
+            /// from an actual nil reply. Errors such as connection issues still fail
+            /// the future.
+            #[inline]
+            fn blpop_typed<K: ToRedisArgs>(self, key: K, timeout: usize) -> CommandFuture<Self, Option<(String, String)>> {
+                self.blpop(key, timeout)
+            }
+
+            /// Remove and get the last element in a list, or block until one is available.
+            ///
+            /// Returns the key popped from and the element. `None` means the timeout
+            /// expired with all the lists empty, as in [`blpop_typed`](#method.blpop_typed).
+            #[inline]
+            fn brpop_typed<K: ToRedisArgs>(self, key: K, timeout: usize) -> CommandFuture<Self, Option<(String, String)>> {
+                self.brpop(key, timeout)
+            }
+
+            /// Insert all the specified values at the head of the list stored at key,
+            /// and return the length of the list after the push.
+            #[inline]
+            fn lpush_len<K: ToRedisArgs, V: ToRedisArgs>(self, key: K, value: V) -> CommandFuture<Self, usize> {
+                self.lpush(key, value)
+            }
+
+            /// Insert all the specified values at the tail of the list stored at key,
+            /// and return the length of the list after the push.
+            #[inline]
+            fn rpush_len<K: ToRedisArgs, V: ToRedisArgs>(self, key: K, value: V) -> CommandFuture<Self, usize> {
+                self.rpush(key, value)
+            }
+
+            /// Insert the value at the head of the list stored at key, trim the list
+            /// to its first `max_len` elements, and return the length of the list after that.
+            ///
+            /// `LPUSH`, `LTRIM` and `LLEN` are pipelined in a transaction, so that the list
+            /// keeps the newest `max_len` values. A zero `max_len` empties the list.
+            #[inline]
+            fn lpush_capped<K: ToRedisArgs, V: ToRedisArgs>(self, key: K, value: V, max_len: usize) -> RedisFuture<(Self, usize)> {
+                let key = key.to_redis_args();
+                // `LTRIM key 0 -1` would keep everything.
+                let (start, stop) = if max_len == 0 { (1, 0) } else { (0, max_len as isize - 1) };
+
+                let mut p = redis::pipe();
+                p.atomic()
+                    .cmd("LPUSH").arg(&key[..]).arg(value).ignore()
+                    .cmd("LTRIM").arg(&key[..]).arg(start).arg(stop).ignore()
+                    .cmd("LLEN").arg(&key[..]);
+
+                Box::new(p.query_async(self).map(|(con, (len,)): (_, (usize,))| (con, len)))
+            }
+
+            /// Remove occurrences of elements equal to value from the list stored at key,
+            /// and return the number of removed elements.
+            ///
+            /// A positive `count` removes up to `count` elements from the head to the tail,
+            /// a negative one removes up to `-count` elements from the tail to the head,
+            /// and zero removes all the elements equal to value.
+            #[inline]
+            fn lrem_count<K: ToRedisArgs, V: ToRedisArgs>(self, key: K, count: isize, value: V) -> CommandFuture<Self, usize> {
+                self.lrem(key, count, value)
+            }
+
+            /// Move the last element of a list to its head with `LMOVE`, and return the element.
+            ///
+            /// Returns `None` if the list is empty. This requires Redis 6.2 or later.
+            #[inline]
+            fn lrotate<K: ToRedisArgs>(self, key: K) -> RedisFuture<(Self, Option<String>)> {
+                let key = key.to_redis_args();
+                cmd("LMOVE")
+                    .arg(&key[..]).arg(&key[..]).arg("RIGHT").arg("LEFT")
+                    .query_async(self)
+            }
+
+            /// Return the positions of the specified members of the geospatial index.
+            ///
+            /// The result is aligned with the given members. Members which don't
+            /// exist in the index are returned as `None`.
+            #[cfg(feature = "geospatial")]
+            #[inline]
+            fn geo_pos_typed<K: ToRedisArgs, M: ToRedisArgs>(self, key: K, members: M) -> CommandFuture<Self, Vec<Option<geo::Coord<f64>>>> {
+                self.geo_pos(key, members)
+            }
+
+            /// Post a message to the given channel, and return whether it's received
+            /// by at least `min` subscribers.
+            ///
+            /// The number of subscribers is the one reported by `PUBLISH`, so the
+            /// caller can retry or give up if nobody is listening.
+            #[inline]
+            fn publish_min_subscribers<K: ToRedisArgs, E: ToRedisArgs>(self, channel: K, message: E, min: usize) -> RedisFuture<(Self, bool)> {
+                Box::new(
+                    self.publish(channel, message)
+                        .map(move |(con, n): (_, usize)| (con, n >= min)),
+                )
+            }
+
+            /// Post a request to a channel, and wait for a reply on another channel.
+            ///
+            /// A connection in the pub/sub mode can't publish, so this uses two
+            /// connections: a new connection is opened from `client` to subscribe to
+            /// `reply_channel`, and then the request is published with this connection.
+            /// The first message on the reply channel is returned, or `None` if no
+            /// reply comes within `timeout`. The subscribing connection is closed in
+            /// both cases, which unsubscribes it without waiting for the server.
+            #[inline]
+            fn request_reply<Q: ToRedisArgs, P: ToRedisArgs, R: ToRedisArgs>(self, client: &redis::Client, request_channel: Q, payload: P, reply_channel: R, timeout: Duration) -> RedisFuture<(Self, Option<Msg>)> {
+                pubsub::request_reply(self, client, request_channel, payload, reply_channel, timeout)
+            }
+
+            // bulk helpers
+
+            /// Add many members to a set, and return the number of members added.
+            ///
+            /// The members are split into multiple `SADD`s within the default
+            /// [`ArgLimits`](./struct.ArgLimits.html), which are sent one by one.
+            #[inline]
+            fn sadd_bulk<K: ToRedisArgs, M: ToRedisArgs>(self, key: K, members: &[M]) -> RedisFuture<(Self, usize)> {
+                self.sadd_bulk_with(key, members, &ArgLimits::default())
+            }
+
+            /// Add many members to a set like [`sadd_bulk`](#method.sadd_bulk),
+            /// splitting them within the given limits.
+            #[inline]
+            fn sadd_bulk_with<K: ToRedisArgs, M: ToRedisArgs>(self, key: K, members: &[M], limits: &ArgLimits) -> RedisFuture<(Self, usize)> {
+                bulk_sum(self, "SADD", Some(key), members, limits)
+            }
+
+            /// Increment many counters by their deltas in a pipeline,
+            /// and return the new values in order.
+            ///
+            /// Every counter has its own `INCRBY`, so this isn't split by
+            /// [`ArgLimits`](./struct.ArgLimits.html).
+            #[inline]
+            fn incr_many<K: ToRedisArgs>(self, deltas: &[(K, i64)]) -> RedisFuture<(Self, Vec<i64>)> {
+                if deltas.is_empty() {
+                    return Box::new(future::ok((self, Vec::new())));
+                }
+
+                let mut p = redis::pipe();
+                for (key, delta) in deltas {
+                    p.cmd("INCRBY").arg(key.to_redis_args()).arg(*delta);
+                }
+                p.query_async(self)
+            }
+
+            /// Delete many keys, and return the number of keys deleted.
+            ///
+            /// The keys are split into multiple `DEL`s within the default
+            /// [`ArgLimits`](./struct.ArgLimits.html), which are sent one by one.
+            #[inline]
+            fn del_bulk<K: ToRedisArgs>(self, keys: &[K]) -> RedisFuture<(Self, usize)> {
+                self.del_bulk_with(keys, &ArgLimits::default())
+            }
+
+            /// Delete many keys like [`del_bulk`](#method.del_bulk),
+            /// splitting them within the given limits.
+            #[inline]
+            fn del_bulk_with<K: ToRedisArgs>(self, keys: &[K], limits: &ArgLimits) -> RedisFuture<(Self, usize)> {
+                bulk_sum(self, "DEL", None::<&str>, keys, limits)
+            }
+
+            /// Get many values by `MGET`, computing the missing ones and caching them with the TTL.
+            ///
+            /// `compute` is called once with the keys missing in the cache, in the order of
+            /// `keys`, and must resolve to their values in the same order. The computed values
+            /// are `SET` with `ttl` seconds in a pipeline. `compute` isn't called if all the keys
+            /// are cached. Returns the values of all the keys in order. The future fails with
+            /// `ResponseError` if `compute` returns a wrong number of values.
+            #[inline]
+            fn mget_or_compute<K, V, F, Fut>(self, keys: &[K], ttl: usize, compute: F) -> RedisFuture<(Self, Vec<V>)>
+            where
+                K: ToRedisArgs+Clone+Send+'static,
+                V: FromRedisValue+ToRedisArgs+Send+'static,
+                F: FnOnce(Vec<K>) -> Fut+Send+'static,
+                Fut: IntoFuture<Item = Vec<V>, Error = RedisError>,
+                Fut::Future: Send+'static,
+            {
+                if keys.is_empty() {
+                    return Box::new(future::ok((self, Vec::new())));
+                }
+                let keys = keys.to_vec();
+
+                Box::new(cmd("MGET").arg(&keys[..]).query_async(self).and_then(
+                    move |(con, cached): (Self, Vec<Option<V>>)| {
+                        let missing: Vec<K> = keys
+                            .iter()
+                            .zip(&cached)
+                            .filter(|(_, v)| v.is_none())
+                            .map(|(k, _)| k.clone())
+                            .collect();
+                        if missing.is_empty() {
+                            let values = cached.into_iter().map(Option::unwrap).collect();
+                            return Either::A(future::ok((con, values)));
+                        }
+
+                        Either::B(compute(missing.clone()).into_future().and_then(move |computed| {
+                            if computed.len() != missing.len() {
+                                return Either::A(future::err(RedisError::from((
+                                    redis::ErrorKind::ResponseError,
+                                    "Wrong number of computed values",
+                                ))));
+                            }
+
+                            let mut p = redis::pipe();
+                            for (key, value) in missing.iter().zip(&computed) {
+                                p.cmd("SET").arg(key.to_redis_args()).arg(value.to_redis_args())
+                                    .arg("EX").arg(ttl).ignore();
+                            }
+
+                            Either::B(p.query_async(con).map(move |(con, ()): (Self, ())| {
+                                let mut computed = computed.into_iter();
+                                let values = cached
+                                    .into_iter()
+                                    .map(|v| v.or_else(|| computed.next()).unwrap())
+                                    .collect();
+                                (con, values)
+                            }))
+                        }))
+                    },
+                ))
+            }
+
+            /// Return the remaining time to live of a key with `TTL`.
+            ///
+            /// Returns `None` if the key doesn't exist or has no timeout.
+            #[inline]
+            fn ttl_duration<K: ToRedisArgs>(self, key: K) -> RedisFuture<(Self, Option<Duration>)> {
+                Box::new(self.ttl(key).map(|(con, ttl): (_, i64)| {
+                    (con, if ttl < 0 { None } else { Some(Duration::from_secs(ttl as u64)) })
+                }))
+            }
+
+            /// Return the remaining time to live of a key with `PTTL`.
+            ///
+            /// Returns `None` if the key doesn't exist or has no timeout.
+            #[inline]
+            fn pttl_duration<K: ToRedisArgs>(self, key: K) -> RedisFuture<(Self, Option<Duration>)> {
+                Box::new(self.pttl(key).map(|(con, ttl): (_, i64)| {
+                    (con, if ttl < 0 { None } else { Some(Duration::from_millis(ttl as u64)) })
+                }))
+            }
+
+            /// Return the current time of the server with `TIME`.
+            #[inline]
+            fn time(self) -> RedisFuture<(Self, SystemTime)> {
+                Box::new(cmd("TIME").query_async(self).map(|(con, (secs, micros)): (_, (u64, u64))| {
+                    (con, UNIX_EPOCH + Duration::from_secs(secs) + Duration::from_micros(micros))
+                }))
+            }
+
+            /// Set a value together with the current time of the server, for [`get_with_age`](#method.get_with_age).
+            ///
+            /// The value is stored in a hash at `key` with the fields `value` and
+            /// `generated_at`, which holds the time from `TIME` in microseconds since
+            /// the Unix epoch. This is done atomically by a Lua script.
+            #[inline]
+            fn set_timestamped<K: ToRedisArgs, V: ToRedisArgs>(self, key: K, value: V) -> RedisFuture<(Self, ())> {
+                scripts::eval(scripts::SET_TIMESTAMPED, 1)
+                    .arg(key).arg(value)
+                    .query_async(self)
+            }
+
+            /// Get a value set by [`set_timestamped`](#method.set_timestamped) with
+            /// its age, computed with the current time of the server.
+            ///
+            /// `HMGET` and `TIME` are pipelined. Returns `None` if the key doesn't exist
+            /// or lacks either field. The age is zero if `generated_at` is in the future.
+            #[inline]
+            fn get_with_age<K: ToRedisArgs>(self, key: K) -> RedisFuture<(Self, Option<(String, Duration)>)> {
+                let mut p = redis::pipe();
+                p.cmd("HMGET").arg(key).arg("value").arg("generated_at")
+                    .cmd("TIME");
+
+                Box::new(p.query_async(self).map(
+                    |(con, ((value, generated_at), (secs, micros))): (_, ((Option<String>, Option<u64>), (u64, u64)))| {
+                        let now = secs * 1_000_000 + micros;
+                        let v = value.and_then(|v| {
+                            generated_at.map(|at| (v, Duration::from_micros(now.saturating_sub(at))))
+                        });
+                        (con, v)
+                    },
+                ))
+            }
+
+            /// Read a frame prefixed with its length from a string at `offset`.
+            ///
+            /// The length is a big-endian `u32` read by a `GETRANGE`, and then the frame
+            /// body is read by another `GETRANGE`. Returns the offset of the next frame
+            /// and the body. The future fails with `ResponseError` if the string ends
+            /// in the middle of the frame.
+            #[inline]
+            fn read_frame<K: ToRedisArgs>(self, key: K, offset: usize) -> RedisFuture<(Self, (usize, Vec<u8>))> {
+                let key = key.to_redis_args();
+                let start = offset + 4;
+
+                Box::new(
+                    cmd("GETRANGE").arg(&key[..]).arg(offset).arg(start - 1).query_async(self)
+                        .and_then(move |(con, len): (Self, Vec<u8>)| {
+                            if len.len() < 4 {
+                                return Either::A(future::err(RedisError::from((
+                                    redis::ErrorKind::ResponseError,
+                                    "Incomplete frame length",
+                                ))));
+                            }
+                            let len = u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize;
+                            if len == 0 {
+                                return Either::A(future::ok((con, (start, Vec::new()))));
+                            }
+
+                            Either::B(cmd("GETRANGE").arg(&key[..]).arg(start).arg(start + len - 1).query_async(con)
+                                .and_then(move |(con, body): (Self, Vec<u8>)| {
+                                    if body.len() < len {
+                                        return Err(RedisError::from((
+                                            redis::ErrorKind::ResponseError,
+                                            "Incomplete frame body",
+                                        )));
+                                    }
+                                    Ok((con, (start + len, body)))
+                                }))
+                        }),
+                )
+            }
+
+            /// Return the information and statistics of the server with `INFO`,
+            /// split into sections.
+            #[inline]
+            fn info_parsed(self) -> RedisFuture<(Self, ServerInfo)> {
+                cmd("INFO").query_async(self)
+            }
+
+            /// Return the replication role of the server with `ROLE`.
+            #[inline]
+            fn role(self) -> RedisFuture<(Self, Role)> {
+                cmd("ROLE").query_async(self)
+            }
+
+            /// Compress a value with the [`Compression`](./struct.Compression.html), and set it.
+            ///
+            /// This requires the `compression` feature.
+            #[cfg(feature = "compression")]
+            #[inline]
+            fn set_compressed<K: ToRedisArgs>(
+                self,
+                key: K,
+                value: &[u8],
+                compression: &Compression,
+            ) -> RedisFuture<(Self, ())> {
+                match compression.compress(value) {
+                    Ok(compressed) => Box::new(cmd("SET").arg(key).arg(compressed).query_async(self)),
+                    Err(e) => Box::new(future::err(e)),
+                }
+            }
+
+            /// Get a value set by [`set_compressed`](#method.set_compressed), and decompress it.
+            ///
+            /// The future fails with `TypeError` if the value isn't compressed with the codec
+            /// of the [`Compression`](./struct.Compression.html). Returns `None` if the key
+            /// doesn't exist. This requires the `compression` feature.
+            #[cfg(feature = "compression")]
+            #[inline]
+            fn get_compressed<K: ToRedisArgs>(
+                self,
+                key: K,
+                compression: &Compression,
+            ) -> RedisFuture<(Self, Option<Vec<u8>>)> {
+                let compression = *compression;
+                Box::new(cmd("GET").arg(key).query_async(self).and_then(
+                    move |(con, value): (Self, Option<Vec<u8>>)| match value {
+                        Some(value) => compression.decompress(&value).map(|v| (con, Some(v))),
+                        None => Ok((con, None)),
+                    },
+                ))
+            }
+
+            // cluster commands
+
+            /// Return the state of the cluster with `CLUSTER INFO`.
+            #[inline]
+            fn cluster_info(self) -> RedisFuture<(Self, InfoDict)> {
+                cmd("CLUSTER").arg("INFO").query_async(self)
+            }
+
+            /// Return the nodes of the cluster with `CLUSTER NODES`.
+            ///
+            /// The reply is parsed into [`ClusterNode`](./struct.ClusterNode.html)s,
+            /// and the future fails with `ResponseError` if it's malformed.
+            #[inline]
+            fn cluster_nodes(self) -> RedisFuture<(Self, Vec<ClusterNode>)> {
+                Box::new(cmd("CLUSTER").arg("NODES").query_async(self).and_then(|(con, text): (_, String)| {
+                    Ok((con, ClusterNode::parse_all(&text)?))
+                }))
+            }
+
+            /// Return the mapping of the slots to the nodes with `CLUSTER SLOTS`.
+            #[inline]
+            fn cluster_slots<RV: FromRedisValue+Send+'static>(self) -> RedisFuture<(Self, RV)> {
+                cmd("CLUSTER").arg("SLOTS").query_async(self)
+            }
+
+            // validated commands
+
+            /// Return the members of a geospatial index within the area given by
+            /// the options, with `GEOSEARCH` of Redis 6.2.
+            ///
+            /// The options are validated before sending the command, and the future
+            /// fails without querying if they are invalid.
+            /// See [`GeoSearchOptions::validate`](./struct.GeoSearchOptions.html#method.validate).
+            #[cfg(feature = "geospatial")]
+            #[inline]
+            fn geo_search<K: ToRedisArgs, RV: FromRedisValue+Send+'static>(self, key: K, options: GeoSearchOptions) -> RedisFuture<(Self, RV)> {
+                match options.validate() {
+                    Ok(()) => cmd("GEOSEARCH").arg(key).arg(options).query_async(self),
+                    Err(e) => Box::new(futures::future::err(e)),
+                }
+            }
+
+            /// Return the `k` nearest members to a member of a geospatial index with
+            /// their distances, excluding the member itself.
+            ///
+            /// The members are ordered from the nearest. This issues `GEOSEARCH` of
+            /// Redis 6.2 with `FROMMEMBER`, `ASC`, `COUNT` and `WITHDIST`, searching
+            /// the whole earth.
+            #[cfg(feature = "geospatial")]
+            #[inline]
+            fn geo_nearest<K: ToRedisArgs, M: ToRedisArgs>(self, key: K, member: M, k: usize, unit: geo::Unit) -> RedisFuture<(Self, Vec<(String, f64)>)> {
+                let origin = member.to_redis_args().concat();
+                let options = GeoSearchOptions::default()
+                    .from_member(&origin[..])
+                    // Wide enough to cover the earth in any unit.
+                    .by_box(1e9, 1e9, unit)
+                    .order(geo::RadiusOrder::Asc)
+                    // One more for the member itself.
+                    .count(k + 1)
+                    .with_dist();
+
+                Box::new(self.geo_search(key, options).map(move |(con, res): (_, Vec<(String, f64)>)| {
+                    let res = res
+                        .into_iter()
+                        .filter(|(name, _)| name.as_bytes() != &origin[..])
+                        .take(k)
+                        .collect();
+                    (con, res)
+                }))
+            }
+
+            // atomic helpers
+
+            /// Increment a field of a hash and return all the fields and values of the hash.
+            ///
+            /// This is done atomically by a Lua script, so the returned map always
+            /// reflects the increment. Like [`hincr`](#method.hincr), this issues
+            /// `HINCRBYFLOAT` if the delta is a float.
+            #[inline]
+            fn hincr_and_getall<K: ToRedisArgs, F: ToRedisArgs, D: ToRedisArgs>(self, key: K, field: F, delta: D) -> RedisFuture<(Self, HashMap<String, String>)> {
+                let incr = if delta.describe_numeric_behavior() == NumericBehavior::NumberIsFloat {
+                    "HINCRBYFLOAT"
+                } else {
+                    "HINCRBY"
+                };
+                scripts::eval(scripts::HINCR_AND_GETALL, 1)
+                    .arg(key).arg(incr).arg(field).arg(delta)
+                    .query_async(self)
+            }
+
+            /// Append a value to a key, and trim it to the last `max_bytes` bytes.
+            ///
+            /// This is done atomically by a Lua script. Returns the length of the
+            /// string after trimming. The expiration of the key is kept.
+            #[inline]
+            fn append_capped<K: ToRedisArgs, V: ToRedisArgs>(self, key: K, value: V, max_bytes: usize) -> RedisFuture<(Self, usize)> {
+                scripts::eval(scripts::APPEND_CAPPED, 1)
+                    .arg(key).arg(value).arg(max_bytes)
+                    .query_async(self)
+            }
+
+            /// Increment the score of a member in a sorted set, and remove the members
+            /// with the lowest scores so that the set has at most `max_size` members.
+            ///
+            /// This is done atomically by a Lua script. Returns the new score of the
+            /// member, even if the member itself is removed for having the lowest score.
+            #[inline]
+            fn zincr_capped<K: ToRedisArgs, M: ToRedisArgs, D: ToRedisArgs>(self, key: K, member: M, delta: D, max_size: usize) -> RedisFuture<(Self, f64)> {
+                scripts::eval(scripts::ZINCR_CAPPED, 1)
+                    .arg(key).arg(member).arg(delta).arg(max_size)
+                    .query_async(self)
+            }
+
+            /// Update the score of a member of a sorted set only if the new score is
+            /// greater, and return the rank of the member from the highest score.
+            ///
+            /// This is done atomically by a Lua script with `ZADD XX GT` and `ZREVRANK`,
+            /// which requires Redis 6.2 or later. A member which isn't in the set isn't
+            /// added, and `None` is returned for it.
+            #[inline]
+            fn zadd_gt_rank<K: ToRedisArgs, M: ToRedisArgs, S: ToRedisArgs>(self, key: K, member: M, score: S) -> RedisFuture<(Self, Option<usize>)> {
+                scripts::eval(scripts::ZADD_GT_RANK, 1)
+                    .arg(key).arg(member).arg(score)
+                    .query_async(self)
+            }
+
+            /// Increment a sequence counter, and add a member to a sorted set with the
+            /// new value of the counter as its score.
+            ///
+            /// This is done atomically by a Lua script, so the members are ordered
+            /// without gaps even if added concurrently. Returns the assigned sequence.
+            /// If the member already exists, its score is updated to the new sequence.
+            #[inline]
+            fn zadd_next<K: ToRedisArgs, Z: ToRedisArgs, M: ToRedisArgs>(self, seq_key: K, zset_key: Z, member: M) -> RedisFuture<(Self, i64)> {
+                scripts::eval(scripts::ZADD_NEXT, 2)
+                    .arg(seq_key).arg(zset_key).arg(member)
+                    .query_async(self)
+            }
+
+            /// Take tokens from a token bucket, and return whether there were enough tokens.
+            ///
+            /// The bucket is a hash at `key` holding the number of tokens and the time of
+            /// the last refill. It starts full with `capacity` tokens, and is refilled by
+            /// `rate` tokens per second up to `capacity` based on `TIME` of the server.
+            /// No token is taken if there are fewer than `tokens`. This is done atomically
+            /// by a Lua script. The key expires once the bucket would be full again.
+            #[inline]
+            fn token_bucket<K: ToRedisArgs>(self, key: K, rate: f64, capacity: f64, tokens: f64) -> RedisFuture<(Self, bool)> {
+                scripts::eval(scripts::TOKEN_BUCKET, 1)
+                    .arg(key).arg(rate).arg(capacity).arg(tokens)
+                    .query_async(self)
+            }
+
+            /// Pick a member of a sorted set at random, with probability proportional to its score.
+            ///
+            /// Members with zero or negative scores are never picked. Returns `None` if
+            /// there's no member to pick. This runs a Lua script which reads the whole
+            /// sorted set, so it takes O(N) on the server.
+            #[inline]
+            fn zrandmember_weighted<K: ToRedisArgs>(self, key: K) -> RedisFuture<(Self, Option<String>)> {
+                scripts::eval(scripts::ZRANDMEMBER_WEIGHTED, 1)
+                    .arg(key).arg(scripts::random_fraction())
+                    .query_async(self)
+            }
+
+            /// Set the string value of a key, seeding its idle time or access frequency.
+            ///
+            /// This is useful to warm a cache with the eviction metadata of a backup.
+            /// The value is set and then restored with `IDLETIME` or `FREQ` within a
+            /// Lua script. The idle time only takes effect with an LRU `maxmemory-policy`
+            /// and the frequency only with an LFU one.
+            #[cfg(feature = "debug-commands")]
+            #[inline]
+            fn set_with_meta<K: ToRedisArgs, V: ToRedisArgs>(self, key: K, value: V, meta: ObjectMeta) -> RedisFuture<(Self, ())> {
+                scripts::eval(scripts::SET_WITH_META, 1)
+                    .arg(key).arg(value).arg(meta)
+                    .query_async(self)
+            }
+
+            // snapshot helpers
+
+            /// Write all the keys to a writer, and return the number of keys written.
+            ///
+            /// The keys are scanned, and `PTTL` and `DUMP` of each batch of them are
+            /// pipelined. Every key is written as a record of the name, the remaining TTL
+            /// and the serialized value, which can be replayed by
+            /// [`restore_from`](#method.restore_from). Note that keys modified during
+            /// the scan may or may not be in the snapshot, as `SCAN` doesn't take an
+            /// atomic view of the database.
+            #[inline]
+            fn snapshot_to<W: Write+Send+'static>(self, writer: W) -> RedisFuture<(Self, usize)> {
+                let records: RedisScanStream<Self, Record> = stream_then(self, |cur| {
+                    let mut c = cmd("SCAN");
+                    c.arg(cur);
+                    c
+                }, |con, keys: Vec<Vec<u8>>| {
+                    let mut p = redis::pipe();
+                    for key in &keys {
+                        p.cmd("PTTL").arg(&key[..]).cmd("DUMP").arg(&key[..]);
+                    }
+                    Box::new(p.query_async(con).map(move |(con, dumps): (_, Vec<(i64, Option<Vec<u8>>)>)| {
+                        let records = keys
+                            .into_iter()
+                            .zip(dumps)
+                            .filter_map(|(key, (ttl, payload))| {
+                                // Skip the keys removed while scanning. `0` means no
+                                // expiration in the record, so a key about to expire keeps 1ms.
+                                let ttl = match ttl {
+                                    -1 => 0,
+                                    0 => 1,
+                                    t => t,
+                                };
+                                payload.map(|payload| Record { key, ttl, payload })
+                            })
+                            .collect();
+                        (con, records)
+                    }))
+                });
+
+                Box::new(
+                    records
+                        .fold_items((writer, 0), |(mut writer, n), record| {
+                            record.write(&mut writer)?;
+                            Ok((writer, n + 1))
+                        })
+                        .and_then(|(con, (mut writer, n))| {
+                            writer.flush()?;
+                            Ok((con, n))
+                        }),
+                )
+            }
+
+            /// Restore the keys written by [`snapshot_to`](#method.snapshot_to),
+            /// and return the number of keys restored.
+            ///
+            /// Existing keys are replaced. The TTL of every key is counted from the time
+            /// it's restored.
+            #[inline]
+            fn restore_from<R: Read+Send+'static>(self, reader: R) -> RedisFuture<(Self, usize)> {
+                Box::new(future::loop_fn((self, reader, 0), |(con, mut reader, n)| {
+                    match Record::read(&mut reader) {
+                        Ok(Some(record)) => Either::A(
+                            cmd("RESTORE")
+                                .arg(record.key).arg(record.ttl).arg(record.payload).arg("REPLACE")
+                                .query_async(con)
+                                .map(move |(con, ()): (_, ())| Loop::Continue((con, reader, n + 1))),
+                        ),
+                        Ok(None) => Either::B(future::ok(Loop::Break((con, n)))),
+                        Err(e) => Either::B(future::err(e.into())),
+                    }
+                }))
+            }
         }
     )
 }
@@ -207,6 +1416,14 @@ implement_commands! {
         cmd("SET").arg(key).arg(value)
     }
 
+    /// Set the string value of a key with the options such as `NX` and `PX`.
+    ///
+    /// With [`SetOptions::get`](./struct.SetOptions.html#method.get), the reply
+    /// is the old value and needs to be parsed as `Option<RV>`.
+    fn set_options<K: ToRedisArgs, V: ToRedisArgs>(key: K, value: V, options: SetOptions) {
+        cmd("SET").arg(key).arg(value).arg(options)
+    }
+
     /// Sets multiple keys to their values.
     fn set_multiple<K: ToRedisArgs, V: ToRedisArgs>(items: &[(K, V)]) {
         cmd("MSET").arg(items)
@@ -232,6 +1449,22 @@ implement_commands! {
         cmd("GETSET").arg(key).arg(value)
     }
 
+    /// Get the value of a key and delete the key.
+    ///
+    /// Returns nil if the key doesn't exist, so the reply can be parsed as `Option<RV>`.
+    /// This requires Redis 6.2 or later.
+    fn getdel<K: ToRedisArgs>(key: K) {
+        cmd("GETDEL").arg(key)
+    }
+
+    /// Get the value of a key and change its timeout.
+    ///
+    /// Returns nil if the key doesn't exist, so the reply can be parsed as `Option<RV>`.
+    /// This requires Redis 6.2 or later.
+    fn getex<K: ToRedisArgs>(key: K, options: GetExOptions) {
+        cmd("GETEX").arg(key).arg(options)
+    }
+
     /// Get a range of bytes/substring from the value of a key. Negative values provide an offset from the end of the value.
     fn getrange<K: ToRedisArgs>(key: K, from: isize, to: isize) {
         cmd("GETRANGE").arg(key).arg(from).arg(to)
@@ -247,6 +1480,74 @@ implement_commands! {
         cmd("DEL").arg(key)
     }
 
+    /// Copy the value of a key to another key, only if the destination doesn't exist.
+    ///
+    /// Returns whether the key was copied, which can be parsed as `bool`.
+    /// This requires Redis 6.2 or later.
+    fn copy<S: ToRedisArgs, D: ToRedisArgs>(src: S, dst: D) {
+        cmd("COPY").arg(src).arg(dst)
+    }
+
+    /// Copy the value of a key to another key with the options such as `REPLACE`.
+    ///
+    /// Returns whether the key was copied, which can be parsed as `bool`.
+    /// This requires Redis 6.2 or later.
+    fn copy_options<S: ToRedisArgs, D: ToRedisArgs>(src: S, dst: D, options: CopyOptions) {
+        cmd("COPY").arg(src).arg(dst).arg(options)
+    }
+
+    /// Sort the elements of a list, a set or a sorted set.
+    fn sort<K: ToRedisArgs>(key: K, options: SortOptions) {
+        cmd("SORT").arg(key).arg(options)
+    }
+
+    /// Sort the elements of a list, a set or a sorted set without storing the
+    /// result, which is allowed on read-only replicas.
+    ///
+    /// This requires Redis 7.0 or later.
+    fn sort_ro<K: ToRedisArgs>(key: K, options: SortOptions) {
+        cmd("SORT_RO").arg(key).arg(options)
+    }
+
+    /// Delete one or more keys, reclaiming the memory in the background.
+    ///
+    /// Unlike `DEL`, this doesn't block the server on large values.
+    fn unlink<K: ToRedisArgs>(key: K) {
+        cmd("UNLINK").arg(key)
+    }
+
+    /// Update the last access time of one or more keys.
+    ///
+    /// Returns the number of the keys which exist.
+    fn touch<K: ToRedisArgs>(key: K) {
+        cmd("TOUCH").arg(key)
+    }
+
+    /// Return a random key, or nil if the database is empty.
+    fn randomkey<>() {
+        cmd("RANDOMKEY")
+    }
+
+    /// Move a key to another database, only if it doesn't exist there.
+    ///
+    /// Returns whether the key was moved, which can be parsed as `bool`.
+    fn move_key<K: ToRedisArgs>(key: K, db: i64) {
+        cmd("MOVE").arg(key).arg(db)
+    }
+
+    /// Serialize the value of a key in the format of `RESTORE`.
+    ///
+    /// Returns nil if the key doesn't exist, so the reply can be parsed as `Option<Vec<u8>>`.
+    fn dump<K: ToRedisArgs>(key: K) {
+        cmd("DUMP").arg(key)
+    }
+
+    /// Create a key from the value serialized by [`dump`](#method.dump), with
+    /// the TTL in milliseconds, or no TTL if it's 0.
+    fn restore<K: ToRedisArgs>(key: K, ttl_ms: usize, serialized: &[u8], options: RestoreOptions) {
+        cmd("RESTORE").arg(key).arg(ttl_ms).arg(serialized).arg(options)
+    }
+
     /// Determine if a key exists.
     fn exists<K: ToRedisArgs>(key: K) {
         cmd("EXISTS").arg(key)
@@ -272,6 +1573,28 @@ implement_commands! {
         cmd("PEXPIREAT").arg(key).arg(ts)
     }
 
+    /// Set a key's time to live in seconds, only if the condition is met.
+    ///
+    /// Returns whether the timeout was set, which can be parsed as `bool`.
+    fn expire_opts<K: ToRedisArgs>(key: K, seconds: usize, option: ExpireOption) {
+        cmd("EXPIRE").arg(key).arg(seconds).arg(option)
+    }
+
+    /// Set the expiration for a key as a UNIX timestamp, only if the condition is met.
+    fn expire_at_opts<K: ToRedisArgs>(key: K, ts: usize, option: ExpireOption) {
+        cmd("EXPIREAT").arg(key).arg(ts).arg(option)
+    }
+
+    /// Set a key's time to live in milliseconds, only if the condition is met.
+    fn pexpire_opts<K: ToRedisArgs>(key: K, ms: usize, option: ExpireOption) {
+        cmd("PEXPIRE").arg(key).arg(ms).arg(option)
+    }
+
+    /// Set the expiration for a key as a UNIX timestamp in milliseconds, only if the condition is met.
+    fn pexpire_at_opts<K: ToRedisArgs>(key: K, ts: usize, option: ExpireOption) {
+        cmd("PEXPIREAT").arg(key).arg(ts).arg(option)
+    }
+
     /// Remove the expiration from a key.
     fn persist<K: ToRedisArgs>(key: K) {
         cmd("PERSIST").arg(key)
@@ -282,6 +1605,55 @@ implement_commands! {
         cmd("TTL").arg(key)
     }
 
+    /// Check the expiration time of a key in milliseconds.
+    ///
+    /// Returns `-2` if the key doesn't exist, and `-1` if it has no timeout.
+    fn pttl<K: ToRedisArgs>(key: K) {
+        cmd("PTTL").arg(key)
+    }
+
+    /// Determine the type stored at key.
+    /// The reply can be parsed into [`RedisKeyType`](./enum.RedisKeyType.html).
+    fn type_of<K: ToRedisArgs>(key: K) {
+        cmd("TYPE").arg(key)
+    }
+
+    /// Return the internal encoding of the value of a key such as `listpack`,
+    /// which can be parsed as `String`.
+    fn object_encoding<K: ToRedisArgs>(key: K) {
+        cmd("OBJECT").arg("ENCODING").arg(key)
+    }
+
+    /// Return the number of references to the value of a key.
+    fn object_refcount<K: ToRedisArgs>(key: K) {
+        cmd("OBJECT").arg("REFCOUNT").arg(key)
+    }
+
+    /// Return the seconds since the value of a key was last accessed.
+    ///
+    /// This fails if an LFU eviction policy is in use.
+    fn object_idletime<K: ToRedisArgs>(key: K) {
+        cmd("OBJECT").arg("IDLETIME").arg(key)
+    }
+
+    /// Return the access frequency counter of the value of a key.
+    ///
+    /// This fails unless an LFU eviction policy is in use.
+    fn object_freq<K: ToRedisArgs>(key: K) {
+        cmd("OBJECT").arg("FREQ").arg(key)
+    }
+
+    /// Watch keys to abort the following transaction if they are modified.
+    /// See [`Transaction`](./struct.Transaction.html).
+    fn watch<K: ToRedisArgs>(key: K) {
+        cmd("WATCH").arg(key)
+    }
+
+    /// Forget about all the watched keys.
+    fn unwatch<>() {
+        cmd("UNWATCH")
+    }
+
     /// Rename a key.
     fn rename<K: ToRedisArgs>(key: K, new_key: K) {
         cmd("RENAME").arg(key).arg(new_key)
@@ -329,6 +1701,20 @@ implement_commands! {
         cmd("BITCOUNT").arg(key).arg(start).arg(end)
     }
 
+    /// Return the position of the first bit set to the given value in a string.
+    ///
+    /// Returns `-1` if no such bit is found in the range.
+    fn bitpos<K: ToRedisArgs>(key: K, bit: bool, options: BitPosOptions) {
+        cmd("BITPOS").arg(key).arg(if bit {1} else {0}).arg(options)
+    }
+
+    /// Get, set and increment integers of arbitrary widths in a string.
+    ///
+    /// See [`BitFieldOps`](./struct.BitFieldOps.html) for the reply.
+    fn bitfield<K: ToRedisArgs>(key: K, ops: BitFieldOps) {
+        cmd("BITFIELD").arg(key).arg(ops)
+    }
+
     /// Perform a bitwise AND between multiple keys (containing string values)
     /// and store the result in the destination key.
     fn bit_and<K: ToRedisArgs>(dstkey: K, srckeys: K) {
@@ -518,6 +1904,27 @@ implement_commands! {
         cmd("RPUSHX").arg(key).arg(value)
     }
 
+    /// Pop up to `count` elements from the first non-empty list of the keys.
+    ///
+    /// Returns the key popped from and the elements, or nil if all the lists are
+    /// empty, so the reply can be parsed as `Option<(String, Vec<RV>)>`.
+    /// This requires Redis 7.0 or later.
+    fn lmpop<K: ToRedisArgs>(keys: &[K], dir: Direction, count: usize) {
+        cmd("LMPOP").arg(keys.len()).arg(keys).arg(dir).arg("COUNT").arg(count)
+    }
+
+    /// Pop up to `count` elements from the first non-empty list of the keys,
+    /// or block until one is available.
+    ///
+    /// Returns nil if the timeout in seconds expires, otherwise the same reply
+    /// as [`lmpop`](#method.lmpop). A timeout of 0 blocks forever.
+    ///
+    /// This blocks the connection, so use a dedicated connection rather than
+    /// one shared by other requests such as `SharedConnection`.
+    fn blmpop<K: ToRedisArgs>(timeout: usize, keys: &[K], dir: Direction, count: usize) {
+        cmd("BLMPOP").arg(timeout).arg(keys.len()).arg(keys).arg(dir).arg("COUNT").arg(count)
+    }
+
     // set commands
 
     /// Add one or more members to a set.
@@ -555,6 +1962,14 @@ implement_commands! {
         cmd("SISMEMBER").arg(key).arg(member)
     }
 
+    /// Determine if each of the given values is a member of a set.
+    ///
+    /// The reply can be parsed as `Vec<bool>` in the order of the members.
+    /// This requires Redis 6.2 or later.
+    fn smismember<K: ToRedisArgs, M: ToRedisArgs>(key: K, members: M) {
+        cmd("SMISMEMBER").arg(key).arg(members)
+    }
+
     /// Get all the members in a set.
     fn smembers<K: ToRedisArgs>(key: K) {
         cmd("SMEMBERS").arg(key)
@@ -602,6 +2017,13 @@ implement_commands! {
         cmd("ZADD").arg(key).arg(score).arg(member)
     }
 
+    /// Add one member to a sorted set, or update its score, with the options such as `GT`.
+    ///
+    /// See [`ZaddOptions`](./struct.ZaddOptions.html) for the reply.
+    fn zadd_options<K: ToRedisArgs, S: ToRedisArgs, M: ToRedisArgs>(key: K, member: M, score: S, options: ZaddOptions) {
+        cmd("ZADD").arg(key).arg(options).arg(score).arg(member)
+    }
+
     /// Add multiple members to a sorted set, or update its score if it already exists.
     fn zadd_multiple<K: ToRedisArgs, S: ToRedisArgs, M: ToRedisArgs>(key: K, items: &[(S, M)]) {
         cmd("ZADD").arg(key).arg(items)
@@ -623,6 +2045,13 @@ implement_commands! {
         cmd("ZINCRBY").arg(key).arg(delta).arg(member)
     }
 
+    /// Subtract multiple sorted sets and store the resulting sorted set in a new key.
+    ///
+    /// Returns the number of members in the resulting sorted set.
+    fn zdiffstore<K: ToRedisArgs>(dstkey: K, keys: &[K]) {
+        cmd("ZDIFFSTORE").arg(dstkey).arg(keys.len()).arg(keys)
+    }
+
     /// Intersect multiple sorted sets and store the resulting sorted set in
     /// a new key using SUM as aggregation function.
     fn zinterstore<K: ToRedisArgs>(dstkey: K, keys: &[K]) {
@@ -656,6 +2085,15 @@ implement_commands! {
         cmd("ZRANGE").arg(key).arg(start).arg(stop).arg("WITHSCORES")
     }
 
+    /// Return a range of members in a sorted set, by index, score or member
+    /// according to the options, with the unified `ZRANGE` of Redis 6.2.
+    ///
+    /// The other `zrange*` and `zrevrange*` methods aren't wrappers of this, as
+    /// `BYSCORE`, `BYLEX` and `REV` aren't supported by Redis before 6.2.
+    fn zrange_generic<K: ToRedisArgs, M: ToRedisArgs, MM: ToRedisArgs>(key: K, min: M, max: MM, options: ZRangeOptions) {
+        cmd("ZRANGE").arg(key).arg(min).arg(max).arg(options)
+    }
+
     /// Return a range of members in a sorted set, by lexicographical range.
     fn zrangebylex<K: ToRedisArgs, M: ToRedisArgs, MM: ToRedisArgs>(key: K, min: M, max: MM) {
         cmd("ZRANGEBYLEX").arg(key).arg(min).arg(max)
@@ -791,6 +2229,28 @@ implement_commands! {
         cmd("ZUNIONSTORE").arg(dstkey).arg(keys.len()).arg(keys).arg("AGGREGATE").arg("MAX")
     }
 
+    /// Pop up to `count` members from the first non-empty sorted set of the keys.
+    ///
+    /// Returns the key popped from and the members, or nil if all the sorted sets
+    /// are empty. Each member comes with its score as a nested pair, so the reply
+    /// can be parsed as `Option<(String, Vec<Vec<String>>)>`.
+    /// This requires Redis 7.0 or later.
+    fn zmpop<K: ToRedisArgs>(keys: &[K], dir: MinMax, count: usize) {
+        cmd("ZMPOP").arg(keys.len()).arg(keys).arg(dir).arg("COUNT").arg(count)
+    }
+
+    /// Pop up to `count` members from the first non-empty sorted set of the keys,
+    /// or block until one is available.
+    ///
+    /// Returns nil if the timeout in seconds expires, otherwise the same reply
+    /// as [`zmpop`](#method.zmpop). A timeout of 0 blocks forever.
+    ///
+    /// This blocks the connection, so use a dedicated connection rather than
+    /// one shared by other requests such as `SharedConnection`.
+    fn bzmpop<K: ToRedisArgs>(timeout: usize, keys: &[K], dir: MinMax, count: usize) {
+        cmd("BZMPOP").arg(timeout).arg(keys.len()).arg(keys).arg(dir).arg("COUNT").arg(count)
+    }
+
     // hyperloglog commands
 
     /// Adds the specified elements to the specified HyperLogLog.
@@ -814,6 +2274,15 @@ implement_commands! {
         cmd("PUBLISH").arg(channel).arg(message)
     }
 
+    /// Posts a message to the given shard channel, and return the number of
+    /// clients receiving it.
+    ///
+    /// The message is delivered only within the shard of the channel in a cluster.
+    /// This requires Redis 7.0 or later.
+    fn spublish<K: ToRedisArgs, E: ToRedisArgs>(channel: K, message: E) {
+        cmd("SPUBLISH").arg(channel).arg(message)
+    }
+
     // geospatial commands
 
     /// Adds the specified geospatial items to the specified key.
@@ -1006,4 +2475,163 @@ implement_commands! {
             .arg(options)
     }
 
+    // stream commands
+
+    /// Append an entry with the fields to the stream, and return the ID of the entry.
+    ///
+    /// Pass `"*"` as `id` to let the server generate the ID.
+    /// The entries can be read as [`StreamEntry`](./struct.StreamEntry.html).
+    fn xadd<K: ToRedisArgs, I: ToRedisArgs, F: ToRedisArgs, V: ToRedisArgs>(key: K, id: I, fields: &[(F, V)]) {
+        cmd("XADD").arg(key).arg(id).arg(fields)
+    }
+
+    /// Return the number of entries in the stream.
+    fn xlen<K: ToRedisArgs>(key: K) {
+        cmd("XLEN").arg(key)
+    }
+
+    /// Return the entries of the stream with IDs between `start` and `end`, inclusive.
+    ///
+    /// `"-"` and `"+"` stand for the minimum and the maximum IDs respectively.
+    fn xrange<K: ToRedisArgs, S: ToRedisArgs, E: ToRedisArgs>(key: K, start: S, end: E) {
+        cmd("XRANGE").arg(key).arg(start).arg(end)
+    }
+
+    /// Read up to `count` entries with IDs greater than `ids` from each of the streams
+    /// with `XREAD`, blocking up to `block` milliseconds until any entry is available.
+    ///
+    /// `ids` correspond to `keys` one by one. `"$"` stands for the last ID of the stream,
+    /// so that only new entries are read. The reply can be read as a list of
+    /// [`StreamKey`](./struct.StreamKey.html), which is empty if the timeout expired.
+    fn xread_block<K: ToRedisArgs, I: ToRedisArgs>(keys: &[K], ids: &[I], block: usize, count: usize) {
+        cmd("XREAD").arg("BLOCK").arg(block).arg("COUNT").arg(count).arg("STREAMS").arg(keys).arg(ids)
+    }
+
+    /// Create a consumer group of the stream with `XGROUP CREATE`, creating the stream if it doesn't exist.
+    ///
+    /// `id` is the last ID delivered to the group; `"$"` means only new entries are delivered.
+    fn xgroup_create<K: ToRedisArgs, G: ToRedisArgs, I: ToRedisArgs>(key: K, group: G, id: I) {
+        cmd("XGROUP").arg("CREATE").arg(key).arg(group).arg(id).arg("MKSTREAM")
+    }
+
+    /// Acknowledge the entries of the stream to the consumer group,
+    /// and return the number of entries acknowledged.
+    fn xack<K: ToRedisArgs, G: ToRedisArgs, I: ToRedisArgs>(key: K, group: G, ids: I) {
+        cmd("XACK").arg(key).arg(group).arg(ids)
+    }
+
+    // scripting commands
+
+    /// Execute a Lua script with `EVAL`.
+    ///
+    /// `numkeys` is the number of `keys`, which are followed by `args`.
+    /// See [`Script`](./struct.Script.html) to cache the script on the server.
+    fn eval<S: ToRedisArgs, K: ToRedisArgs, A: ToRedisArgs>(script: S, numkeys: usize, keys: K, args: A) {
+        cmd("EVAL").arg(script).arg(numkeys).arg(keys).arg(args)
+    }
+
+    /// Execute a Lua script cached on the server by its SHA1 digest with `EVALSHA`.
+    fn evalsha<S: ToRedisArgs, K: ToRedisArgs, A: ToRedisArgs>(sha: S, numkeys: usize, keys: K, args: A) {
+        cmd("EVALSHA").arg(sha).arg(numkeys).arg(keys).arg(args)
+    }
+
+    /// Load a Lua script into the script cache, and return its SHA1 digest.
+    fn script_load<S: ToRedisArgs>(script: S) {
+        cmd("SCRIPT").arg("LOAD").arg(script)
+    }
+
+    // client commands
+
+    /// Close the client connections matching all the given filters.
+    ///
+    /// Returns the number of clients killed.
+    fn client_kill<>(filter: ClientKillFilter) {
+        cmd("CLIENT").arg("KILL").arg(filter)
+    }
+
+    /// Set the name of the current connection, which is shown in `CLIENT LIST`.
+    fn client_setname<N: ToRedisArgs>(name: N) {
+        cmd("CLIENT").arg("SETNAME").arg(name)
+    }
+
+    /// Return the name of the current connection, or nil if it isn't set.
+    fn client_getname<>() {
+        cmd("CLIENT").arg("GETNAME")
+    }
+
+    /// Return the unique ID of the current connection, which can be passed to
+    /// [`ClientKillFilter::id`](./struct.ClientKillFilter.html#method.id).
+    fn client_id<>() {
+        cmd("CLIENT").arg("ID")
+    }
+
+    /// Exclude the current connection from the client eviction, or include it again.
+    ///
+    /// This requires Redis 7.0 or later.
+    fn client_no_evict<>(on: bool) {
+        cmd("CLIENT").arg("NO-EVICT").arg(if on {"ON"} else {"OFF"})
+    }
+
+    // server commands
+
+    /// Return the information and statistics of the server as the raw text of `INFO`.
+    fn info<>() {
+        cmd("INFO")
+    }
+
+    /// Return the given section of `INFO` such as `memory` as the raw text.
+    fn info_section<S: ToRedisArgs>(section: S) {
+        cmd("INFO").arg(section)
+    }
+
+    /// Return the number of keys in the current database.
+    fn dbsize<>() {
+        cmd("DBSIZE")
+    }
+
+    /// Delete all the keys of the current database.
+    fn flushdb<>() {
+        cmd("FLUSHDB")
+    }
+
+    /// Delete all the keys of the current database in the background.
+    fn flushdb_async<>() {
+        cmd("FLUSHDB").arg("ASYNC")
+    }
+
+    /// Delete all the keys of all the databases.
+    fn flushall<>() {
+        cmd("FLUSHALL")
+    }
+
+    /// Delete all the keys of all the databases in the background.
+    fn flushall_async<>() {
+        cmd("FLUSHALL").arg("ASYNC")
+    }
+
+    /// Return the configuration parameters matching the pattern and their values.
+    ///
+    /// The reply can be parsed into `HashMap<String, String>`.
+    fn config_get<P: ToRedisArgs>(pattern: P) {
+        cmd("CONFIG").arg("GET").arg(pattern)
+    }
+
+    /// Set a configuration parameter.
+    fn config_set<P: ToRedisArgs, V: ToRedisArgs>(param: P, value: V) {
+        cmd("CONFIG").arg("SET").arg(param).arg(value)
+    }
+
+    // debug commands
+
+    /// Enable or disable active expiration of keys with `DEBUG SET-ACTIVE-EXPIRE`.
+    ///
+    /// With active expiration disabled, expired keys are only removed when they
+    /// are accessed, which makes TTL behavior deterministic in tests.
+    ///
+    /// Note that Redis 7 rejects `DEBUG` unless `enable-debug-command` is set.
+    #[cfg(feature = "debug-commands")]
+    fn debug_set_active_expire<>(on: bool) {
+        cmd("DEBUG").arg("SET-ACTIVE-EXPIRE").arg(if on {1} else {0})
+    }
+
 }
@@ -0,0 +1,83 @@
+use futures::Future;
+use futures03::compat::Future01CompatExt;
+use redis::{aio::ConnectionLike, Cmd, ErrorKind, FromRedisValue, RedisError, RedisResult};
+
+/// Connection usable with `async`/`await` through `std::future::Future`.
+///
+/// The methods of [`Commands`][1] are futures 0.1 futures which consume the
+/// connection and give it back with the reply. This holds the connection, and
+/// runs such a future with [`run`](#method.run) as a `std::future::Future`
+/// resolving to only the reply, putting the connection back in place. So any
+/// command of [`Commands`][1] can be awaited while keeping `&mut self` access.
+///
+/// A command which failed consumes the connection as usual, after which the
+/// methods fail with `IoError`. The underlying I/O is still done by
+/// tokio 0.1, whose reactor must be available to the connection.
+///
+/// Streams such as [`RedisScanStream`][2] implement the `Stream` of futures 0.3
+/// as well with the `futures03` feature, which this feature enables.
+///
+/// This requires the `compat` feature.
+///
+/// [1]: ./trait.Commands.html
+/// [2]: ./struct.RedisScanStream.html
+///
+/// ```rust,no_run
+/// use futures03::compat::Future01CompatExt;
+/// use redis_ac::{AsyncConnection, Commands};
+///
+/// # async fn run() -> redis::RedisResult<()> {
+/// let client = redis::Client::open("redis://127.0.0.1").unwrap();
+/// let mut con = AsyncConnection::new(client.get_async_connection().compat().await?);
+///
+/// let () = con.run(|c| c.set("key", "value")).await?;
+/// let value: String = con.run(|c| c.get("key")).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct AsyncConnection<C> {
+    con: Option<C>,
+}
+
+impl<C> AsyncConnection<C>
+where
+    C: ConnectionLike + Send + 'static,
+{
+    /// Wraps a connection.
+    pub fn new(con: C) -> Self {
+        Self { con: Some(con) }
+    }
+
+    /// Runs a future taking the connection and giving it back with a value,
+    /// such as the ones of [`Commands`](./trait.Commands.html), and returns the value.
+    pub async fn run<F, R, T>(&mut self, f: F) -> RedisResult<T>
+    where
+        F: FnOnce(C) -> R,
+        R: Future<Item = (C, T), Error = RedisError>,
+    {
+        let con = self.con.take().ok_or_else(|| {
+            RedisError::from((ErrorKind::IoError, "Connection lost by a failed command"))
+        })?;
+        let (con, v) = f(con).compat().await?;
+        self.con = Some(con);
+        Ok(v)
+    }
+
+    /// Sends the command and returns the reply.
+    pub async fn query<RV: FromRedisValue + Send + 'static>(
+        &mut self,
+        cmd: &Cmd,
+    ) -> RedisResult<RV> {
+        self.run(|con| cmd.query_async(con)).await
+    }
+
+    /// Returns `true` if the connection was lost by a failed command.
+    pub fn is_lost(&self) -> bool {
+        self.con.is_none()
+    }
+
+    /// Unwraps the connection, or returns `None` if it was lost.
+    pub fn into_inner(self) -> Option<C> {
+        self.con
+    }
+}
@@ -0,0 +1,121 @@
+use flate2::{read::GzDecoder, write::GzEncoder};
+use redis::{ErrorKind, RedisError, RedisResult};
+use std::io::{Read, Write};
+
+/// Compression algorithm of values for [`set_compressed`][1].
+///
+/// [1]: ./trait.Commands.html#method.set_compressed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    /// gzip.
+    Gzip,
+    /// Zstandard.
+    Zstd,
+}
+
+impl Codec {
+    // The magic number at the start of the compressed data.
+    fn magic(self) -> &'static [u8] {
+        match self {
+            Codec::Gzip => &[0x1f, 0x8b],
+            Codec::Zstd => &[0x28, 0xb5, 0x2f, 0xfd],
+        }
+    }
+}
+
+/// Compression of values by [`set_compressed`][1] and [`get_compressed`][2].
+///
+/// It defaults to gzip with the default level of the codec. Values need to be
+/// read with the same codec as they were written with.
+///
+/// This requires the `compression` feature.
+///
+/// [1]: ./trait.Commands.html#method.set_compressed
+/// [2]: ./trait.Commands.html#method.get_compressed
+///
+/// ```rust,no_run
+/// use futures::prelude::*;
+/// use redis_ac::{Codec, Commands, Compression};
+///
+/// # fn main() {
+/// let client = redis::Client::open("redis://127.0.0.1").unwrap();
+/// let compression = Compression::default().codec(Codec::Zstd).level(19);
+///
+/// let f = client
+///     .get_async_connection()
+///     .and_then(move |con| {
+///         con.set_compressed("key", b"value", &compression)
+///             .and_then(move |(con, ())| con.get_compressed("key", &compression))
+///     })
+///     .map(|(_, value)| assert_eq!(value, Some(b"value".to_vec())))
+///     .map_err(|e| eprintln!("{}", e));
+///
+/// tokio::run(f);
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Compression {
+    codec: Codec,
+    level: Option<u32>,
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Self {
+            codec: Codec::Gzip,
+            level: None,
+        }
+    }
+}
+
+impl Compression {
+    /// Sets the codec.
+    pub fn codec(mut self, codec: Codec) -> Self {
+        self.codec = codec;
+        self
+    }
+
+    /// Sets the compression level, from 0 to 9 for gzip and from 1 to 22 for Zstandard.
+    pub fn level(mut self, level: u32) -> Self {
+        self.level = Some(level);
+        self
+    }
+
+    pub(crate) fn compress(&self, data: &[u8]) -> RedisResult<Vec<u8>> {
+        Ok(match self.codec {
+            Codec::Gzip => {
+                let level = self.level.map(flate2::Compression::new).unwrap_or_default();
+                let mut e = GzEncoder::new(Vec::new(), level);
+                e.write_all(data)?;
+                e.finish()?
+            }
+            Codec::Zstd => zstd::encode_all(data, self.level.unwrap_or(0) as i32)?,
+        })
+    }
+
+    /// Decompresses the data, failing with `TypeError` if it isn't compressed with the codec.
+    pub(crate) fn decompress(&self, data: &[u8]) -> RedisResult<Vec<u8>> {
+        if !data.starts_with(self.codec.magic()) {
+            return Err(RedisError::from((
+                ErrorKind::TypeError,
+                "Value is not compressed",
+                format!("not compressed with {:?}", self.codec),
+            )));
+        }
+
+        let decompressed = match self.codec {
+            Codec::Gzip => {
+                let mut d = Vec::new();
+                GzDecoder::new(data).read_to_end(&mut d).map(|_| d)
+            }
+            Codec::Zstd => zstd::decode_all(data),
+        };
+        decompressed.map_err(|e| {
+            RedisError::from((
+                ErrorKind::TypeError,
+                "Value is not compressed",
+                format!("corrupted {:?} data: {}", self.codec, e),
+            ))
+        })
+    }
+}
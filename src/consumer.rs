@@ -0,0 +1,113 @@
+use futures::prelude::*;
+use redis::{aio::ConnectionLike, cmd, RedisError, RedisFuture, ToRedisArgs};
+use std::collections::VecDeque;
+
+use crate::value::{StreamEntry, StreamKey};
+
+/// Stream over entries read by `XREADGROUP` as a consumer of a consumer group.
+///
+/// This is created by [`xread_group_stream`][1]. It repeatedly issues
+/// `XREADGROUP GROUP group consumer BLOCK ms STREAMS key >` with the same
+/// connection, and yields the new entries delivered to the consumer. A read
+/// which times out without entries is simply issued again, so the stream never
+/// ends unless a read fails, in which case the error is returned and the stream
+/// ends.
+///
+/// Nothing is acknowledged automatically. The entries stay pending in the group
+/// until their IDs are acknowledged by [`xack`][2], e.g. with another connection.
+///
+/// [1]: ./trait.Commands.html#method.xread_group_stream
+/// [2]: ./trait.Commands.html#method.xack
+pub struct RedisStreamConsumer<C> {
+    key: Vec<Vec<u8>>,
+    group: Vec<Vec<u8>>,
+    consumer: Vec<Vec<u8>>,
+    block: usize,
+    count: Option<usize>,
+    con: Option<C>,
+    pending: Option<RedisFuture<(C, Vec<StreamKey>)>>,
+    queue: VecDeque<StreamEntry>,
+}
+
+impl<C> RedisStreamConsumer<C>
+where
+    C: ConnectionLike + Send + 'static,
+{
+    pub(crate) fn new<K, G, N>(con: C, key: K, group: G, consumer: N, block: usize) -> Self
+    where
+        K: ToRedisArgs,
+        G: ToRedisArgs,
+        N: ToRedisArgs,
+    {
+        Self {
+            key: key.to_redis_args(),
+            group: group.to_redis_args(),
+            consumer: consumer.to_redis_args(),
+            block,
+            count: None,
+            con: Some(con),
+            pending: None,
+            queue: VecDeque::new(),
+        }
+    }
+
+    /// Reads up to `count` entries with every `XREADGROUP`.
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    fn query(&self, con: C) -> RedisFuture<(C, Vec<StreamKey>)> {
+        let mut c = cmd("XREADGROUP");
+        c.arg("GROUP")
+            .arg(&self.group[..])
+            .arg(&self.consumer[..])
+            .arg("BLOCK")
+            .arg(self.block);
+        if let Some(count) = self.count {
+            c.arg("COUNT").arg(count);
+        }
+        c.arg("STREAMS").arg(&self.key[..]).arg(">");
+        c.query_async(con)
+    }
+}
+
+impl<C> Stream for RedisStreamConsumer<C>
+where
+    C: ConnectionLike + Send + 'static,
+{
+    type Item = StreamEntry;
+    type Error = RedisError;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        loop {
+            if let Some(entry) = self.queue.pop_front() {
+                return Ok(Async::Ready(Some(entry)));
+            }
+
+            if self.pending.is_none() {
+                match self.con.take() {
+                    Some(con) => self.pending = Some(self.query(con)),
+                    // The last read failed.
+                    None => return Ok(Async::Ready(None)),
+                }
+            }
+
+            let res = self.pending.as_mut().map(|p| p.poll()).unwrap();
+            let (con, keys) = match res {
+                Ok(Async::Ready(res)) => res,
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Err(e) => {
+                    self.pending = None;
+                    return Err(e);
+                }
+            };
+
+            // Nothing is read on timeout, so just read again.
+            self.pending = None;
+            self.con = Some(con);
+            self.queue
+                .extend(keys.into_iter().flat_map(|key| key.entries));
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use futures::{future, prelude::*};
+use redis::{aio::ConnectionLike, RedisFuture, Value};
+use std::sync::Arc;
+
+/// Connection which tags every command with a correlation id.
+///
+/// With the `tracing` feature, every command issued through this connection is
+/// polled in a `redis` span with the `correlation_id` field, and failures are
+/// emitted as events in the span. This way a failure can be traced back to the
+/// request which caused it. The errors themselves are returned as they are.
+///
+/// Since [`Commands`][1] is implemented for any [`ConnectionLike`][2], this can be
+/// used in place of the wrapped connection. [`with_id`](#method.with_id) changes
+/// the id for the following operations.
+///
+/// [1]: ./trait.Commands.html
+/// [2]: https://docs.rs/redis/0.13.0/redis/aio/trait.ConnectionLike.html
+///
+/// ```rust,no_run
+/// use futures::{future, prelude::*};
+/// use redis_ac::{Commands, CorrelatedConnection};
+///
+/// # fn main() {
+/// let client = redis::Client::open("redis://127.0.0.1").unwrap();
+/// let connect = client.get_async_connection();
+///
+/// let f = connect.and_then(|con| {
+///     CorrelatedConnection::new(con, "request-1")
+///         .incr("key", 1)
+///         .and_then(|(con, _): (_, i64)| con.with_id("request-2").get("key"))
+///         .map(|(_, v): (_, i64)| println!("{}", v))
+/// }).map_err(|e| eprintln!("{}", e));
+///
+/// tokio::run(f);
+/// # }
+/// ```
+pub struct CorrelatedConnection<C> {
+    inner: C,
+    id: Arc<str>,
+}
+
+impl<C> CorrelatedConnection<C> {
+    /// Wraps a connection with a correlation id.
+    pub fn new<I: Into<String>>(inner: C, id: I) -> Self {
+        Self {
+            inner,
+            id: id.into().into(),
+        }
+    }
+
+    /// Replaces the correlation id for the following operations.
+    pub fn with_id<I: Into<String>>(mut self, id: I) -> Self {
+        self.id = id.into().into();
+        self
+    }
+
+    /// Returns the correlation id.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the wrapped connection.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+/// Future which is polled in a span.
+#[cfg(feature = "tracing")]
+struct Instrumented<F> {
+    inner: F,
+    span: tracing::Span,
+}
+
+#[cfg(feature = "tracing")]
+impl<F: Future> Future for Instrumented<F> {
+    type Item = F::Item;
+    type Error = F::Error;
+
+    fn poll(&mut self) -> Poll<F::Item, F::Error> {
+        let _enter = self.span.enter();
+        self.inner.poll()
+    }
+}
+
+impl<C> CorrelatedConnection<C>
+where
+    C: Send + 'static,
+{
+    fn wrap<T, F>(self, f: F) -> RedisFuture<(Self, T)>
+    where
+        T: Send + 'static,
+        F: FnOnce(C) -> RedisFuture<(C, T)> + Send + 'static,
+    {
+        let (inner, id) = (self.inner, self.id);
+
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!("redis", correlation_id = &*id);
+
+        // Issue the command in the first poll so that it runs in the span too.
+        let f = future::lazy(move || f(inner)).then(move |res| match res {
+            Ok((inner, v)) => Ok((Self { inner, id }, v)),
+            Err(e) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(error = %e, "command failed");
+                Err(e)
+            }
+        });
+
+        #[cfg(feature = "tracing")]
+        let f = Instrumented { inner: f, span };
+
+        Box::new(f)
+    }
+}
+
+impl<C> ConnectionLike for CorrelatedConnection<C>
+where
+    C: ConnectionLike + Send + 'static,
+{
+    fn req_packed_command(self, cmd: Vec<u8>) -> RedisFuture<(Self, Value)> {
+        self.wrap(move |con| con.req_packed_command(cmd))
+    }
+
+    fn req_packed_commands(
+        self,
+        cmd: Vec<u8>,
+        offset: usize,
+        count: usize,
+    ) -> RedisFuture<(Self, Vec<Value>)> {
+        self.wrap(move |con| con.req_packed_commands(cmd, offset, count))
+    }
+
+    fn get_db(&self) -> i64 {
+        self.inner.get_db()
+    }
+}
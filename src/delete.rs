@@ -0,0 +1,104 @@
+use futures::{future::Either, prelude::*};
+use redis::{aio::ConnectionLike, cmd, ErrorKind, RedisError, RedisFuture};
+
+use crate::commands::Commands;
+use crate::stream::{stream_then, RedisScanStream};
+
+/// Deletion of the keys matching a pattern, prepared by [`prepare_delete`][1].
+///
+/// This carries the number of the keys matching the pattern at the time of
+/// preparation, so that it can be confirmed before [`commit`](#method.commit).
+///
+/// [1]: ./trait.Commands.html#method.prepare_delete
+pub struct PreparedDelete<C> {
+    con: C,
+    pattern: Vec<Vec<u8>>,
+    count: usize,
+    growth: usize,
+}
+
+impl<C> PreparedDelete<C>
+where
+    C: ConnectionLike + Send + 'static,
+{
+    pub(crate) fn prepare(con: C, pattern: Vec<Vec<u8>>) -> RedisFuture<Self> {
+        Box::new(
+            con.count_matching(pattern.clone())
+                .map(move |(con, count)| Self {
+                    con,
+                    pattern,
+                    count,
+                    growth: 0,
+                }),
+        )
+    }
+
+    /// Returns the number of the keys matching the pattern at the time of preparation.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Allows `n` more keys than [`count`](#method.count) to match the pattern on commit.
+    ///
+    /// This defaults to `0`.
+    pub fn allow_growth(mut self, n: usize) -> Self {
+        self.growth = n;
+        self
+    }
+
+    /// Gives up the deletion and returns the connection.
+    pub fn cancel(self) -> C {
+        self.con
+    }
+
+    /// Deletes the keys matching the pattern, and returns the number of keys deleted.
+    ///
+    /// The keys are counted again first, and the future fails with `ResponseError`
+    /// without deleting anything if more keys than allowed match. Then each batch of
+    /// the scanned keys is removed by `UNLINK`. This isn't atomic; keys added between
+    /// counting and deletion may be deleted as well.
+    pub fn commit(self) -> RedisFuture<(C, usize)> {
+        let Self {
+            con,
+            pattern,
+            count,
+            growth,
+        } = self;
+        let max = count.saturating_add(growth);
+
+        Box::new(
+            con.count_matching(pattern.clone())
+                .and_then(move |(con, now)| {
+                    if now > max {
+                        return Either::A(
+                            Err(RedisError::from((
+                                ErrorKind::ResponseError,
+                                "Matching keys grew beyond the threshold",
+                                format!("prepared {}, allowed {}, now {}", count, max, now),
+                            )))
+                            .into_future(),
+                        );
+                    }
+
+                    let deleted: RedisScanStream<C, usize> = stream_then(
+                        con,
+                        move |cur| {
+                            let mut c = cmd("SCAN");
+                            c.arg(cur).arg("MATCH").arg(&pattern[..]);
+                            c
+                        },
+                        |con, keys: Vec<Vec<u8>>| {
+                            Box::new(
+                                cmd("UNLINK")
+                                    .arg(keys)
+                                    .query_async(con)
+                                    .map(|(con, n)| (con, vec![n])),
+                            )
+                        },
+                    );
+
+                    Either::B(deleted.fold_items(0, |n, count| Ok(n + count)))
+                }),
+        )
+    }
+}
@@ -0,0 +1,283 @@
+use futures::{
+    future::{self, Either, Loop},
+    prelude::*,
+    sync::oneshot,
+    try_ready,
+};
+use redis::{
+    aio::{Connection, ConnectionLike},
+    ControlFlow, ErrorKind, RedisError, RedisFuture, Value,
+};
+use std::time::{Duration, Instant};
+use tokio_timer::{Delay, Interval};
+
+use crate::pubsub::PubSubCommands;
+use crate::scripts;
+
+fn timer_error(e: tokio_timer::Error) -> RedisError {
+    RedisError::from((ErrorKind::IoError, "timer error", e.to_string()))
+}
+
+/// Leader election among clients by a key holding the ID of the leader.
+///
+/// A candidate becomes the leader by setting the key with `SET NX` with the
+/// TTL by [`campaign`](#method.campaign). The leader renews the TTL while its
+/// [`Leadership`][1] is running, so another candidate can take over once the
+/// leader stops renewing, e.g. because it crashed.
+///
+/// Changes of the leadership are published to [`channel`](#method.channel):
+/// the ID of a new leader when elected, and an empty message when released.
+/// Followers can wait for the leadership to be freed with [`observe`](#method.observe).
+///
+/// [1]: ./struct.Leadership.html
+///
+/// ```rust,no_run
+/// use futures::prelude::*;
+/// use redis_ac::Election;
+/// use std::time::{Duration, Instant};
+/// use tokio::timer::Delay;
+///
+/// # fn main() {
+/// let client = redis::Client::open("redis://127.0.0.1").unwrap();
+/// let election = Election::new("leader", "node-1", Duration::from_secs(10));
+///
+/// let f = client
+///     .get_shared_async_connection()
+///     .and_then(move |con| election.campaign(con))
+///     .and_then(|(handle, leadership)| {
+///         tokio::spawn(leadership.map(|_| ()).map_err(|e| eprintln!("{}", e)));
+///
+///         // Work as the leader for a minute, and then step down.
+///         Delay::new(Instant::now() + Duration::from_secs(60))
+///             .map(move |_| drop(handle))
+///             .map_err(|e| panic!("{}", e))
+///     })
+///     .map_err(|e| eprintln!("{}", e));
+///
+/// tokio::run(f);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Election {
+    key: String,
+    id: String,
+    ttl: Duration,
+}
+
+impl Election {
+    /// Creates an election at `key`, in which this candidate is identified by `id`.
+    ///
+    /// `ttl` is the TTL of the key, which is renewed every third of it by the leader.
+    /// It's rounded to milliseconds and needs to be 3 milliseconds or longer.
+    pub fn new<K: Into<String>, I: Into<String>>(key: K, id: I, ttl: Duration) -> Self {
+        Self {
+            key: key.into(),
+            id: id.into(),
+            ttl,
+        }
+    }
+
+    /// Returns the channel which the changes of the leadership are published to.
+    pub fn channel(&self) -> String {
+        format!("{}:leader", self.key)
+    }
+
+    fn ttl_millis(&self) -> u64 {
+        self.ttl.as_millis() as u64
+    }
+
+    /// Waits until this candidate becomes the leader.
+    ///
+    /// This tries `SET NX` and, if another leader exists, waits for its TTL to
+    /// expire to try again. Once elected, the ID of this candidate is published
+    /// to [`channel`](#method.channel), and the future resolves to the handle
+    /// and the [`Leadership`](./struct.Leadership.html) which needs to be spawned
+    /// to keep the leadership. The connection needs to be cloneable like
+    /// [`redis::aio::SharedConnection`][1].
+    ///
+    /// [1]: https://docs.rs/redis/0.13.0/redis/aio/struct.SharedConnection.html
+    pub fn campaign<C>(&self, con: C) -> RedisFuture<(LeadershipHandle, Leadership<C>)>
+    where
+        C: ConnectionLike + Clone + Send + 'static,
+    {
+        let election = self.clone();
+
+        Box::new(
+            future::loop_fn(con, move |con| {
+                let election = election.clone();
+
+                redis::cmd("SET")
+                    .arg(&election.key)
+                    .arg(&election.id)
+                    .arg("NX")
+                    .arg("PX")
+                    .arg(election.ttl_millis())
+                    .query_async(con)
+                    .and_then(move |(con, elected): (C, Option<String>)| {
+                        if elected.is_some() {
+                            let mut c = redis::cmd("PUBLISH");
+                            c.arg(election.channel()).arg(&election.id);
+                            return Either::A(
+                                c.query_async(con)
+                                    .map(move |(con, _): (C, usize)| Loop::Break((con, election))),
+                            );
+                        }
+
+                        let ttl = election.ttl;
+                        Either::B(
+                            redis::cmd("PTTL")
+                                .arg(&election.key)
+                                .query_async(con)
+                                .and_then(move |(con, pttl): (C, i64)| {
+                                    // `-2` means the key is already gone, and `-1`
+                                    // that it has no TTL, in which case poll by the TTL.
+                                    let wait = match pttl {
+                                        -2 => Duration::from_millis(0),
+                                        pttl if pttl < 0 => ttl,
+                                        pttl => Duration::from_millis(pttl as u64).min(ttl),
+                                    };
+                                    Delay::new(Instant::now() + wait)
+                                        .map_err(timer_error)
+                                        .map(move |_| Loop::Continue(con))
+                                }),
+                        )
+                    })
+            })
+            .map(|(con, election)| {
+                let (tx, rx) = oneshot::channel();
+                let interval = Interval::new_interval(election.ttl / 3);
+                let leadership = Leadership {
+                    con,
+                    election,
+                    stop: rx,
+                    interval,
+                    pending: None,
+                    releasing: None,
+                };
+                (LeadershipHandle { _stop: tx }, leadership)
+            }),
+        )
+    }
+
+    /// Waits until the leadership is freed, by subscribing with the connection.
+    ///
+    /// This resolves on a release published to [`channel`](#method.channel), or
+    /// on the expiration or deletion of the key. The latter requires keyspace
+    /// notifications of generic commands and expired events to be enabled,
+    /// e.g. `notify-keyspace-events Kgx`. Note that the leadership freed before
+    /// subscribing isn't notified, so followers should [`campaign`](#method.campaign)
+    /// after this resolves rather than expect to be notified once.
+    pub fn observe(&self, con: Connection) -> RedisFuture<(Connection, ())> {
+        let channel = self.channel();
+        let keyspace = format!("__keyspace@{}__:{}", con.get_db(), self.key);
+
+        Box::new(
+            con.subscribe(vec![channel.clone(), keyspace.clone()], move |msg| {
+                let freed = match msg.get_payload_bytes() {
+                    b"" => msg.get_channel_name() == channel,
+                    b"expired" | b"del" => msg.get_channel_name() == keyspace,
+                    _ => false,
+                };
+                Ok::<_, ()>(if freed {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue
+                })
+            })
+            .map(|(con, _)| (con, ())),
+        )
+    }
+}
+
+/// Handle to step down from [`Leadership`][1]. The leadership is released when this is dropped.
+///
+/// [1]: ./struct.Leadership.html
+pub struct LeadershipHandle {
+    _stop: oneshot::Sender<()>,
+}
+
+/// Future which renews the leadership acquired by [`Election::campaign`][1].
+///
+/// This renews the TTL of the key every third of the TTL, only if the key still
+/// holds the ID of this candidate. The future finishes with `false` if the
+/// leadership is lost, e.g. because the renewal was too late and another
+/// candidate took over. When its [`LeadershipHandle`][2] is dropped, it deletes
+/// the key if this is still the leader, publishes the release, and finishes
+/// with `true`. Dropping this future stops renewing, so the leadership expires
+/// with the TTL.
+///
+/// The future needs to be spawned on a runtime with a timer such as tokio.
+///
+/// [1]: ./struct.Election.html#method.campaign
+/// [2]: ./struct.LeadershipHandle.html
+pub struct Leadership<C> {
+    con: C,
+    election: Election,
+    stop: oneshot::Receiver<()>,
+    interval: Interval,
+    pending: Option<RedisFuture<(C, bool)>>,
+    releasing: Option<RedisFuture<(C, Value)>>,
+}
+
+impl<C> Leadership<C> {
+    /// Returns the election.
+    pub fn election(&self) -> &Election {
+        &self.election
+    }
+}
+
+impl<C> Future for Leadership<C>
+where
+    C: ConnectionLike + Clone + Send + 'static,
+{
+    type Item = bool;
+    type Error = RedisError;
+
+    fn poll(&mut self) -> Poll<bool, RedisError> {
+        loop {
+            if let Some(releasing) = self.releasing.as_mut() {
+                try_ready!(releasing.poll());
+                return Ok(Async::Ready(true));
+            }
+
+            match self.stop.poll() {
+                Ok(Async::NotReady) => {}
+                // The handle is dropped.
+                _ => {
+                    let e = &self.election;
+                    self.releasing = Some(
+                        scripts::eval(scripts::RELEASE_LEADER, 1)
+                            .arg(&e.key)
+                            .arg(&e.id)
+                            .arg(e.channel())
+                            .query_async(self.con.clone()),
+                    );
+                    continue;
+                }
+            }
+
+            if let Some(pending) = self.pending.as_mut() {
+                let (_, renewed) = try_ready!(pending.poll());
+                self.pending = None;
+                if !renewed {
+                    return Ok(Async::Ready(false));
+                }
+            }
+
+            match self.interval.poll() {
+                Ok(Async::Ready(_)) => {
+                    let e = &self.election;
+                    self.pending = Some(
+                        scripts::eval(scripts::RENEW_LEADER, 1)
+                            .arg(&e.key)
+                            .arg(&e.id)
+                            .arg(e.ttl_millis())
+                            .query_async(self.con.clone()),
+                    );
+                }
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Err(e) => return Err(timer_error(e)),
+            }
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use futures::{prelude::*, try_ready};
+use redis::{
+    aio::ConnectionLike, from_redis_value, FromRedisValue, RedisError, RedisFuture, Value,
+};
+use std::marker::PhantomData;
+
+/// Future returned by the commands of [`Commands`](./trait.Commands.html).
+///
+/// This resolves to the connection and the reply converted to `RV`, like
+/// [`redis::RedisFuture`][] does. It sends the command by
+/// `ConnectionLike::req_packed_command` and converts the reply by itself, which
+/// saves the allocation `redis::Cmd::query_async` makes to box the conversion.
+/// The future of `req_packed_command` is still boxed by the connection.
+///
+/// Use [`boxed`](#method.boxed) to get a [`redis::RedisFuture`][] as before.
+#[must_use = "futures do nothing unless polled"]
+pub struct CommandFuture<C, RV> {
+    inner: RedisFuture<(C, Value)>,
+    _rv: PhantomData<fn() -> RV>,
+}
+
+impl<C, RV> CommandFuture<C, RV>
+where
+    C: ConnectionLike + Send + 'static,
+    RV: FromRedisValue + Send + 'static,
+{
+    pub(crate) fn new(con: C, packed: Vec<u8>) -> Self {
+        Self {
+            inner: con.req_packed_command(packed),
+            _rv: PhantomData,
+        }
+    }
+
+    /// Boxes the future into a [`redis::RedisFuture`][].
+    pub fn boxed(self) -> RedisFuture<(C, RV)> {
+        Box::new(self)
+    }
+}
+
+impl<C, RV> Future for CommandFuture<C, RV>
+where
+    RV: FromRedisValue,
+{
+    type Item = (C, RV);
+    type Error = RedisError;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        let (con, v) = try_ready!(self.inner.poll());
+        Ok(Async::Ready((con, from_redis_value(&v)?)))
+    }
+}
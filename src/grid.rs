@@ -0,0 +1,43 @@
+const BASE32: &[u8] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+/// Maximum precision of [`geohash`], which is enough to distinguish points
+/// a few centimeters apart.
+pub(crate) const MAX_PRECISION: u8 = 12;
+
+/// Encodes a point into a geohash of `precision` characters.
+///
+/// Bits of the longitude and the latitude are interleaved from the longitude,
+/// each bit halving the range of the coordinate, and every 5 bits are encoded
+/// as a Base32 character. Points in the same cell share the same geohash, and
+/// a shorter geohash stands for a larger cell containing the longer ones.
+/// `precision` is clamped to `1..=MAX_PRECISION`.
+pub(crate) fn geohash(lat: f64, lon: f64, precision: u8) -> String {
+    let precision = precision.clamp(1, MAX_PRECISION);
+    let mut lat_range = (-90.0, 90.0);
+    let mut lon_range = (-180.0, 180.0);
+    let mut hash = String::with_capacity(precision as usize);
+    let mut even = true;
+
+    for _ in 0..precision {
+        let mut ch = 0;
+        for _ in 0..5 {
+            let (value, range) = if even {
+                (lon, &mut lon_range)
+            } else {
+                (lat, &mut lat_range)
+            };
+            let mid = (range.0 + range.1) / 2.0;
+            ch <<= 1;
+            if value >= mid {
+                ch |= 1;
+                range.0 = mid;
+            } else {
+                range.1 = mid;
+            }
+            even = !even;
+        }
+        hash.push(BASE32[ch] as char);
+    }
+
+    hash
+}
@@ -0,0 +1,77 @@
+use redis::{from_redis_value, ErrorKind, FromRedisValue, RedisError, RedisResult, Value};
+use std::collections::HashMap;
+
+/// Fields of a hash read by [`hget_struct`][1], to be converted by [`FromRedisHash`][2].
+///
+/// [1]: ./trait.Commands.html#method.hget_struct
+/// [2]: ./trait.FromRedisHash.html
+#[derive(Debug, Clone, Default)]
+pub struct RedisHash {
+    fields: HashMap<String, Value>,
+}
+
+impl RedisHash {
+    /// Returns the value of a field, failing with `TypeError` if it's missing.
+    pub fn required<T: FromRedisValue>(&self, name: &str) -> RedisResult<T> {
+        match self.fields.get(name) {
+            Some(v) => from_redis_value(v),
+            None => Err(RedisError::from((
+                ErrorKind::TypeError,
+                "Missing hash field",
+                name.to_string(),
+            ))),
+        }
+    }
+
+    /// Returns the value of a field, or `None` if it's missing.
+    pub fn optional<T: FromRedisValue>(&self, name: &str) -> RedisResult<Option<T>> {
+        self.fields.get(name).map(from_redis_value).transpose()
+    }
+
+    /// Returns `true` if the hash has no field, e.g. the key doesn't exist.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+}
+
+impl FromRedisValue for RedisHash {
+    fn from_redis_value(v: &Value) -> RedisResult<Self> {
+        Ok(Self {
+            fields: from_redis_value(v)?,
+        })
+    }
+}
+
+/// Conversion of a hash into a struct, for [`hget_struct`][1].
+///
+/// There's no derive macro. Implement this by reading each field with
+/// [`RedisHash::required`][2] or [`RedisHash::optional`][3].
+///
+/// [1]: ./trait.Commands.html#method.hget_struct
+/// [2]: ./struct.RedisHash.html#method.required
+/// [3]: ./struct.RedisHash.html#method.optional
+///
+/// ```rust
+/// use redis::RedisResult;
+/// use redis_ac::{FromRedisHash, RedisHash};
+///
+/// struct Config {
+///     name: String,
+///     port: u16,
+///     verbose: Option<bool>,
+/// }
+///
+/// impl FromRedisHash for Config {
+///     fn from_redis_hash(hash: &RedisHash) -> RedisResult<Self> {
+///         Ok(Self {
+///             name: hash.required("name")?,
+///             port: hash.required("port")?,
+///             verbose: hash.optional("verbose")?,
+///         })
+///     }
+/// }
+/// ```
+pub trait FromRedisHash: Sized {
+    /// Converts the fields of a hash.
+    fn from_redis_hash(hash: &RedisHash) -> RedisResult<Self>;
+}
@@ -0,0 +1,116 @@
+use redis::{from_redis_value, FromRedisValue, RedisResult, Value};
+use std::collections::HashMap;
+
+/// A section of `INFO` such as `Replication`, holding its fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InfoSection {
+    name: String,
+    fields: HashMap<String, String>,
+}
+
+impl InfoSection {
+    /// Returns the name of the section as in its `# Section` header.
+    ///
+    /// This is empty for the fields before any header.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the value of the field converted to `T`, or `None` if the field
+    /// is missing or can't be converted.
+    pub fn get<T: FromRedisValue>(&self, field: &str) -> Option<T> {
+        let v = self.fields.get(field)?;
+        from_redis_value(&Value::Data(v.as_bytes().to_vec())).ok()
+    }
+
+    /// Returns the fields of the section and their raw values.
+    pub fn fields(&self) -> &HashMap<String, String> {
+        &self.fields
+    }
+}
+
+/// The reply of `INFO` split into sections, returned by [`info_parsed`][1].
+///
+/// [1]: ./trait.Commands.html#method.info_parsed
+///
+/// ```rust
+/// use redis_ac::ServerInfo;
+///
+/// let info: ServerInfo = "# Memory\r\nused_memory:1024\r\n".parse().unwrap();
+/// assert_eq!(info.get::<u64>("used_memory"), Some(1024));
+/// assert!(info.section("memory").is_some());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServerInfo {
+    sections: Vec<InfoSection>,
+}
+
+impl ServerInfo {
+    /// Returns the section of the name, which is compared case-insensitively.
+    pub fn section(&self, name: &str) -> Option<&InfoSection> {
+        self.sections
+            .iter()
+            .find(|s| s.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Returns the sections in the order of the reply.
+    pub fn sections(&self) -> &[InfoSection] {
+        &self.sections
+    }
+
+    /// Returns the value of the field in any section converted to `T`, or
+    /// `None` if the field is missing or can't be converted.
+    pub fn get<T: FromRedisValue>(&self, field: &str) -> Option<T> {
+        self.sections
+            .iter()
+            .find(|s| s.fields.contains_key(field))
+            .and_then(|s| s.get(field))
+    }
+
+    fn parse(text: &str) -> Self {
+        let mut sections: Vec<InfoSection> = Vec::new();
+
+        for line in text.lines().map(str::trim) {
+            if let Some(name) = line.strip_prefix('#') {
+                sections.push(InfoSection {
+                    name: name.trim().to_string(),
+                    fields: HashMap::new(),
+                });
+                continue;
+            }
+
+            // Blank lines and anything else without a value.
+            let (k, v) = match line.split_once(':') {
+                Some(kv) => kv,
+                None => continue,
+            };
+
+            if sections.is_empty() {
+                sections.push(InfoSection {
+                    name: String::new(),
+                    fields: HashMap::new(),
+                });
+            }
+            if let Some(section) = sections.last_mut() {
+                section.fields.insert(k.to_string(), v.to_string());
+            }
+        }
+
+        Self { sections }
+    }
+}
+
+impl std::str::FromStr for ServerInfo {
+    type Err = std::convert::Infallible;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Ok(Self::parse(text))
+    }
+}
+
+impl FromRedisValue for ServerInfo {
+    fn from_redis_value(v: &Value) -> RedisResult<Self> {
+        let text: String = from_redis_value(v)?;
+        Ok(Self::parse(&text))
+    }
+}
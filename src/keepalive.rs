@@ -0,0 +1,119 @@
+use futures::{prelude::*, sync::oneshot, try_ready};
+use redis::{aio::ConnectionLike, ErrorKind, RedisError, RedisFuture};
+use std::time::Duration;
+use tokio_timer::Interval;
+
+/// Handle to stop [`Keepalive`][1]. The keepalive stops when this is dropped.
+///
+/// [1]: ./struct.Keepalive.html
+pub struct KeepaliveHandle {
+    _stop: oneshot::Sender<()>,
+}
+
+/// Future which periodically sends `PING` to keep a connection alive.
+///
+/// This is created by [`spawn_keepalive`][1]. The future finishes with the
+/// number of `PING`s sent when its [`KeepaliveHandle`][2] is dropped.
+///
+/// [1]: ./fn.spawn_keepalive.html
+/// [2]: ./struct.KeepaliveHandle.html
+pub struct Keepalive<C> {
+    con: C,
+    stop: oneshot::Receiver<()>,
+    interval: Interval,
+    pending: Option<RedisFuture<(C, String)>>,
+    count: usize,
+}
+
+/// Creates a future which sends `PING` through the connection at every interval.
+///
+/// This is intended for long-lived idle connections which may be closed by
+/// NAT or proxies otherwise. The connection needs to be cloneable like
+/// [`redis::aio::SharedConnection`][1]. Every `PING` is sent through a clone
+/// of it, so it doesn't interfere with other commands on the same connection.
+///
+/// The returned future needs to be spawned on a runtime with a timer such as tokio.
+/// It keeps running until the returned handle is dropped.
+///
+/// [1]: https://docs.rs/redis/0.13.0/redis/aio/struct.SharedConnection.html
+///
+/// ```rust,no_run
+/// use futures::prelude::*;
+/// use redis_ac::{spawn_keepalive, Commands};
+/// use std::time::Duration;
+///
+/// # fn main() {
+/// let client = redis::Client::open("redis://127.0.0.1").unwrap();
+///
+/// let f = client
+///     .get_shared_async_connection()
+///     .and_then(|con| {
+///         let (handle, keepalive) = spawn_keepalive(con.clone(), Duration::from_secs(30));
+///         tokio::spawn(keepalive.map(|_| ()).map_err(|e| eprintln!("{}", e)));
+///
+///         con.get("key").map(move |(_, v): (_, Option<String>)| {
+///             println!("{:?}", v);
+///             // Stop the keepalive.
+///             drop(handle);
+///         })
+///     })
+///     .map_err(|e| eprintln!("{}", e));
+///
+/// tokio::run(f);
+/// # }
+/// ```
+pub fn spawn_keepalive<C>(con: C, interval: Duration) -> (KeepaliveHandle, Keepalive<C>)
+where
+    C: ConnectionLike + Clone + Send + 'static,
+{
+    let (tx, rx) = oneshot::channel();
+
+    let handle = KeepaliveHandle { _stop: tx };
+    let keepalive = Keepalive {
+        con,
+        stop: rx,
+        interval: Interval::new_interval(interval),
+        pending: None,
+        count: 0,
+    };
+
+    (handle, keepalive)
+}
+
+impl<C> Future for Keepalive<C>
+where
+    C: ConnectionLike + Clone + Send + 'static,
+{
+    type Item = usize;
+    type Error = RedisError;
+
+    fn poll(&mut self) -> Poll<usize, RedisError> {
+        match self.stop.poll() {
+            Ok(Async::NotReady) => {}
+            // The handle is dropped.
+            _ => return Ok(Async::Ready(self.count)),
+        }
+
+        loop {
+            if let Some(pending) = self.pending.as_mut() {
+                try_ready!(pending.poll());
+                self.pending = None;
+            }
+
+            match self.interval.poll() {
+                Ok(Async::Ready(_)) => {
+                    self.count += 1;
+                    self.pending = Some(redis::cmd("PING").query_async(self.con.clone()));
+                }
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Err(e) => {
+                    return Err(RedisError::from((
+                        ErrorKind::IoError,
+                        "timer error",
+                        e.to_string(),
+                    )))
+                }
+            }
+        }
+    }
+}
@@ -91,14 +91,70 @@
 #![cfg_attr(feature = "readme", feature(external_doc))]
 #![warn(missing_docs)]
 
+#[cfg(feature = "bench-support")]
+pub mod bench_support;
+mod cluster;
 mod commands;
+#[cfg(feature = "compat")]
+mod compat;
+#[cfg(feature = "compression")]
+mod compression;
+mod consumer;
+mod correlated;
+mod delete;
+mod election;
+mod future;
+mod grid;
+mod hash;
+mod info;
+mod keepalive;
+mod limits;
+mod options;
+mod pipeline;
+#[cfg(feature = "pool")]
+mod pool;
 mod pubsub;
+mod scripts;
+mod snapshot;
 mod stream;
+mod transaction;
+mod value;
 
 #[cfg_attr(feature = "readme", doc(include = "../README.md"))]
 type _Doctest = ();
 
 pub use crate::{
-    commands::{Commands, RedisScanAll, RedisScanStream},
-    pubsub::{Msg, PubSubCommands},
+    cluster::ClusterNode,
+    commands::{Commands, RedisScanAll, RedisScanItems, RedisScanStream},
+    consumer::RedisStreamConsumer,
+    correlated::CorrelatedConnection,
+    delete::PreparedDelete,
+    election::{Election, Leadership, LeadershipHandle},
+    future::CommandFuture,
+    hash::{FromRedisHash, RedisHash},
+    info::{InfoSection, ServerInfo},
+    keepalive::{spawn_keepalive, Keepalive, KeepaliveHandle},
+    limits::ArgLimits,
+    options::{
+        BitFieldOps, BitFieldOverflow, BitPosOptions, ClientKillFilter, ClientType, CopyOptions,
+        Direction, ExpireOption, GetExOptions, MinMax, RestoreOptions, SetOptions, SizeMetric,
+        SortOptions, ZRangeOptions, ZaddOptions,
+    },
+    pipeline::{Pipeline, PipelineStream, TypedPipeline},
+    pubsub::{Msg, MsgKind, PubSubCommands, PubSubHandle, RedisPubSubStream},
+    scripts::Script,
+    stream::{stream_from, stream_shared},
+    transaction::Transaction,
+    value::{RedisKeyType, ReplicaInfo, Role, StreamEntry, StreamKey, TypedValue},
 };
+
+#[cfg(feature = "compat")]
+pub use crate::compat::AsyncConnection;
+#[cfg(feature = "compression")]
+pub use crate::compression::{Codec, Compression};
+#[cfg(feature = "geospatial")]
+pub use crate::options::GeoSearchOptions;
+#[cfg(feature = "debug-commands")]
+pub use crate::options::ObjectMeta;
+#[cfg(feature = "pool")]
+pub use crate::pool::{ConnectionManager, OnReturn, Pool};
@@ -0,0 +1,131 @@
+use futures::prelude::*;
+use redis::{aio::ConnectionLike, cmd, RedisFuture, ToRedisArgs};
+
+const DEFAULT_MAX_ARGS: usize = 1024 * 1024;
+const DEFAULT_MAX_BYTES: usize = 64 * 1024 * 1024;
+
+/// Limits of the size of a command issued by bulk helpers such as [`sadd_bulk_with`][1].
+///
+/// The bulk helpers split their items into multiple commands, each of which is
+/// within the limits, and send them one by one. This avoids hitting the limits
+/// of the server such as `proto-max-bulk-len` with huge batches. A single item
+/// exceeding the limits is still sent in its own command.
+///
+/// The limits default to 1M arguments and 64MiB per command, which
+/// [`sadd_bulk`][2] and [`del_bulk`][3] use.
+///
+/// Pipelines such as [`incr_many`][4] and [`Pipeline`][5] aren't split by the
+/// limits, as they already send every item in its own small command. Use
+/// [`Pipeline::auto_flush`][6] to bound the size of a round trip instead.
+///
+/// [1]: ./trait.Commands.html#method.sadd_bulk_with
+/// [2]: ./trait.Commands.html#method.sadd_bulk
+/// [3]: ./trait.Commands.html#method.del_bulk
+/// [4]: ./trait.Commands.html#method.incr_many
+/// [5]: ./struct.Pipeline.html
+/// [6]: ./struct.Pipeline.html#method.auto_flush
+///
+/// ```rust,no_run
+/// use futures::prelude::*;
+/// use redis_ac::{ArgLimits, Commands};
+///
+/// # fn main() {
+/// let client = redis::Client::open("redis://127.0.0.1").unwrap();
+/// let limits = ArgLimits::default().max_args(10000).max_bytes(1024 * 1024);
+/// let members: Vec<_> = (0..100_000).collect();
+///
+/// let f = client
+///     .get_async_connection()
+///     .and_then(move |con| con.sadd_bulk_with("set", &members, &limits))
+///     .map(|(_, n)| println!("{} added", n))
+///     .map_err(|e| eprintln!("{}", e));
+///
+/// tokio::run(f);
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArgLimits {
+    max_args: usize,
+    max_bytes: usize,
+}
+
+impl Default for ArgLimits {
+    fn default() -> Self {
+        Self {
+            max_args: DEFAULT_MAX_ARGS,
+            max_bytes: DEFAULT_MAX_BYTES,
+        }
+    }
+}
+
+impl ArgLimits {
+    /// Sets the maximum number of arguments per command, including the command name.
+    pub fn max_args(mut self, n: usize) -> Self {
+        self.max_args = n;
+        self
+    }
+
+    /// Sets the maximum total bytes of arguments per command.
+    pub fn max_bytes(mut self, n: usize) -> Self {
+        self.max_bytes = n;
+        self
+    }
+
+    /// Splits items into chunks, each of which fits in a command with `fixed` arguments.
+    fn split(&self, fixed: &[Vec<u8>], items: Vec<Vec<Vec<u8>>>) -> Vec<Vec<Vec<u8>>> {
+        let fixed_args = fixed.len() + 1;
+        let fixed_bytes: usize = fixed.iter().map(|a| a.len()).sum();
+
+        let mut chunks = Vec::new();
+        let mut chunk: Vec<Vec<u8>> = Vec::new();
+        let mut bytes = fixed_bytes;
+
+        for item in items {
+            let item_bytes: usize = item.iter().map(|a| a.len()).sum();
+            let full = fixed_args + chunk.len() + item.len() > self.max_args
+                || bytes + item_bytes > self.max_bytes;
+
+            if full && !chunk.is_empty() {
+                chunks.push(std::mem::take(&mut chunk));
+                bytes = fixed_bytes;
+            }
+
+            chunk.extend(item);
+            bytes += item_bytes;
+        }
+
+        if !chunk.is_empty() {
+            chunks.push(chunk);
+        }
+
+        chunks
+    }
+}
+
+/// Issues the command for every chunk of items within the limits,
+/// and sums up the integer replies.
+pub(crate) fn bulk_sum<C, K, I>(
+    con: C,
+    name: &'static str,
+    key: Option<K>,
+    items: &[I],
+    limits: &ArgLimits,
+) -> RedisFuture<(C, usize)>
+where
+    C: ConnectionLike + Send + 'static,
+    K: ToRedisArgs,
+    I: ToRedisArgs,
+{
+    let fixed = key.map(|k| k.to_redis_args()).unwrap_or_default();
+    let items = items.iter().map(|i| i.to_redis_args()).collect();
+    let chunks = limits.split(&fixed, items);
+
+    Box::new(
+        futures::stream::iter_ok(chunks).fold((con, 0), move |(con, n), chunk| {
+            let mut c = cmd(name);
+            c.arg(&fixed[..]).arg(chunk);
+            c.query_async(con)
+                .map(move |(con, m): (_, usize)| (con, n + m))
+        }),
+    )
+}
@@ -0,0 +1,1236 @@
+#[cfg(feature = "geospatial")]
+use redis::{
+    geo::{RadiusOrder, Unit},
+    ErrorKind, RedisError, RedisResult,
+};
+use redis::{RedisWrite, ToRedisArgs};
+
+/// The type of a client connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientType {
+    /// Normal clients.
+    Normal,
+    /// Masters of this server, if it is a replica.
+    Master,
+    /// Replicas connected to this server.
+    Replica,
+    /// Clients subscribed to pubsub channels.
+    PubSub,
+}
+
+impl ClientType {
+    fn as_bytes(self) -> &'static [u8] {
+        match self {
+            ClientType::Normal => b"normal",
+            ClientType::Master => b"master",
+            ClientType::Replica => b"replica",
+            ClientType::PubSub => b"pubsub",
+        }
+    }
+}
+
+/// Filters to select the clients to kill by [`client_kill`][1].
+///
+/// Multiple filters can be combined, in which case only the clients
+/// matching all of them are killed.
+///
+/// [1]: ./trait.Commands.html#method.client_kill
+///
+/// ```rust,no_run
+/// use redis_ac::{ClientKillFilter, ClientType};
+///
+/// let filter = ClientKillFilter::default()
+///     .type_(ClientType::PubSub)
+///     .skipme(true);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ClientKillFilter {
+    id: Option<u64>,
+    addr: Option<Vec<Vec<u8>>>,
+    laddr: Option<Vec<Vec<u8>>>,
+    ty: Option<ClientType>,
+    skipme: Option<bool>,
+    maxage: Option<u64>,
+}
+
+impl ClientKillFilter {
+    /// Kill the client with the given unique ID.
+    pub fn id(mut self, id: u64) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Kill the client connected from the given `ip:port` address.
+    pub fn addr<A: ToRedisArgs>(mut self, addr: A) -> Self {
+        self.addr = Some(addr.to_redis_args());
+        self
+    }
+
+    /// Kill clients connected to the given local `ip:port` address of the server.
+    pub fn laddr<A: ToRedisArgs>(mut self, laddr: A) -> Self {
+        self.laddr = Some(laddr.to_redis_args());
+        self
+    }
+
+    /// Kill clients of the given type.
+    pub fn type_(mut self, ty: ClientType) -> Self {
+        self.ty = Some(ty);
+        self
+    }
+
+    /// Whether to skip the client calling the command. Redis defaults to `true`.
+    pub fn skipme(mut self, skipme: bool) -> Self {
+        self.skipme = Some(skipme);
+        self
+    }
+
+    /// Kill clients connected for longer than the given seconds.
+    ///
+    /// This requires Redis 7.4 or later.
+    pub fn maxage(mut self, secs: u64) -> Self {
+        self.maxage = Some(secs);
+        self
+    }
+}
+
+impl ToRedisArgs for ClientKillFilter {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        if let Some(id) = self.id {
+            out.write_arg(b"ID");
+            out.write_arg(format!("{}", id).as_bytes());
+        }
+
+        if let Some(ref addr) = self.addr {
+            out.write_arg(b"ADDR");
+            for i in addr {
+                out.write_arg(i);
+            }
+        }
+
+        if let Some(ref laddr) = self.laddr {
+            out.write_arg(b"LADDR");
+            for i in laddr {
+                out.write_arg(i);
+            }
+        }
+
+        if let Some(ty) = self.ty {
+            out.write_arg(b"TYPE");
+            out.write_arg(ty.as_bytes());
+        }
+
+        if let Some(skipme) = self.skipme {
+            out.write_arg(b"SKIPME");
+            out.write_arg(if skipme { b"yes" } else { b"no" });
+        }
+
+        if let Some(maxage) = self.maxage {
+            out.write_arg(b"MAXAGE");
+            out.write_arg(format!("{}", maxage).as_bytes());
+        }
+    }
+
+    fn is_single_arg(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum SetCondition {
+    Nx,
+    Xx,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum SetExpiry {
+    Ex(usize),
+    Px(usize),
+    KeepTtl,
+}
+
+/// Options for [`set_options`][1].
+///
+/// Only one of [`nx`](#method.nx) and [`xx`](#method.xx), and one of
+/// [`ex`](#method.ex), [`px`](#method.px) and [`keep_ttl`](#method.keep_ttl)
+/// take effect; the last one set wins.
+///
+/// With [`get`](#method.get), the reply is the old value of the key, so it
+/// needs to be parsed as `Option<RV>`, which is `None` if the key didn't exist.
+/// Otherwise the reply is `OK`, or nil if the condition isn't met.
+///
+/// [1]: ./trait.Commands.html#method.set_options
+///
+/// ```rust,no_run
+/// use redis_ac::SetOptions;
+///
+/// // A lock which expires in 30 seconds.
+/// let opts = SetOptions::default().nx().px(30000);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SetOptions {
+    condition: Option<SetCondition>,
+    expiry: Option<SetExpiry>,
+    get: bool,
+}
+
+impl SetOptions {
+    /// Set the key only if it doesn't exist.
+    pub fn nx(mut self) -> Self {
+        self.condition = Some(SetCondition::Nx);
+        self
+    }
+
+    /// Set the key only if it already exists.
+    pub fn xx(mut self) -> Self {
+        self.condition = Some(SetCondition::Xx);
+        self
+    }
+
+    /// Expire the key in the given seconds.
+    pub fn ex(mut self, secs: usize) -> Self {
+        self.expiry = Some(SetExpiry::Ex(secs));
+        self
+    }
+
+    /// Expire the key in the given milliseconds.
+    pub fn px(mut self, ms: usize) -> Self {
+        self.expiry = Some(SetExpiry::Px(ms));
+        self
+    }
+
+    /// Keep the TTL of the key instead of clearing it.
+    ///
+    /// This requires Redis 6.0 or later.
+    pub fn keep_ttl(mut self) -> Self {
+        self.expiry = Some(SetExpiry::KeepTtl);
+        self
+    }
+
+    /// Return the old value of the key.
+    ///
+    /// This requires Redis 6.2 or later, or 7.0 or later with `nx`.
+    pub fn get(mut self) -> Self {
+        self.get = true;
+        self
+    }
+}
+
+impl ToRedisArgs for SetOptions {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        match self.condition {
+            Some(SetCondition::Nx) => out.write_arg(b"NX"),
+            Some(SetCondition::Xx) => out.write_arg(b"XX"),
+            None => {}
+        }
+
+        if self.get {
+            out.write_arg(b"GET");
+        }
+
+        match self.expiry {
+            Some(SetExpiry::Ex(secs)) => {
+                out.write_arg(b"EX");
+                out.write_arg(format!("{}", secs).as_bytes());
+            }
+            Some(SetExpiry::Px(ms)) => {
+                out.write_arg(b"PX");
+                out.write_arg(format!("{}", ms).as_bytes());
+            }
+            Some(SetExpiry::KeepTtl) => out.write_arg(b"KEEPTTL"),
+            None => {}
+        }
+    }
+
+    fn is_single_arg(&self) -> bool {
+        false
+    }
+}
+
+/// Options for [`bitpos`][1] to search only in a range.
+///
+/// The range is in bytes unless [`bit`](#method.bit) is set. A missing start is
+/// the beginning of the string and a missing end is the end of the string.
+///
+/// [1]: ./trait.Commands.html#method.bitpos
+///
+/// ```rust,no_run
+/// use redis_ac::BitPosOptions;
+///
+/// // Search from the 8th bit to the end.
+/// let opts = BitPosOptions::default().start(8).bit();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BitPosOptions {
+    start: Option<isize>,
+    end: Option<isize>,
+    unit: Option<&'static [u8]>,
+}
+
+impl BitPosOptions {
+    /// Search from the given position. Negative values are offsets from the end.
+    pub fn start(mut self, start: isize) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    /// Search until the given position inclusive. Negative values are offsets from the end.
+    pub fn end(mut self, end: isize) -> Self {
+        self.end = Some(end);
+        self
+    }
+
+    /// Regard the range as in bytes, which is the default.
+    pub fn byte(mut self) -> Self {
+        self.unit = Some(b"BYTE");
+        self
+    }
+
+    /// Regard the range as in bits.
+    ///
+    /// This requires Redis 7.0 or later.
+    pub fn bit(mut self) -> Self {
+        self.unit = Some(b"BIT");
+        self
+    }
+}
+
+impl ToRedisArgs for BitPosOptions {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        // Each argument requires the preceding ones.
+        if self.start.is_none() && self.end.is_none() && self.unit.is_none() {
+            return;
+        }
+        out.write_arg(format!("{}", self.start.unwrap_or(0)).as_bytes());
+
+        if self.end.is_none() && self.unit.is_none() {
+            return;
+        }
+        out.write_arg(format!("{}", self.end.unwrap_or(-1)).as_bytes());
+
+        if let Some(unit) = self.unit {
+            out.write_arg(unit);
+        }
+    }
+
+    fn is_single_arg(&self) -> bool {
+        false
+    }
+}
+
+/// The behavior of [`BitFieldOps`](./struct.BitFieldOps.html) on overflows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitFieldOverflow {
+    /// Wrap around, which is the default.
+    Wrap,
+    /// Saturate to the minimum or the maximum value.
+    Sat,
+    /// Don't change the value, and return nil.
+    Fail,
+}
+
+/// The subcommands of [`bitfield`][1], run in the order they are added.
+///
+/// Types are such as `u8` and `i16`, and offsets are in bits, or in the
+/// widths of the type if prefixed by `#`. The reply has a value for each
+/// `get`, `set` and `incrby`, which is the old value for `set` and the new
+/// value for `incrby`. It can be parsed as `Vec<i64>`, or `Vec<Option<i64>>`
+/// with [`BitFieldOverflow::Fail`](./enum.BitFieldOverflow.html#variant.Fail).
+///
+/// [1]: ./trait.Commands.html#method.bitfield
+///
+/// ```rust,no_run
+/// use redis_ac::{BitFieldOps, BitFieldOverflow};
+///
+/// // Two saturating 8-bit counters in one key.
+/// let ops = BitFieldOps::default()
+///     .overflow(BitFieldOverflow::Sat)
+///     .incrby("u8", "#0", 1)
+///     .incrby("u8", "#1", 10);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BitFieldOps {
+    args: Vec<Vec<u8>>,
+}
+
+impl BitFieldOps {
+    fn push<T: ToRedisArgs, O: ToRedisArgs>(mut self, name: &[u8], ty: T, offset: O) -> Self {
+        self.args.push(name.to_vec());
+        self.args.extend(ty.to_redis_args());
+        self.args.extend(offset.to_redis_args());
+        self
+    }
+
+    /// Get the value at the offset.
+    pub fn get<T: ToRedisArgs, O: ToRedisArgs>(self, ty: T, offset: O) -> Self {
+        self.push(b"GET", ty, offset)
+    }
+
+    /// Set the value at the offset.
+    pub fn set<T: ToRedisArgs, O: ToRedisArgs>(self, ty: T, offset: O, value: i64) -> Self {
+        let mut ops = self.push(b"SET", ty, offset);
+        ops.args.push(format!("{}", value).into_bytes());
+        ops
+    }
+
+    /// Increment the value at the offset.
+    pub fn incrby<T: ToRedisArgs, O: ToRedisArgs>(self, ty: T, offset: O, incr: i64) -> Self {
+        let mut ops = self.push(b"INCRBY", ty, offset);
+        ops.args.push(format!("{}", incr).into_bytes());
+        ops
+    }
+
+    /// Change the behavior on overflows of the following `set` and `incrby`.
+    pub fn overflow(mut self, overflow: BitFieldOverflow) -> Self {
+        self.args.push(b"OVERFLOW".to_vec());
+        self.args.push(
+            match overflow {
+                BitFieldOverflow::Wrap => &b"WRAP"[..],
+                BitFieldOverflow::Sat => b"SAT",
+                BitFieldOverflow::Fail => b"FAIL",
+            }
+            .to_vec(),
+        );
+        self
+    }
+}
+
+impl ToRedisArgs for BitFieldOps {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        for i in &self.args {
+            out.write_arg(i);
+        }
+    }
+
+    fn is_single_arg(&self) -> bool {
+        false
+    }
+}
+
+/// Options for [`zrange_generic`][1], which is the unified `ZRANGE` of Redis 6.2.
+///
+/// By default, `min` and `max` are indexes. With [`by_score`](#method.by_score)
+/// they are scores such as `1`, `(1` and `-inf`, and with [`by_lex`](#method.by_lex)
+/// members such as `[a`, `(a` and `-`. With [`rev`](#method.rev), the members
+/// are returned from the highest to the lowest, and `min` and `max` need to be
+/// swapped for scores and members.
+///
+/// [1]: ./trait.Commands.html#method.zrange_generic
+///
+/// ```rust,no_run
+/// use futures::prelude::*;
+/// use redis_ac::{Commands, ZRangeOptions};
+///
+/// # fn main() {
+/// let client = redis::Client::open("redis://127.0.0.1").unwrap();
+/// let connect = client.get_async_connection();
+///
+/// // The top 10 scores up to 100.
+/// let opts = ZRangeOptions::default()
+///     .by_score()
+///     .rev()
+///     .limit(0, 10)
+///     .with_scores();
+///
+/// let f = connect.and_then(move |con| {
+///     con.zrange_generic("scores", 100, "-inf", opts)
+///         .map(|(_, top): (_, Vec<(String, f64)>)| println!("{:?}", top))
+/// }).map_err(|e| eprintln!("{}", e));
+///
+/// tokio::run(f);
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ZRangeOptions {
+    by: Option<&'static [u8]>,
+    rev: bool,
+    limit: Option<(isize, isize)>,
+    with_scores: bool,
+}
+
+impl ZRangeOptions {
+    /// Select the members by scores.
+    pub fn by_score(mut self) -> Self {
+        self.by = Some(b"BYSCORE");
+        self
+    }
+
+    /// Select the members lexicographically, which requires all the scores to be equal.
+    pub fn by_lex(mut self) -> Self {
+        self.by = Some(b"BYLEX");
+        self
+    }
+
+    /// Return the members in the descending order.
+    pub fn rev(mut self) -> Self {
+        self.rev = true;
+        self
+    }
+
+    /// Return only `count` members from `offset`, which requires `by_score` or `by_lex`.
+    pub fn limit(mut self, offset: isize, count: isize) -> Self {
+        self.limit = Some((offset, count));
+        self
+    }
+
+    /// Return the scores together with the members.
+    pub fn with_scores(mut self) -> Self {
+        self.with_scores = true;
+        self
+    }
+}
+
+impl ToRedisArgs for ZRangeOptions {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        if let Some(by) = self.by {
+            out.write_arg(by);
+        }
+
+        if self.rev {
+            out.write_arg(b"REV");
+        }
+
+        if let Some((offset, count)) = self.limit {
+            out.write_arg(b"LIMIT");
+            out.write_arg(format!("{}", offset).as_bytes());
+            out.write_arg(format!("{}", count).as_bytes());
+        }
+
+        if self.with_scores {
+            out.write_arg(b"WITHSCORES");
+        }
+    }
+
+    fn is_single_arg(&self) -> bool {
+        false
+    }
+}
+
+/// Options for [`zadd_options`][1].
+///
+/// Only one of [`nx`](#method.nx) and [`xx`](#method.xx), and one of
+/// [`gt`](#method.gt) and [`lt`](#method.lt) take effect; the last one set wins.
+/// `NX` can't be combined with `GT` or `LT`, which the server rejects.
+///
+/// The reply is the number of the members added, or with [`ch`](#method.ch)
+/// the number of the members added or updated. With [`incr`](#method.incr),
+/// it's the new score, which needs to be parsed as `Option<f64>` as it's nil
+/// if the condition isn't met.
+///
+/// [1]: ./trait.Commands.html#method.zadd_options
+///
+/// ```rust,no_run
+/// use redis_ac::ZaddOptions;
+///
+/// // Only raise the score, and count the updated members.
+/// let opts = ZaddOptions::default().gt().ch();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ZaddOptions {
+    exists: Option<bool>,
+    greater: Option<bool>,
+    ch: bool,
+    incr: bool,
+}
+
+impl ZaddOptions {
+    /// Only add new members, without updating the existing ones.
+    pub fn nx(mut self) -> Self {
+        self.exists = Some(false);
+        self
+    }
+
+    /// Only update the existing members, without adding new ones.
+    pub fn xx(mut self) -> Self {
+        self.exists = Some(true);
+        self
+    }
+
+    /// Only update the score if the new one is greater than the current one.
+    pub fn gt(mut self) -> Self {
+        self.greater = Some(true);
+        self
+    }
+
+    /// Only update the score if the new one is less than the current one.
+    pub fn lt(mut self) -> Self {
+        self.greater = Some(false);
+        self
+    }
+
+    /// Count the updated members as well as the added ones.
+    pub fn ch(mut self) -> Self {
+        self.ch = true;
+        self
+    }
+
+    /// Increment the score by the given one like `ZINCRBY`.
+    pub fn incr(mut self) -> Self {
+        self.incr = true;
+        self
+    }
+}
+
+impl ToRedisArgs for ZaddOptions {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        match self.exists {
+            Some(false) => out.write_arg(b"NX"),
+            Some(true) => out.write_arg(b"XX"),
+            None => {}
+        }
+
+        match self.greater {
+            Some(true) => out.write_arg(b"GT"),
+            Some(false) => out.write_arg(b"LT"),
+            None => {}
+        }
+
+        if self.ch {
+            out.write_arg(b"CH");
+        }
+
+        if self.incr {
+            out.write_arg(b"INCR");
+        }
+    }
+
+    fn is_single_arg(&self) -> bool {
+        false
+    }
+}
+
+/// How [`size_histogram_by`][1] measures the size of keys.
+///
+/// [1]: ./trait.Commands.html#method.size_histogram_by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeMetric {
+    /// The memory usage by `MEMORY USAGE`, which requires Redis 4.0 or later.
+    MemoryUsage,
+    /// The length of values by `STRLEN`, for servers without `MEMORY USAGE`.
+    ///
+    /// All the keys must be strings, and keys removed while scanning count as 0 bytes.
+    Strlen,
+}
+
+/// The end of lists to pop from by [`lmpop`][1].
+///
+/// [1]: ./trait.Commands.html#method.lmpop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// The head.
+    Left,
+    /// The tail.
+    Right,
+}
+
+impl ToRedisArgs for Direction {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        out.write_arg(match *self {
+            Direction::Left => b"LEFT",
+            Direction::Right => b"RIGHT",
+        });
+    }
+}
+
+/// The end of sorted sets to pop from by [`zmpop`][1].
+///
+/// [1]: ./trait.Commands.html#method.zmpop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MinMax {
+    /// The members with the lowest scores.
+    Min,
+    /// The members with the highest scores.
+    Max,
+}
+
+impl ToRedisArgs for MinMax {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        out.write_arg(match *self {
+            MinMax::Min => b"MIN",
+            MinMax::Max => b"MAX",
+        });
+    }
+}
+
+/// Options for [`sort`][1] and [`sort_ro`][2].
+///
+/// The options are written in the order required by `SORT` regardless of the
+/// order they are set in. [`get`](#method.get) can be set multiple times, in
+/// which case the reply holds the values of the patterns for each element in turn.
+///
+/// [1]: ./trait.Commands.html#method.sort
+/// [2]: ./trait.Commands.html#method.sort_ro
+///
+/// ```rust,no_run
+/// use redis_ac::SortOptions;
+///
+/// let opts = SortOptions::default()
+///     .by("weight:*")
+///     .limit(0, 10)
+///     .get("#")
+///     .get("name:*")
+///     .desc()
+///     .alpha();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SortOptions {
+    by: Option<Vec<Vec<u8>>>,
+    limit: Option<(isize, isize)>,
+    get: Vec<Vec<Vec<u8>>>,
+    desc: Option<bool>,
+    alpha: bool,
+    store: Option<Vec<Vec<u8>>>,
+}
+
+impl SortOptions {
+    /// Sort by the values of the keys made by substituting `*` in the pattern
+    /// with the elements. `nosort` skips sorting.
+    pub fn by<P: ToRedisArgs>(mut self, pattern: P) -> Self {
+        self.by = Some(pattern.to_redis_args());
+        self
+    }
+
+    /// Return only `count` elements from `offset` of the sorted elements.
+    pub fn limit(mut self, offset: isize, count: isize) -> Self {
+        self.limit = Some((offset, count));
+        self
+    }
+
+    /// Return the values of the keys made by substituting `*` in the pattern
+    /// with the elements instead of the elements. `#` returns the elements.
+    pub fn get<P: ToRedisArgs>(mut self, pattern: P) -> Self {
+        self.get.push(pattern.to_redis_args());
+        self
+    }
+
+    /// Sort in the ascending order, which is the default.
+    pub fn asc(mut self) -> Self {
+        self.desc = Some(false);
+        self
+    }
+
+    /// Sort in the descending order.
+    pub fn desc(mut self) -> Self {
+        self.desc = Some(true);
+        self
+    }
+
+    /// Sort lexicographically instead of numerically.
+    pub fn alpha(mut self) -> Self {
+        self.alpha = true;
+        self
+    }
+
+    /// Store the result in the list at the given key instead of returning it.
+    ///
+    /// The reply is then the number of the stored elements. This isn't
+    /// allowed by [`sort_ro`](./trait.Commands.html#method.sort_ro).
+    pub fn store<K: ToRedisArgs>(mut self, dst: K) -> Self {
+        self.store = Some(dst.to_redis_args());
+        self
+    }
+}
+
+impl ToRedisArgs for SortOptions {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        if let Some(ref by) = self.by {
+            out.write_arg(b"BY");
+            for i in by {
+                out.write_arg(i);
+            }
+        }
+
+        if let Some((offset, count)) = self.limit {
+            out.write_arg(b"LIMIT");
+            out.write_arg(format!("{}", offset).as_bytes());
+            out.write_arg(format!("{}", count).as_bytes());
+        }
+
+        for get in &self.get {
+            out.write_arg(b"GET");
+            for i in get {
+                out.write_arg(i);
+            }
+        }
+
+        match self.desc {
+            Some(false) => out.write_arg(b"ASC"),
+            Some(true) => out.write_arg(b"DESC"),
+            None => {}
+        }
+
+        if self.alpha {
+            out.write_arg(b"ALPHA");
+        }
+
+        if let Some(ref store) = self.store {
+            out.write_arg(b"STORE");
+            for i in store {
+                out.write_arg(i);
+            }
+        }
+    }
+
+    fn is_single_arg(&self) -> bool {
+        false
+    }
+}
+
+/// Options for [`copy_options`][1].
+///
+/// [1]: ./trait.Commands.html#method.copy_options
+///
+/// ```rust,no_run
+/// use redis_ac::CopyOptions;
+///
+/// let opts = CopyOptions::default().db(1).replace();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CopyOptions {
+    db: Option<i64>,
+    replace: bool,
+}
+
+impl CopyOptions {
+    /// Copy to the key in the given database instead of the current one.
+    pub fn db(mut self, db: i64) -> Self {
+        self.db = Some(db);
+        self
+    }
+
+    /// Overwrite the destination key if it exists.
+    pub fn replace(mut self) -> Self {
+        self.replace = true;
+        self
+    }
+}
+
+impl ToRedisArgs for CopyOptions {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        if let Some(db) = self.db {
+            out.write_arg(b"DB");
+            out.write_arg(format!("{}", db).as_bytes());
+        }
+
+        if self.replace {
+            out.write_arg(b"REPLACE");
+        }
+    }
+
+    fn is_single_arg(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum RestoreMeta {
+    Idle(u64),
+    Freq(u8),
+}
+
+/// Options for [`restore`][1].
+///
+/// Only one of [`idletime`](#method.idletime) and [`freq`](#method.freq)
+/// takes effect; the last one set wins.
+///
+/// [1]: ./trait.Commands.html#method.restore
+///
+/// ```rust,no_run
+/// use redis_ac::RestoreOptions;
+///
+/// let opts = RestoreOptions::default().replace().absttl();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RestoreOptions {
+    replace: bool,
+    absttl: bool,
+    meta: Option<RestoreMeta>,
+}
+
+impl RestoreOptions {
+    /// Overwrite the key if it exists.
+    pub fn replace(mut self) -> Self {
+        self.replace = true;
+        self
+    }
+
+    /// Regard the TTL as a UNIX timestamp in milliseconds instead of a duration.
+    pub fn absttl(mut self) -> Self {
+        self.absttl = true;
+        self
+    }
+
+    /// Set the idle time in seconds used by the LRU eviction policies.
+    pub fn idletime(mut self, secs: u64) -> Self {
+        self.meta = Some(RestoreMeta::Idle(secs));
+        self
+    }
+
+    /// Set the access frequency counter used by the LFU eviction policies.
+    pub fn freq(mut self, freq: u8) -> Self {
+        self.meta = Some(RestoreMeta::Freq(freq));
+        self
+    }
+}
+
+impl ToRedisArgs for RestoreOptions {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        if self.replace {
+            out.write_arg(b"REPLACE");
+        }
+
+        if self.absttl {
+            out.write_arg(b"ABSTTL");
+        }
+
+        match self.meta {
+            Some(RestoreMeta::Idle(secs)) => {
+                out.write_arg(b"IDLETIME");
+                out.write_arg(format!("{}", secs).as_bytes());
+            }
+            Some(RestoreMeta::Freq(freq)) => {
+                out.write_arg(b"FREQ");
+                out.write_arg(format!("{}", freq).as_bytes());
+            }
+            None => {}
+        }
+    }
+
+    fn is_single_arg(&self) -> bool {
+        false
+    }
+}
+
+/// The change of the timeout applied by [`getex`][1].
+///
+/// [1]: ./trait.Commands.html#method.getex
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GetExOptions {
+    /// Expire the key in the given seconds.
+    Ex(usize),
+    /// Expire the key in the given milliseconds.
+    Px(usize),
+    /// Expire the key at the given UNIX timestamp in seconds.
+    ExAt(usize),
+    /// Expire the key at the given UNIX timestamp in milliseconds.
+    PxAt(usize),
+    /// Remove the timeout of the key.
+    Persist,
+}
+
+impl ToRedisArgs for GetExOptions {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        let (name, value): (&[u8], _) = match *self {
+            GetExOptions::Ex(secs) => (b"EX", secs),
+            GetExOptions::Px(ms) => (b"PX", ms),
+            GetExOptions::ExAt(ts) => (b"EXAT", ts),
+            GetExOptions::PxAt(ts) => (b"PXAT", ts),
+            GetExOptions::Persist => return out.write_arg(b"PERSIST"),
+        };
+        out.write_arg(name);
+        out.write_arg(format!("{}", value).as_bytes());
+    }
+
+    fn is_single_arg(&self) -> bool {
+        false
+    }
+}
+
+/// The condition to set a timeout by [`expire_opts`][1] and its variants.
+///
+/// This requires Redis 7.0 or later. A key without a timeout is regarded as
+/// having an infinite TTL by `Gt` and `Lt`.
+///
+/// [1]: ./trait.Commands.html#method.expire_opts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpireOption {
+    /// Set only if the key has no timeout.
+    Nx,
+    /// Set only if the key already has a timeout.
+    Xx,
+    /// Set only if the new timeout is greater than the current one.
+    Gt,
+    /// Set only if the new timeout is less than the current one.
+    Lt,
+}
+
+impl ToRedisArgs for ExpireOption {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        out.write_arg(match *self {
+            ExpireOption::Nx => b"NX",
+            ExpireOption::Xx => b"XX",
+            ExpireOption::Gt => b"GT",
+            ExpireOption::Lt => b"LT",
+        });
+    }
+}
+
+/// Eviction metadata of a key to be applied by [`set_with_meta`][1].
+///
+/// [1]: ./trait.Commands.html#method.set_with_meta
+#[cfg(feature = "debug-commands")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectMeta {
+    /// The idle time in seconds used by the LRU eviction policies.
+    Idle(u64),
+    /// The access frequency counter used by the LFU eviction policies.
+    Freq(u8),
+}
+
+#[cfg(feature = "debug-commands")]
+impl ToRedisArgs for ObjectMeta {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        match *self {
+            ObjectMeta::Idle(secs) => {
+                out.write_arg(b"IDLETIME");
+                out.write_arg(format!("{}", secs).as_bytes());
+            }
+            ObjectMeta::Freq(freq) => {
+                out.write_arg(b"FREQ");
+                out.write_arg(format!("{}", freq).as_bytes());
+            }
+        }
+    }
+
+    fn is_single_arg(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(feature = "geospatial")]
+enum GeoSearchFrom {
+    Member(Vec<Vec<u8>>),
+    LonLat(f64, f64),
+}
+
+#[cfg(feature = "geospatial")]
+enum GeoSearchBy {
+    Radius(f64, Vec<Vec<u8>>),
+    Box(f64, f64, Vec<Vec<u8>>),
+}
+
+/// Options for [`geo_search`][1] which is the `GEOSEARCH` command of Redis 6.2.
+///
+/// Both the center (`from_*`) and the shape of the area (`by_*`) are required.
+///
+/// [`count`](#method.count) limits the results to the N nearest items, while
+/// [`count_any`](#method.count_any) adds `ANY` so that the search returns as soon
+/// as N matching items are found. The latter is faster, but the returned items
+/// are not necessarily the nearest ones.
+///
+/// [1]: ./trait.Commands.html#method.geo_search
+///
+/// ```rust,no_run
+/// use redis::geo::{RadiusOrder, Unit};
+/// use redis_ac::GeoSearchOptions;
+///
+/// let opts = GeoSearchOptions::default()
+///     .from_lonlat(15.0, 37.0)
+///     .by_radius(200.0, Unit::Kilometers)
+///     .order(RadiusOrder::Asc)
+///     .count(3);
+/// ```
+#[cfg(feature = "geospatial")]
+#[derive(Default)]
+pub struct GeoSearchOptions {
+    from: Option<GeoSearchFrom>,
+    by: Option<GeoSearchBy>,
+    order: RadiusOrder,
+    count: Option<(usize, bool)>,
+    with_coord: bool,
+    with_dist: bool,
+    with_hash: bool,
+}
+
+#[cfg(feature = "geospatial")]
+impl GeoSearchOptions {
+    /// Search around the position of the given member.
+    pub fn from_member<M: ToRedisArgs>(mut self, member: M) -> Self {
+        self.from = Some(GeoSearchFrom::Member(member.to_redis_args()));
+        self
+    }
+
+    /// Search around the given position.
+    pub fn from_lonlat(mut self, longitude: f64, latitude: f64) -> Self {
+        self.from = Some(GeoSearchFrom::LonLat(longitude, latitude));
+        self
+    }
+
+    /// Search within the circle of the given radius.
+    pub fn by_radius(mut self, radius: f64, unit: Unit) -> Self {
+        self.by = Some(GeoSearchBy::Radius(radius, unit.to_redis_args()));
+        self
+    }
+
+    /// Search within the axis-aligned box of the given width and height.
+    pub fn by_box(mut self, width: f64, height: f64, unit: Unit) -> Self {
+        self.by = Some(GeoSearchBy::Box(width, height, unit.to_redis_args()));
+        self
+    }
+
+    /// Sort the returned items.
+    pub fn order(mut self, o: RadiusOrder) -> Self {
+        self.order = o;
+        self
+    }
+
+    /// Limit the results to the N nearest matching items.
+    pub fn count(mut self, n: usize) -> Self {
+        self.count = Some((n, false));
+        self
+    }
+
+    /// Limit the results to the first N matching items found, which are not
+    /// necessarily the nearest ones.
+    pub fn count_any(mut self, n: usize) -> Self {
+        self.count = Some((n, true));
+        self
+    }
+
+    /// Return the `longitude, latitude` coordinates of the matching items.
+    pub fn with_coord(mut self) -> Self {
+        self.with_coord = true;
+        self
+    }
+
+    /// Return the distance of the matching items from the center.
+    pub fn with_dist(mut self) -> Self {
+        self.with_dist = true;
+        self
+    }
+
+    /// Return the raw geohash of the matching items.
+    pub fn with_hash(mut self) -> Self {
+        self.with_hash = true;
+        self
+    }
+
+    /// Checks the options are accepted by the server.
+    ///
+    /// This fails if the center or the shape is missing, or if the count is
+    /// zero, which includes `ANY` without an effective count.
+    pub fn validate(&self) -> RedisResult<()> {
+        let err = |desc| Err(RedisError::from((ErrorKind::InvalidClientConfig, desc)));
+
+        match self.count {
+            _ if self.from.is_none() => err("GEOSEARCH requires the center"),
+            _ if self.by.is_none() => err("GEOSEARCH requires the shape"),
+            Some((0, true)) => err("GEOSEARCH requires a positive count with ANY"),
+            Some((0, false)) => err("GEOSEARCH requires a positive count"),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(feature = "geospatial")]
+impl ToRedisArgs for GeoSearchOptions {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        match self.from {
+            Some(GeoSearchFrom::Member(ref member)) => {
+                out.write_arg(b"FROMMEMBER");
+                for i in member {
+                    out.write_arg(i);
+                }
+            }
+            Some(GeoSearchFrom::LonLat(lon, lat)) => {
+                out.write_arg(b"FROMLONLAT");
+                out.write_arg(format!("{}", lon).as_bytes());
+                out.write_arg(format!("{}", lat).as_bytes());
+            }
+            None => {}
+        }
+
+        match self.by {
+            Some(GeoSearchBy::Radius(radius, ref unit)) => {
+                out.write_arg(b"BYRADIUS");
+                out.write_arg(format!("{}", radius).as_bytes());
+                for i in unit {
+                    out.write_arg(i);
+                }
+            }
+            Some(GeoSearchBy::Box(width, height, ref unit)) => {
+                out.write_arg(b"BYBOX");
+                out.write_arg(format!("{}", width).as_bytes());
+                out.write_arg(format!("{}", height).as_bytes());
+                for i in unit {
+                    out.write_arg(i);
+                }
+            }
+            None => {}
+        }
+
+        match self.order {
+            RadiusOrder::Asc => out.write_arg(b"ASC"),
+            RadiusOrder::Desc => out.write_arg(b"DESC"),
+            RadiusOrder::Unsorted => {}
+        }
+
+        if let Some((n, any)) = self.count {
+            out.write_arg(b"COUNT");
+            out.write_arg(format!("{}", n).as_bytes());
+            if any {
+                out.write_arg(b"ANY");
+            }
+        }
+
+        if self.with_coord {
+            out.write_arg(b"WITHCOORD");
+        }
+
+        if self.with_dist {
+            out.write_arg(b"WITHDIST");
+        }
+
+        if self.with_hash {
+            out.write_arg(b"WITHHASH");
+        }
+    }
+
+    fn is_single_arg(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,390 @@
+use futures::{future::Either, prelude::*, stream};
+use redis::{
+    aio::ConnectionLike, from_redis_value, Cmd, FromRedisValue, RedisError, RedisFuture,
+    ToRedisArgs, Value,
+};
+use std::{collections::VecDeque, marker::PhantomData, time::Duration};
+
+/// Stream over the results of [`Pipeline`](./struct.Pipeline.html).
+///
+/// Each item holds the results of a batch. The last item is `(Some(con), None)`
+/// which gives back the connection.
+pub type PipelineStream<C, RV> =
+    Box<dyn Stream<Item = (Option<C>, Option<Vec<RV>>), Error = RedisError> + Send>;
+
+/// Pipeline which sends many commands with few round trips.
+///
+/// Commands are queued with [`cmd`](#method.cmd) and [`arg`](#method.arg) like
+/// `redis::Pipeline`, with [`add_command`](#method.add_command), or with the
+/// helpers such as [`set`](#method.set) and [`get`](#method.get).
+/// [`query_async`](#method.query_async) sends them and returns all the results.
+/// A pipeline created by [`atomic`](#method.atomic) wraps the commands with
+/// `MULTI` and `EXEC` so that they are executed atomically.
+///
+/// ```rust,no_run
+/// use futures::prelude::*;
+/// use redis_ac::Pipeline;
+///
+/// # fn main() {
+/// let client = redis::Client::open("redis://127.0.0.1").unwrap();
+/// let connect = client.get_async_connection();
+///
+/// let f = connect.and_then(|con| {
+///     Pipeline::new()
+///         .set("key", 1)
+///         .cmd("INCR").arg("key")
+///         .get("key")
+///         .query_async(con)
+///         .map(|(_, (ok, n, v)): (_, (String, i64, i64))| {
+///             assert_eq!(ok, "OK");
+///             assert_eq!(n, 2);
+///             assert_eq!(v, 2);
+///         })
+/// }).map_err(|e| eprintln!("{}", e));
+///
+/// tokio::run(f);
+/// # }
+/// ```
+///
+/// A non-atomic pipeline can be split into multiple batches by the size of
+/// queued commands. With [`auto_flush`](#method.auto_flush), a new batch is
+/// started whenever the serialized size of the queued commands would exceed the
+/// limit, so that a huge number of commands doesn't end up with a single giant
+/// request. [`execute`](#method.execute) sends the batches one by one and
+/// yields the results of each batch as soon as it's done.
+///
+/// ```rust,no_run
+/// use futures::prelude::*;
+/// use redis_ac::Pipeline;
+///
+/// # fn main() {
+/// let client = redis::Client::open("redis://127.0.0.1").unwrap();
+/// let connect = client.get_async_connection();
+///
+/// let f = connect.and_then(|con| {
+///     (0..10000)
+///         .fold(Pipeline::new().auto_flush(64 * 1024), |p, i| p.set(i, i))
+///         .execute(con)
+///         .filter_map(|(_, results)| results)
+///         .for_each(|results: Vec<String>| {
+///             println!("{} commands done", results.len());
+///             Ok(())
+///         })
+/// }).map_err(|e| eprintln!("{}", e));
+///
+/// tokio::run(f);
+/// # }
+/// ```
+#[derive(Default)]
+pub struct Pipeline {
+    batches: VecDeque<redis::Pipeline>,
+    bytes: usize,
+    max_bytes: Option<usize>,
+    atomic: bool,
+    // The command being built by `cmd` and `arg`.
+    current: Option<Cmd>,
+}
+
+impl Pipeline {
+    /// Creates an empty pipeline, which sends all the commands in a single batch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty pipeline, which wraps the commands with `MULTI` and `EXEC`.
+    ///
+    /// An atomic pipeline is never split into batches, so
+    /// [`auto_flush`](#method.auto_flush) has no effect.
+    pub fn atomic() -> Self {
+        Self {
+            atomic: true,
+            ..Self::default()
+        }
+    }
+
+    /// Starts a new batch when the serialized size of the queued commands of the
+    /// current batch would exceed `max_bytes`.
+    ///
+    /// A single command larger than `max_bytes` is sent in its own batch.
+    pub fn auto_flush(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Queues a command.
+    pub fn add_command(mut self, cmd: Cmd) -> Self {
+        self.finish();
+        self.push(cmd);
+        self
+    }
+
+    /// Starts a new command to which [`arg`](#method.arg) appends arguments.
+    pub fn cmd(mut self, name: &str) -> Self {
+        self.finish();
+        self.current = Some(redis::cmd(name));
+        self
+    }
+
+    /// Appends an argument to the command started by [`cmd`](#method.cmd).
+    ///
+    /// # Panics
+    ///
+    /// Panics if no command is started.
+    pub fn arg<T: ToRedisArgs>(mut self, arg: T) -> Self {
+        self.current.as_mut().expect("No command on stack").arg(arg);
+        self
+    }
+
+    /// Queues `SET`.
+    pub fn set<K: ToRedisArgs, V: ToRedisArgs>(self, key: K, value: V) -> Self {
+        self.cmd("SET").arg(key).arg(value)
+    }
+
+    /// Queues `GET`.
+    pub fn get<K: ToRedisArgs>(self, key: K) -> Self {
+        self.cmd("GET").arg(key)
+    }
+
+    /// Queues `DEL`.
+    pub fn del<K: ToRedisArgs>(self, key: K) -> Self {
+        self.cmd("DEL").arg(key)
+    }
+
+    /// Queues `INCRBY`.
+    pub fn incr<K: ToRedisArgs, D: ToRedisArgs>(self, key: K, delta: D) -> Self {
+        self.cmd("INCRBY").arg(key).arg(delta)
+    }
+
+    /// Queues `EXPIRE`.
+    pub fn expire<K: ToRedisArgs>(self, key: K, seconds: usize) -> Self {
+        self.cmd("EXPIRE").arg(key).arg(seconds)
+    }
+
+    // Queues the command being built.
+    fn finish(&mut self) {
+        if let Some(cmd) = self.current.take() {
+            self.push(cmd);
+        }
+    }
+
+    fn bytes_of(cmd: &Cmd) -> usize {
+        cmd.get_packed_command().len()
+    }
+
+    // Whether a command of the size needs a new batch.
+    fn needs_batch(&self, bytes: usize) -> bool {
+        let full = !self.atomic
+            && self
+                .max_bytes
+                .map(|max| self.bytes + bytes > max)
+                .unwrap_or(false);
+
+        self.batches.is_empty() || (full && self.bytes > 0)
+    }
+
+    fn push(&mut self, cmd: Cmd) {
+        let bytes = Self::bytes_of(&cmd);
+
+        if self.needs_batch(bytes) {
+            let mut p = redis::pipe();
+            if self.atomic {
+                p.atomic();
+            }
+            self.batches.push_back(p);
+            self.bytes = 0;
+        }
+
+        if let Some(p) = self.batches.back_mut() {
+            p.add_command(cmd);
+        }
+        self.bytes += bytes;
+    }
+
+    /// Returns the number of batches to be sent.
+    pub fn batches(&self) -> usize {
+        let current = self
+            .current
+            .as_ref()
+            .map(|cmd| self.needs_batch(Self::bytes_of(cmd)))
+            .unwrap_or(false);
+
+        self.batches.len() + current as usize
+    }
+
+    /// Sends all the commands, and returns all the results.
+    ///
+    /// The results of all the batches are parsed together as `RV`, such as a tuple
+    /// or a `Vec`. Use [`execute`](#method.execute) to process the results of
+    /// each batch as soon as it's done.
+    pub fn query_async<C, RV>(self, con: C) -> RedisFuture<(C, RV)>
+    where
+        C: ConnectionLike + Send + 'static,
+        RV: FromRedisValue + Send + 'static,
+    {
+        Box::new(
+            self.execute(con)
+                .fold((None, Vec::new()), |(_, mut values), (con, batch)| {
+                    values.extend(batch.unwrap_or_default());
+                    Ok::<_, RedisError>((con, values))
+                })
+                .and_then(|(con, values): (Option<C>, Vec<Value>)| {
+                    // `PipelineStream` returns the connection with the last item.
+                    let con = con.expect("PipelineStream didn't return connection");
+                    Ok((con, from_redis_value(&Value::Bulk(values))?))
+                }),
+        )
+    }
+
+    /// Sends the batches one by one, and yields the results of each batch.
+    ///
+    /// Every command is expected to return `RV`. The stream stops at the first error.
+    pub fn execute<C, RV>(mut self, con: C) -> PipelineStream<C, RV>
+    where
+        C: ConnectionLike + Send + 'static,
+        RV: FromRedisValue + Send + 'static,
+    {
+        self.finish();
+
+        Box::new(stream::unfold(
+            (Some(con), self.batches),
+            |(con, mut batches)| {
+                let con = con?;
+                Some(match batches.pop_front() {
+                    Some(p) => {
+                        Either::A(p.query_async(con).map(move |(con, rvs): (C, Vec<RV>)| {
+                            ((None, Some(rvs)), (Some(con), batches))
+                        }))
+                    }
+                    None => Either::B(Ok(((Some(con), None), (None, batches))).into_future()),
+                })
+            },
+        ))
+    }
+}
+
+/// Pipeline which keeps track of the result type of each queued command.
+///
+/// Every call of [`add_command`](#method.add_command) appends the expected result type of the
+/// command to the type parameter `T`. [`execute`](#method.execute) then sends
+/// all the commands at once and returns the results as a tuple of those types.
+/// Up to 12 commands can be queued.
+///
+/// ```rust,no_run
+/// use futures::prelude::*;
+/// use redis_ac::TypedPipeline;
+///
+/// # fn main() {
+/// let client = redis::Client::open("redis://127.0.0.1").unwrap();
+/// let connect = client.get_async_connection();
+///
+/// let f = connect.and_then(|con| {
+///     TypedPipeline::new()
+///         .add_command::<String>(redis::cmd("SET").arg("key").arg(1).clone())
+///         .add_command::<i64>(redis::cmd("INCR").arg("key").clone())
+///         .add_command::<bool>(redis::cmd("EXISTS").arg("key").clone())
+///         .execute(con)
+///         .map(|(_, (ok, n, exists))| {
+///             assert_eq!(ok, "OK");
+///             assert_eq!(n, 2);
+///             assert!(exists);
+///         })
+/// }).map_err(|e| eprintln!("{}", e));
+///
+/// tokio::run(f);
+/// # }
+/// ```
+pub struct TypedPipeline<T> {
+    pipe: redis::Pipeline,
+    _result: PhantomData<T>,
+}
+
+impl TypedPipeline<()> {
+    /// Creates an empty pipeline.
+    pub fn new() -> Self {
+        Self {
+            pipe: redis::pipe(),
+            _result: PhantomData,
+        }
+    }
+}
+
+impl Default for TypedPipeline<()> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> TypedPipeline<T> {
+    /// Wraps the queued commands with `MULTI` and `EXEC`
+    /// so that they are executed atomically.
+    pub fn atomic(mut self) -> Self {
+        self.pipe.atomic();
+        self
+    }
+
+    fn push<U>(mut self, cmd: Cmd) -> TypedPipeline<U> {
+        self.pipe.add_command(cmd);
+        TypedPipeline {
+            pipe: self.pipe,
+            _result: PhantomData,
+        }
+    }
+
+    /// Sends all the queued commands and returns their results as a tuple.
+    pub fn execute<C>(self, con: C) -> RedisFuture<(C, T)>
+    where
+        C: ConnectionLike + Send + 'static,
+        T: FromRedisValue + Send + 'static,
+    {
+        self.pipe.query_async(con)
+    }
+
+    /// Sends all the queued commands, and then waits for them to be acknowledged
+    /// by at least `numreplicas` replicas with `WAIT`.
+    ///
+    /// Returns the results of the commands along with the number of replicas which
+    /// acknowledged the writes, which can be less than `numreplicas` on timeout.
+    /// Note that a zero `timeout` makes `WAIT` block until enough replicas respond.
+    pub fn execute_durable<C>(
+        self,
+        con: C,
+        numreplicas: usize,
+        timeout: Duration,
+    ) -> RedisFuture<(C, (T, usize))>
+    where
+        C: ConnectionLike + Send + 'static,
+        T: FromRedisValue + Send + 'static,
+    {
+        Box::new(self.execute(con).and_then(move |(con, results)| {
+            redis::cmd("WAIT")
+                .arg(numreplicas)
+                .arg(timeout.as_millis() as u64)
+                .query_async(con)
+                .map(move |(con, acked)| (con, (results, acked)))
+        }))
+    }
+}
+
+macro_rules! implement_add {
+    ($($ty:ident)*) => {
+        impl<$($ty),*> TypedPipeline<($($ty,)*)> {
+            /// Queues a command whose result is parsed as `RV`.
+            pub fn add_command<RV>(self, cmd: Cmd) -> TypedPipeline<($($ty,)* RV,)> {
+                self.push(cmd)
+            }
+        }
+    };
+}
+
+implement_add!();
+implement_add!(T1);
+implement_add!(T1 T2);
+implement_add!(T1 T2 T3);
+implement_add!(T1 T2 T3 T4);
+implement_add!(T1 T2 T3 T4 T5);
+implement_add!(T1 T2 T3 T4 T5 T6);
+implement_add!(T1 T2 T3 T4 T5 T6 T7);
+implement_add!(T1 T2 T3 T4 T5 T6 T7 T8);
+implement_add!(T1 T2 T3 T4 T5 T6 T7 T8 T9);
+implement_add!(T1 T2 T3 T4 T5 T6 T7 T8 T9 T10);
+implement_add!(T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 T11);
@@ -0,0 +1,225 @@
+use futures::{future::Either, prelude::*};
+use redis::{aio::Connection, Client, ErrorKind, RedisError, RedisFuture};
+use std::sync::{Arc, Mutex};
+
+/// Manager which creates and checks connections for connection pools.
+///
+/// This follows the shape of `ManageConnection` of common pool crates such as
+/// `bb8`, so that it can be wrapped to plug into them. The connections it
+/// creates implement [`Commands`][1] as usual. [`Pool`][2] is a minimal pool
+/// built on it.
+///
+/// [1]: ./trait.Commands.html
+/// [2]: ./struct.Pool.html
+#[derive(Debug, Clone)]
+pub struct ConnectionManager {
+    client: Client,
+}
+
+impl ConnectionManager {
+    /// Creates a manager which connects with the given client.
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+
+    /// Creates a new connection.
+    pub fn connect(&self) -> RedisFuture<Connection> {
+        Box::new(self.client.get_async_connection())
+    }
+
+    /// Checks the connection is still alive by `PING`.
+    ///
+    /// This returns the connection if it answered, or fails otherwise.
+    /// A connection which failed can't be used anymore.
+    pub fn is_valid(&self, con: Connection) -> RedisFuture<Connection> {
+        Box::new(
+            redis::cmd("PING")
+                .query_async(con)
+                .and_then(|(con, pong): (_, String)| {
+                    if pong == "PONG" {
+                        Ok(con)
+                    } else {
+                        Err(RedisError::from((
+                            ErrorKind::ResponseError,
+                            "unexpected reply to PING",
+                            pong,
+                        )))
+                    }
+                }),
+        )
+    }
+
+    /// Checks quickly if the connection is known to be broken, without querying.
+    ///
+    /// A command which failed consumes the connection, so a connection given
+    /// back by users is never known to be broken. This always returns `false`;
+    /// use [`is_valid`](#method.is_valid) to check the connection.
+    pub fn has_broken(&self, _con: &mut Connection) -> bool {
+        false
+    }
+}
+
+/// The way to clean up the state of a connection given back to [`Pool`][1].
+///
+/// Connections can carry state such as subscriptions, the selected database
+/// or an open transaction. The cleanup is done right before an idle connection
+/// is checked out again, and it costs a round-trip. It also checks the connection
+/// in place of [`ConnectionManager::is_valid`][2]. If it fails, the connection
+/// is replaced with a new one, which is always clean.
+///
+/// [1]: ./struct.Pool.html
+/// [2]: ./struct.ConnectionManager.html#method.is_valid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnReturn {
+    /// Reuse the connection as it is.
+    Keep,
+    /// Issue `RESET`, which requires Redis 6.2 or later.
+    Reset,
+    /// Issue `SELECT 0`, and `DISCARD` if it turns out to be in a transaction.
+    ///
+    /// This is for older servers without `RESET`. Subscribed connections reject
+    /// `SELECT`, so they are replaced with new ones.
+    Cleanup,
+}
+
+fn unexpected(cmd: &str, reply: String) -> RedisError {
+    RedisError::from((
+        ErrorKind::ResponseError,
+        "unexpected reply to cleanup",
+        format!("{}: {}", cmd, reply),
+    ))
+}
+
+fn reset(con: Connection) -> RedisFuture<Connection> {
+    Box::new(
+        redis::cmd("RESET")
+            .query_async(con)
+            .and_then(|(con, reply): (_, String)| {
+                if reply == "RESET" {
+                    Ok(con)
+                } else {
+                    Err(unexpected("RESET", reply))
+                }
+            }),
+    )
+}
+
+fn cleanup(con: Connection) -> RedisFuture<Connection> {
+    Box::new(
+        redis::cmd("SELECT")
+            .arg(0)
+            .query_async(con)
+            .and_then(|(con, reply): (_, String)| match reply.as_str() {
+                "OK" => Either::A(Ok(con).into_future()),
+                // Queued in a transaction.
+                "QUEUED" => Either::B(
+                    redis::cmd("DISCARD")
+                        .query_async(con)
+                        .and_then(|(con, ()): (_, ())| cleanup(con)),
+                ),
+                _ => Either::A(Err(unexpected("SELECT", reply)).into_future()),
+            }),
+    )
+}
+
+/// Minimal pool of connections created by [`ConnectionManager`][1].
+///
+/// Connections are checked out by [`get`](#method.get) and given back by
+/// [`put`](#method.put) after use. Idle connections are checked by
+/// [`ConnectionManager::is_valid`][2] on checkout, and a new connection is
+/// created instead if it's broken. The state of connections can be cleaned up
+/// on checkout with [`on_return`](#method.on_return).
+///
+/// [1]: ./struct.ConnectionManager.html
+/// [2]: ./struct.ConnectionManager.html#method.is_valid
+///
+/// ```rust,no_run
+/// use futures::prelude::*;
+/// use redis_ac::{Commands, ConnectionManager, Pool};
+///
+/// # fn main() {
+/// let client = redis::Client::open("redis://127.0.0.1").unwrap();
+/// let pool = Pool::new(ConnectionManager::new(client));
+/// let p = pool.clone();
+///
+/// let f = pool
+///     .get()
+///     .and_then(|con| con.get("key"))
+///     .map(move |(con, v): (_, Option<String>)| {
+///         println!("{:?}", v);
+///         p.put(con);
+///     })
+///     .map_err(|e| eprintln!("{}", e));
+///
+/// tokio::run(f);
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Pool {
+    manager: ConnectionManager,
+    idle: Arc<Mutex<Vec<Connection>>>,
+    max_idle: usize,
+    on_return: OnReturn,
+}
+
+impl Pool {
+    /// Creates a pool which keeps up to 16 idle connections.
+    pub fn new(manager: ConnectionManager) -> Self {
+        Self {
+            manager,
+            idle: Arc::new(Mutex::new(Vec::new())),
+            max_idle: 16,
+            on_return: OnReturn::Keep,
+        }
+    }
+
+    /// Sets the maximum number of idle connections to keep.
+    pub fn max_idle(mut self, n: usize) -> Self {
+        self.max_idle = n;
+        self
+    }
+
+    /// Sets how to clean up connections given back to the pool.
+    /// Defaults to [`OnReturn::Keep`](./enum.OnReturn.html#variant.Keep).
+    pub fn on_return(mut self, on_return: OnReturn) -> Self {
+        self.on_return = on_return;
+        self
+    }
+
+    /// Checks out a connection, reusing an idle one if it's still valid.
+    pub fn get(&self) -> RedisFuture<Connection> {
+        let idle = self.idle.lock().unwrap().pop();
+
+        match idle {
+            Some(con) => {
+                let manager = self.manager.clone();
+                let check = match self.on_return {
+                    OnReturn::Keep => self.manager.is_valid(con),
+                    OnReturn::Reset => reset(con),
+                    OnReturn::Cleanup => cleanup(con),
+                };
+                Box::new(check.or_else(move |_| manager.connect()))
+            }
+            None => self.manager.connect(),
+        }
+    }
+
+    /// Gives back a connection so that it can be checked out again.
+    ///
+    /// The connection is dropped if the pool already has enough idle connections.
+    pub fn put(&self, mut con: Connection) {
+        if self.manager.has_broken(&mut con) {
+            return;
+        }
+
+        let mut idle = self.idle.lock().unwrap();
+        if idle.len() < self.max_idle {
+            idle.push(con);
+        }
+    }
+
+    /// Returns the number of idle connections.
+    pub fn idle(&self) -> usize {
+        self.idle.lock().unwrap().len()
+    }
+}
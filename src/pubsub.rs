@@ -1,12 +1,37 @@
-use futures::{prelude::*, try_ready};
+use futures::{
+    future::{self, Either, Loop},
+    prelude::*,
+    sync::mpsc,
+    try_ready,
+};
 use redis::{
-    aio::Connection, from_redis_value, ControlFlow, FromRedisValue, RedisError, RedisFuture,
+    aio::{Connection, ConnectionLike},
+    from_redis_value, Client, ControlFlow, ErrorKind, FromRedisValue, RedisError, RedisFuture,
     RedisResult, ToRedisArgs, Value,
 };
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    hash::{BuildHasher, Hasher},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio_timer::Delay;
+
+/// The kind of a pubsub message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MsgKind {
+    /// A message to a subscribed channel.
+    Message,
+    /// A message to a channel matching a subscribed pattern.
+    PMessage,
+    /// A message to a subscribed shard channel.
+    SMessage,
+}
 
 /// Represents a pubsub message.
 #[derive(Debug)]
 pub struct Msg {
+    kind: MsgKind,
     payload: Value,
     channel: Value,
     pattern: Option<Value>,
@@ -15,6 +40,11 @@ pub struct Msg {
 /// This holds the data that comes from listening to a pubsub
 /// connection.  It only contains actual message data.
 impl Msg {
+    /// Returns the kind of this message.
+    pub fn kind(&self) -> MsgKind {
+        self.kind
+    }
+
     /// Returns the channel this message came on.
     pub fn get_channel<T: FromRedisValue>(&self) -> RedisResult<T> {
         from_redis_value(&self.channel)
@@ -72,8 +102,15 @@ impl Msg {
 /// messages, and unsubscribing from the same list of channels once
 /// a ControlFlow::Break is encountered.
 ///
-/// Once (p)subscribe returns Ok(U), the connection is again safe to use
-/// for calling other methods.
+/// Once (p)subscribe returns, the connection is again safe to use
+/// for calling other methods. This is also the case when the closure returns
+/// an error, as the connection is unsubscribed before it's returned.
+///
+/// If the future is dropped before completion, e.g. on panic in the closure,
+/// the connection is dropped with it. Closing the connection makes the server
+/// remove its subscriptions, so no connection is left in the pub/sub mode.
+/// A connection subscribed by other means such as `redis::cmd("SUBSCRIBE")`
+/// can be brought back with [`unsubscribe_now`](#tymethod.unsubscribe_now).
 ///
 /// Note that the error type returned from the closure
 /// corresponds to the error type of the Result type
@@ -153,6 +190,111 @@ pub trait PubSubCommands: Sized {
         E: Send + 'static,
         R: IntoFuture<Item = ControlFlow<U>, Error = E>,
         P: ToRedisArgs;
+
+    /// Subscribe to a list of shard channels using SSUBSCRIBE and run the provided
+    /// closure for each message received, like [`subscribe`](#tymethod.subscribe).
+    ///
+    /// Messages published by `SPUBLISH` are delivered only within the shard of the
+    /// channel in a cluster. The channels are unsubscribed by `SUNSUBSCRIBE` once
+    /// `ControlFlow::Break` is observed. This requires Redis 7.0 or later.
+    fn ssubscribe<C, R, F, U, E>(self, _: C, _: F) -> RedisFuture<(Self, Result<U, E>)>
+    where
+        F: FnMut(Msg) -> R + Send + 'static,
+        R: Send + 'static,
+        R::Future: Send + 'static,
+        U: Send + 'static,
+        E: Send + 'static,
+        R: IntoFuture<Item = ControlFlow<U>, Error = E>,
+        C: ToRedisArgs;
+
+    /// Subscribe to a list of channels like [`subscribe`](#tymethod.subscribe),
+    /// returning a handle to change the subscriptions while the future is running.
+    ///
+    /// The handle sends `SUBSCRIBE` and `UNSUBSCRIBE` commands into the subscribed
+    /// connection. As the connection is busy waiting for messages, the handle wakes
+    /// it up by publishing to a private channel through `notifier`, which needs to
+    /// be another cloneable connection like [`redis::aio::SharedConnection`][1].
+    /// The messages of the private channel aren't passed to the closure.
+    ///
+    /// [1]: https://docs.rs/redis/0.13.0/redis/aio/struct.SharedConnection.html
+    #[allow(clippy::type_complexity)]
+    fn subscribe_with_handle<N, C, R, F, U, E>(
+        self,
+        notifier: N,
+        channels: C,
+        f: F,
+    ) -> (PubSubHandle<N>, RedisFuture<(Self, Result<U, E>)>)
+    where
+        N: ConnectionLike + Clone + Send + 'static,
+        F: FnMut(Msg) -> R + Send + 'static,
+        R: Send + 'static,
+        R::Future: Send + 'static,
+        U: Send + 'static,
+        E: Send + 'static,
+        R: IntoFuture<Item = ControlFlow<U>, Error = E>,
+        C: ToRedisArgs;
+
+    /// Subscribe to a list of channels using SUBSCRIBE, and return a stream of
+    /// the messages received.
+    ///
+    /// Unlike [`subscribe`](#tymethod.subscribe), this allows composing the
+    /// messages with other streams and futures, e.g. by `select` or timeouts.
+    /// The stream ends when the connection is closed. Dropping the stream drops
+    /// the connection with it, which removes the subscriptions.
+    fn subscribe_stream<C: ToRedisArgs>(self, channels: C) -> RedisFuture<RedisPubSubStream>;
+
+    /// Subscribe to a list of channel patterns using PSUBSCRIBE, and return a
+    /// stream of the messages received, like [`subscribe_stream`](#tymethod.subscribe_stream).
+    fn psubscribe_stream<P: ToRedisArgs>(self, patterns: P) -> RedisFuture<RedisPubSubStream>;
+
+    /// Unsubscribe from all the channels and patterns, and wait until the
+    /// connection gets out of the pub/sub mode.
+    ///
+    /// Messages received meanwhile are discarded. This can be used on a
+    /// connection which isn't subscribed to anything as well.
+    fn unsubscribe_now(self) -> RedisFuture<Self>;
+
+    /// Subscribe to a list of channels like [`subscribe`](#tymethod.subscribe),
+    /// counting the number of messages received on each channel.
+    ///
+    /// Once `ControlFlow::Break` is observed, the counts are returned along with
+    /// the value of `ControlFlow::Break`. They are keyed by the channel names
+    /// given by [`Msg::get_channel_name`](./struct.Msg.html#method.get_channel_name).
+    #[allow(clippy::type_complexity)]
+    fn subscribe_multi<C, R, F, U, E>(
+        self,
+        channels: C,
+        mut f: F,
+    ) -> RedisFuture<(Self, Result<(U, HashMap<String, usize>), E>)>
+    where
+        Self: Send + 'static,
+        F: FnMut(Msg) -> R + Send + 'static,
+        R: Send + 'static,
+        R::Future: Send + 'static,
+        U: Send + 'static,
+        E: Send + 'static,
+        R: IntoFuture<Item = ControlFlow<U>, Error = E>,
+        C: ToRedisArgs,
+    {
+        let counts = Arc::new(Mutex::new(HashMap::new()));
+        let counter = counts.clone();
+
+        let fut = self
+            .subscribe(channels, move |msg| {
+                *counter
+                    .lock()
+                    .unwrap()
+                    .entry(msg.get_channel_name().to_string())
+                    .or_insert(0) += 1;
+                f(msg)
+            })
+            .map(move |(con, res)| {
+                let counts = std::mem::take(&mut *counts.lock().unwrap());
+                (con, res.map(|item| (item, counts)))
+            });
+
+        Box::new(fut)
+    }
 }
 
 macro_rules! unwrap_or {
@@ -171,21 +313,22 @@ fn value_to_msg(value: Value) -> RedisResult<Option<Msg>> {
     let mut iter = raw_msg.into_iter();
     let msg_type: String = from_redis_value(&unwrap_or!(iter.next(), return Ok(None)))?;
     let mut pattern = None;
-    let payload;
-    let channel;
 
-    if msg_type == "message" {
-        channel = unwrap_or!(iter.next(), return Ok(None));
-        payload = unwrap_or!(iter.next(), return Ok(None));
-    } else if msg_type == "pmessage" {
+    let kind = match msg_type.as_str() {
+        "message" => MsgKind::Message,
+        "pmessage" => MsgKind::PMessage,
+        "smessage" => MsgKind::SMessage,
+        _ => return Ok(None),
+    };
+
+    if kind == MsgKind::PMessage {
         pattern = Some(unwrap_or!(iter.next(), return Ok(None)));
-        channel = unwrap_or!(iter.next(), return Ok(None));
-        payload = unwrap_or!(iter.next(), return Ok(None));
-    } else {
-        return Ok(None);
     }
+    let channel = unwrap_or!(iter.next(), return Ok(None));
+    let payload = unwrap_or!(iter.next(), return Ok(None));
 
     Ok(Some(Msg {
+        kind,
         payload,
         channel,
         pattern,
@@ -228,6 +371,265 @@ impl PubSubCommands for Connection {
                 .and_then(move |(con, ())| RedisPubSubFuture::new(con, f)),
         )
     }
+
+    fn ssubscribe<C, R, F, U, E>(self, channel: C, f: F) -> RedisFuture<(Self, Result<U, E>)>
+    where
+        F: FnMut(Msg) -> R + Send + 'static,
+        R: Send + 'static,
+        R::Future: Send + 'static,
+        U: Send + 'static,
+        E: Send + 'static,
+        R: IntoFuture<Item = ControlFlow<U>, Error = E>,
+        C: ToRedisArgs,
+    {
+        Box::new(
+            redis::cmd("SSUBSCRIBE")
+                .arg(channel)
+                .query_async(self)
+                .and_then(move |(con, ())| RedisPubSubFuture::sharded(con, f)),
+        )
+    }
+
+    fn subscribe_with_handle<N, C, R, F, U, E>(
+        self,
+        notifier: N,
+        channels: C,
+        f: F,
+    ) -> (PubSubHandle<N>, RedisFuture<(Self, Result<U, E>)>)
+    where
+        N: ConnectionLike + Clone + Send + 'static,
+        F: FnMut(Msg) -> R + Send + 'static,
+        R: Send + 'static,
+        R::Future: Send + 'static,
+        U: Send + 'static,
+        E: Send + 'static,
+        R: IntoFuture<Item = ControlFlow<U>, Error = E>,
+        C: ToRedisArgs,
+    {
+        let (tx, rx) = mpsc::unbounded();
+        let wake = wake_channel();
+        let handle = PubSubHandle {
+            con: notifier,
+            wake: wake.clone(),
+            tx,
+        };
+
+        // The private channel goes first, so that it's subscribed once the
+        // first reply is read.
+        let fut = redis::cmd("SUBSCRIBE")
+            .arg(&wake)
+            .arg(channels)
+            .query_async(self)
+            .and_then(move |(con, ())| {
+                RedisPubSubFuture::with_control(con, f, Control { wake, rx })
+            });
+
+        (handle, Box::new(fut))
+    }
+
+    fn subscribe_stream<C: ToRedisArgs>(self, channels: C) -> RedisFuture<RedisPubSubStream> {
+        Box::new(
+            redis::cmd("SUBSCRIBE")
+                .arg(channels)
+                .query_async(self)
+                .map(|(con, ())| RedisPubSubStream::new(con)),
+        )
+    }
+
+    fn psubscribe_stream<P: ToRedisArgs>(self, patterns: P) -> RedisFuture<RedisPubSubStream> {
+        Box::new(
+            redis::cmd("PSUBSCRIBE")
+                .arg(patterns)
+                .query_async(self)
+                .map(|(con, ())| RedisPubSubStream::new(con)),
+        )
+    }
+
+    fn unsubscribe_now(self) -> RedisFuture<Self> {
+        unsubscribe_all(self)
+    }
+}
+
+// Whether the value is the reply to the unsubscribe command with no subscription left.
+fn is_unsubscribed(value: &Value, command: &[u8]) -> bool {
+    match value {
+        Value::Bulk(items) if items.len() == 3 => match (&items[0], &items[2]) {
+            (Value::Data(kind), Value::Int(0)) => kind == command,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+// Reads the replies until the last one of the unsubscribe command.
+fn read_until_unsubscribed(
+    con: Connection,
+    packed: Vec<u8>,
+    command: &'static [u8],
+) -> RedisFuture<Connection> {
+    Box::new(
+        con.req_packed_commands(packed, 0, 0)
+            .and_then(move |(con, _)| {
+                future::loop_fn(con, move |con| {
+                    con.read_response().map(move |(con, value)| {
+                        if is_unsubscribed(&value, command) {
+                            Loop::Break(con)
+                        } else {
+                            Loop::Continue(con)
+                        }
+                    })
+                })
+            }),
+    )
+}
+
+fn unsubscribe_all(con: Connection) -> RedisFuture<Connection> {
+    // Both commands reply once per subscription, so read the replies
+    // until the last one of `PUNSUBSCRIBE`.
+    let mut packed = redis::cmd("UNSUBSCRIBE").get_packed_command();
+    packed.extend(redis::cmd("PUNSUBSCRIBE").get_packed_command());
+    read_until_unsubscribed(con, packed, b"punsubscribe")
+}
+
+fn unsubscribe_sharded(con: Connection) -> RedisFuture<Connection> {
+    let packed = redis::cmd("SUNSUBSCRIBE").get_packed_command();
+    read_until_unsubscribed(con, packed, b"sunsubscribe")
+}
+
+/// Subscribes to the reply channel with a new connection, publishes the request
+/// with `con`, and waits for a message on the reply channel until the timeout.
+pub(crate) fn request_reply<C, Q, P, R>(
+    con: C,
+    client: &Client,
+    request_channel: Q,
+    payload: P,
+    reply_channel: R,
+    timeout: Duration,
+) -> RedisFuture<(C, Option<Msg>)>
+where
+    C: ConnectionLike + Send + 'static,
+    Q: ToRedisArgs,
+    P: ToRedisArgs,
+    R: ToRedisArgs,
+{
+    let mut subscribe = redis::cmd("SUBSCRIBE");
+    subscribe.arg(reply_channel);
+    let mut publish = redis::cmd("PUBLISH");
+    publish.arg(request_channel).arg(payload);
+    let deadline = Instant::now() + timeout;
+
+    Box::new(
+        client
+            .get_async_connection()
+            .and_then(move |sub| subscribe.query_async(sub))
+            // Publish after subscribing so that the reply isn't missed.
+            .and_then(move |(sub, ()): (Connection, ())| {
+                publish
+                    .query_async(con)
+                    .map(move |(con, _): (C, usize)| (con, sub))
+            })
+            .and_then(move |(con, sub)| {
+                let recv = future::loop_fn(sub, |sub| {
+                    sub.read_response().and_then(|(sub, value)| {
+                        Ok(match value_to_msg(value)? {
+                            Some(msg) => Loop::Break(msg),
+                            None => Loop::Continue(sub),
+                        })
+                    })
+                });
+                let timeout = Delay::new(deadline).map_err(|e| {
+                    RedisError::from((ErrorKind::IoError, "timer error", e.to_string()))
+                });
+
+                // Either way, the subscriber is dropped, which closes
+                // the connection and so removes the subscription.
+                recv.select2(timeout).then(move |res| match res {
+                    Ok(Either::A((msg, _))) => Ok((con, Some(msg))),
+                    Ok(Either::B(_)) => Ok((con, None)),
+                    Err(Either::A((e, _))) | Err(Either::B((e, _))) => Err(e),
+                })
+            }),
+    )
+}
+
+// Creates a channel name unique to a subscription.
+fn wake_channel() -> String {
+    let mut h = RandomState::new().build_hasher();
+    h.write_u32(std::process::id());
+    format!("redis-ac:pubsub-wake:{:016x}", h.finish())
+}
+
+/// Handle to change the subscriptions of a running pubsub future.
+///
+/// This is returned by [`subscribe_with_handle`][1]. The futures of the methods
+/// finish once the command is queued and the subscribed connection is woken up,
+/// and fail if the pubsub future has finished. The command is sent by the pubsub
+/// future, so it takes effect a little later; messages published right after
+/// the future finishes may be missed.
+///
+/// [1]: ./trait.PubSubCommands.html#tymethod.subscribe_with_handle
+#[derive(Clone)]
+pub struct PubSubHandle<C> {
+    con: C,
+    wake: String,
+    tx: mpsc::UnboundedSender<Vec<u8>>,
+}
+
+impl<C> PubSubHandle<C>
+where
+    C: ConnectionLike + Clone + Send + 'static,
+{
+    /// Subscribes to more channels with `SUBSCRIBE`.
+    pub fn subscribe<K: ToRedisArgs>(&self, channels: K) -> RedisFuture<()> {
+        self.send("SUBSCRIBE", channels.to_redis_args())
+    }
+
+    /// Unsubscribes from the channels with `UNSUBSCRIBE`.
+    ///
+    /// Unlike the command, no channel means no change rather than all the channels.
+    pub fn unsubscribe<K: ToRedisArgs>(&self, channels: K) -> RedisFuture<()> {
+        self.send("UNSUBSCRIBE", channels.to_redis_args())
+    }
+
+    /// Subscribes to more channel patterns with `PSUBSCRIBE`.
+    pub fn psubscribe<P: ToRedisArgs>(&self, patterns: P) -> RedisFuture<()> {
+        self.send("PSUBSCRIBE", patterns.to_redis_args())
+    }
+
+    /// Unsubscribes from the channel patterns with `PUNSUBSCRIBE`.
+    ///
+    /// Unlike the command, no pattern means no change rather than all the patterns.
+    pub fn punsubscribe<P: ToRedisArgs>(&self, patterns: P) -> RedisFuture<()> {
+        self.send("PUNSUBSCRIBE", patterns.to_redis_args())
+    }
+
+    fn send(&self, name: &str, args: Vec<Vec<u8>>) -> RedisFuture<()> {
+        // Without arguments, the command would apply to all including the private channel.
+        if args.is_empty() {
+            return Box::new(future::ok(()));
+        }
+        let packed = redis::cmd(name).arg(args).get_packed_command();
+        if self.tx.unbounded_send(packed).is_err() {
+            return Box::new(future::err(RedisError::from((
+                ErrorKind::IoError,
+                "pubsub future finished",
+            ))));
+        }
+
+        Box::new(
+            redis::cmd("PUBLISH")
+                .arg(&self.wake)
+                .arg("")
+                .query_async(self.con.clone())
+                .map(|(_, _): (C, usize)| ()),
+        )
+    }
+}
+
+// The private channel to wake up a pubsub future, and the commands to send.
+struct Control {
+    wake: String,
+    rx: mpsc::UnboundedReceiver<Vec<u8>>,
 }
 
 /// Stream over items of pubsub commands.
@@ -242,7 +644,12 @@ where
     // Set when processing a message.
     proc: Option<R::Future>,
     // Set when waiting for a response to unsubscribe commands.
-    fin: Option<RedisFuture<(Connection, U)>>,
+    fin: Option<RedisFuture<(Connection, Result<U, E>)>>,
+    // Set when sending commands from the handle.
+    send: Option<RedisFuture<(Connection, Vec<Value>)>>,
+    ctrl: Option<Control>,
+    // Whether subscribed to shard channels.
+    sharded: bool,
     callback: F,
 }
 
@@ -251,6 +658,7 @@ where
     F: FnMut(Msg) -> R,
     R: IntoFuture<Item = ControlFlow<U>, Error = E>,
     U: Send + 'static,
+    E: Send + 'static,
 {
     pub(crate) fn new(con: Connection, callback: F) -> Self {
         Self {
@@ -258,16 +666,63 @@ where
             recv: Some(Box::new(con.read_response())),
             proc: None,
             fin: None,
+            send: None,
+            ctrl: None,
+            sharded: false,
             callback,
         }
     }
 
-    fn clear_active_subscriptions(&self, con: Connection, item: U) -> RedisFuture<(Connection, U)> {
-        let fut = redis::cmd("UNSUBSCRIBE")
-            .query_async(con)
-            .and_then(|(con, ())| redis::cmd("PUNSUBSCRIBE").query_async(con))
-            .map(move |(con, ())| (con, item));
-        Box::new(fut)
+    fn sharded(con: Connection, callback: F) -> Self {
+        Self {
+            sharded: true,
+            ..Self::new(con, callback)
+        }
+    }
+
+    fn with_control(con: Connection, callback: F, ctrl: Control) -> Self {
+        Self {
+            ctrl: Some(ctrl),
+            ..Self::new(con, callback)
+        }
+    }
+
+    // Whether the message is to the private channel of the handle.
+    fn is_wake(&self, msg: &Msg) -> bool {
+        match (&self.ctrl, &msg.channel) {
+            (Some(ctrl), Value::Data(ch)) => ch == ctrl.wake.as_bytes(),
+            _ => false,
+        }
+    }
+
+    // Sends the commands queued by the handle, or waits for a next message if none.
+    fn flush(&mut self, con: Connection) {
+        let mut packed = Vec::new();
+        if let Some(ctrl) = self.ctrl.as_mut() {
+            while let Ok(Async::Ready(Some(cmd))) = ctrl.rx.poll() {
+                packed.extend(cmd);
+            }
+        }
+
+        if packed.is_empty() {
+            self.recv = Some(Box::new(con.read_response()));
+        } else {
+            // The replies are read as messages, which are skipped.
+            self.send = Some(con.req_packed_commands(packed, 0, 0));
+        }
+    }
+
+    fn clear_active_subscriptions(
+        &self,
+        con: Connection,
+        item: Result<U, E>,
+    ) -> RedisFuture<(Connection, Result<U, E>)> {
+        let unsubscribe = if self.sharded {
+            unsubscribe_sharded(con)
+        } else {
+            unsubscribe_all(con)
+        };
+        Box::new(unsubscribe.map(move |con| (con, item)))
     }
 }
 
@@ -276,18 +731,27 @@ where
     F: FnMut(Msg) -> R,
     R: IntoFuture<Item = ControlFlow<U>, Error = E>,
     U: Send + 'static,
+    E: Send + 'static,
 {
     type Item = (Connection, Result<U, E>);
     type Error = RedisError;
 
     fn poll(&mut self) -> Poll<(Connection, Result<U, E>), RedisError> {
-        assert!(self.recv.is_some() || self.proc.is_some() || self.fin.is_some());
+        assert!(
+            self.recv.is_some() || self.proc.is_some() || self.fin.is_some() || self.send.is_some()
+        );
 
         loop {
-            if self.fin.is_some() {
+            if let Some(fin) = self.fin.as_mut() {
                 // Unsubscribing from the pub-sub channel.
-                let (con, value) = try_ready!(self.fin.as_mut().unwrap().poll());
-                return Ok(Async::Ready((con, Ok(value))));
+                return fin.poll();
+            }
+
+            if let Some(send) = self.send.as_mut() {
+                // Sending commands from the handle.
+                let (con, _) = try_ready!(send.poll());
+                self.send = None;
+                self.recv = Some(Box::new(con.read_response()));
             }
 
             if self.recv.is_some() {
@@ -297,8 +761,13 @@ where
                 self.recv.take();
 
                 let msg = match value_to_msg(value)? {
+                    Some(msg) if self.is_wake(&msg) => {
+                        self.flush(con);
+                        continue;
+                    }
                     Some(msg) => msg,
                     None => {
+                        // Skip the replies to subscribe commands.
                         self.recv = Some(Box::new(con.read_response()));
                         continue;
                     }
@@ -321,13 +790,75 @@ where
                 let con = self.con.take().unwrap();
                 match ctrl {
                     Ok(ControlFlow::Break(item)) => {
-                        self.fin = Some(self.clear_active_subscriptions(con, item));
-                        continue;
+                        self.fin = Some(self.clear_active_subscriptions(con, Ok(item)));
                     }
                     Ok(ControlFlow::Continue) => {
                         self.recv = Some(Box::new(con.read_response()));
                     }
-                    Err(e) => return Ok(Async::Ready((con, Err(e)))),
+                    Err(e) => {
+                        self.fin = Some(self.clear_active_subscriptions(con, Err(e)));
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Whether the error means the connection is closed.
+//
+// `redis` reports the end of the input as a `ResponseError` without detail,
+// while errors from the server and parse errors carry their detail. Errors
+// compare equal by the kind and whether they have detail, not by the text.
+fn is_closed(e: &RedisError) -> bool {
+    let eof = RedisError::from((ErrorKind::ResponseError, "end of input"));
+    e.is_connection_dropped() || *e == eof
+}
+
+/// Stream over messages of pubsub commands.
+///
+/// This is created by [`subscribe_stream`][1] or [`psubscribe_stream`][2].
+/// It ends when the connection is closed, and fails with other errors.
+///
+/// [1]: ./trait.PubSubCommands.html#tymethod.subscribe_stream
+/// [2]: ./trait.PubSubCommands.html#tymethod.psubscribe_stream
+pub struct RedisPubSubStream {
+    // Unset once the stream ends.
+    recv: Option<RedisFuture<(Connection, Value)>>,
+}
+
+impl RedisPubSubStream {
+    fn new(con: Connection) -> Self {
+        Self {
+            recv: Some(Box::new(con.read_response())),
+        }
+    }
+}
+
+impl Stream for RedisPubSubStream {
+    type Item = Msg;
+    type Error = RedisError;
+
+    fn poll(&mut self) -> Poll<Option<Msg>, RedisError> {
+        loop {
+            let res = match self.recv.as_mut() {
+                Some(recv) => recv.poll(),
+                None => return Ok(Async::Ready(None)),
+            };
+
+            match res {
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Ok(Async::Ready((con, value))) => {
+                    self.recv = Some(Box::new(con.read_response()));
+                    if let Some(msg) = value_to_msg(value)? {
+                        return Ok(Async::Ready(Some(msg)));
+                    }
+                }
+                Err(e) => {
+                    self.recv = None;
+                    if is_closed(&e) {
+                        return Ok(Async::Ready(None));
+                    }
+                    return Err(e);
                 }
             }
         }
@@ -0,0 +1,289 @@
+use futures::{
+    future::{self, Either},
+    prelude::*,
+};
+use redis::{aio::ConnectionLike, cmd, Cmd, ErrorKind, FromRedisValue, RedisFuture, ToRedisArgs};
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::SystemTime,
+};
+
+/// Lua script which is cached on the server and called by its SHA1 digest.
+///
+/// The first call sends the whole script with `EVAL`, which also caches it on
+/// the server, and the later calls send only the digest with `EVALSHA`. If the
+/// script is gone from the cache, e.g. by `SCRIPT FLUSH` or a restart, `EVALSHA`
+/// fails with `NoScriptError`. As an error drops the connection, [`invoke`](#method.invoke)
+/// fails then, and the next call sends the script again. [`invoke_shared`](#method.invoke_shared)
+/// instead retries with `EVAL` right away on a clone of the connection, like
+/// `redis::Script`. Clones of a `Script` share the state.
+///
+/// ```rust,no_run
+/// use futures::prelude::*;
+/// use redis_ac::Script;
+///
+/// # fn main() {
+/// let client = redis::Client::open("redis://127.0.0.1").unwrap();
+/// let connect = client.get_async_connection();
+/// let script = Script::new("return redis.call('INCRBY', KEYS[1], ARGV[1])");
+///
+/// let f = connect.and_then(move |con| {
+///     script
+///         .invoke(con, &["counter"], 2)
+///         .map(|(_, n): (_, i64)| println!("{}", n))
+/// }).map_err(|e| eprintln!("{}", e));
+///
+/// tokio::run(f);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Script {
+    code: Arc<str>,
+    hash: Arc<str>,
+    loaded: Arc<AtomicBool>,
+}
+
+impl Script {
+    /// Creates a script from its code.
+    pub fn new(code: &str) -> Self {
+        Self {
+            code: code.into(),
+            hash: redis::Script::new(code).get_hash().into(),
+            loaded: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Returns the SHA1 digest of the script.
+    pub fn get_hash(&self) -> &str {
+        &self.hash
+    }
+
+    fn command<K: ToRedisArgs, A: ToRedisArgs>(&self, keys: &[K], args: A, load: bool) -> Cmd {
+        let mut c = if load {
+            let mut c = cmd("EVAL");
+            c.arg(&*self.code);
+            c
+        } else {
+            let mut c = cmd("EVALSHA");
+            c.arg(&*self.hash);
+            c
+        };
+        c.arg(keys.len()).arg(keys).arg(args);
+        c
+    }
+
+    fn query<C, RV>(&self, cmd: Cmd, con: C) -> RedisFuture<(C, RV)>
+    where
+        C: ConnectionLike + Send + 'static,
+        RV: FromRedisValue + Send + 'static,
+    {
+        let loaded = self.loaded.clone();
+        Box::new(cmd.query_async(con).then(move |res| {
+            match res {
+                Ok(_) => loaded.store(true, Ordering::Relaxed),
+                Err(ref e) if e.kind() == ErrorKind::NoScriptError => {
+                    loaded.store(false, Ordering::Relaxed)
+                }
+                Err(_) => {}
+            }
+            res
+        }))
+    }
+
+    /// Calls the script with the keys and the arguments.
+    pub fn invoke<C, K, A, RV>(&self, con: C, keys: &[K], args: A) -> RedisFuture<(C, RV)>
+    where
+        C: ConnectionLike + Send + 'static,
+        K: ToRedisArgs,
+        A: ToRedisArgs,
+        RV: FromRedisValue + Send + 'static,
+    {
+        let load = !self.loaded.load(Ordering::Relaxed);
+        self.query(self.command(keys, args, load), con)
+    }
+
+    /// Calls the script like [`invoke`](#method.invoke), but retries with `EVAL`
+    /// on `NoScriptError` using a clone of the connection.
+    pub fn invoke_shared<C, K, A, RV>(&self, con: C, keys: &[K], args: A) -> RedisFuture<(C, RV)>
+    where
+        C: ConnectionLike + Clone + Send + 'static,
+        K: ToRedisArgs,
+        A: ToRedisArgs,
+        RV: FromRedisValue + Send + 'static,
+    {
+        let args = args.to_redis_args();
+        let retry = self.clone();
+        let eval = self.command(keys, &args[..], true);
+
+        Box::new(self.invoke(con.clone(), keys, &args[..]).or_else(move |e| {
+            if e.kind() == ErrorKind::NoScriptError {
+                Either::A(retry.query(eval, con))
+            } else {
+                Either::B(future::err(e))
+            }
+        }))
+    }
+}
+
+/// Creates an `EVAL` command for the script, to which keys and arguments are appended.
+pub(crate) fn eval(script: &str, numkeys: usize) -> Cmd {
+    let mut c = cmd("EVAL");
+    c.arg(script).arg(numkeys);
+    c
+}
+
+/// Returns a random number in `[0, 1)` to be passed to scripts.
+///
+/// This doesn't rely on `math.random` of scripts, which may return the same
+/// sequence for every script call depending on the version of Redis.
+pub(crate) fn random_fraction() -> f64 {
+    let mut h = RandomState::new().build_hasher();
+    if let Ok(d) = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+        h.write_u128(d.as_nanos());
+    }
+    (h.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+// KEYS[1]: hash, ARGV[1]: HINCRBY or HINCRBYFLOAT, ARGV[2]: field, ARGV[3]: delta
+pub(crate) const HINCR_AND_GETALL: &str = r"
+redis.call(ARGV[1], KEYS[1], ARGV[2], ARGV[3])
+return redis.call('HGETALL', KEYS[1])
+";
+
+// KEYS[1]: key, ARGV[1]: value, ARGV[2]: max bytes
+pub(crate) const APPEND_CAPPED: &str = r"
+local len = redis.call('APPEND', KEYS[1], ARGV[1])
+local max = tonumber(ARGV[2])
+if len <= max then
+    return len
+end
+local tail = redis.call('GETRANGE', KEYS[1], len - max, -1)
+local ttl = redis.call('PTTL', KEYS[1])
+redis.call('SET', KEYS[1], tail)
+if ttl > 0 then
+    redis.call('PEXPIRE', KEYS[1], ttl)
+end
+return string.len(tail)
+";
+
+// KEYS[1]: sorted set, ARGV[1]: member, ARGV[2]: delta, ARGV[3]: max size
+pub(crate) const ZINCR_CAPPED: &str = r"
+local score = redis.call('ZINCRBY', KEYS[1], ARGV[2], ARGV[1])
+local size = redis.call('ZCARD', KEYS[1])
+local max = tonumber(ARGV[3])
+if size > max then
+    redis.call('ZREMRANGEBYRANK', KEYS[1], 0, size - max - 1)
+end
+return score
+";
+
+// KEYS[1]: sorted set, ARGV[1]: member, ARGV[2]: score
+pub(crate) const ZADD_GT_RANK: &str = r"
+redis.call('ZADD', KEYS[1], 'XX', 'GT', ARGV[2], ARGV[1])
+return redis.call('ZREVRANK', KEYS[1], ARGV[1])
+";
+
+// KEYS[1]: sequence, KEYS[2]: sorted set, ARGV[1]: member
+pub(crate) const ZADD_NEXT: &str = r"
+local seq = redis.call('INCR', KEYS[1])
+redis.call('ZADD', KEYS[2], seq, ARGV[1])
+return seq
+";
+
+// KEYS[1]: hash, ARGV[1]: value
+pub(crate) const SET_TIMESTAMPED: &str = r"
+-- Replicate the effects instead of the script calling `TIME` (no-op since Redis 5).
+redis.replicate_commands()
+local t = redis.call('TIME')
+local at = tonumber(t[1]) * 1000000 + tonumber(t[2])
+redis.call('DEL', KEYS[1])
+redis.call('HSET', KEYS[1], 'value', ARGV[1], 'generated_at', string.format('%d', at))
+return redis.status_reply('OK')
+";
+
+// KEYS[1]: hash, ARGV[1]: rate per second, ARGV[2]: capacity, ARGV[3]: tokens to take
+pub(crate) const TOKEN_BUCKET: &str = r"
+-- Replicate the effects instead of the script calling `TIME` (no-op since Redis 5).
+redis.replicate_commands()
+local rate = tonumber(ARGV[1])
+local capacity = tonumber(ARGV[2])
+local take = tonumber(ARGV[3])
+local t = redis.call('TIME')
+local now = tonumber(t[1]) + tonumber(t[2]) / 1000000
+local state = redis.call('HMGET', KEYS[1], 'tokens', 'refilled_at')
+local tokens = tonumber(state[1]) or capacity
+local refilled_at = tonumber(state[2]) or now
+tokens = math.min(capacity, tokens + math.max(0, now - refilled_at) * rate)
+local ok = tokens >= take
+if ok then
+    tokens = tokens - take
+end
+redis.call('HSET', KEYS[1], 'tokens', string.format('%.6f', tokens), 'refilled_at', string.format('%.6f', now))
+-- Forget the bucket once it's full again.
+if rate > 0 then
+    redis.call('PEXPIRE', KEYS[1], math.ceil((capacity - tokens) / rate * 1000) + 1000)
+end
+if ok then
+    return 1
+end
+return 0
+";
+
+// KEYS[1]: sorted set, ARGV[1]: random number in [0, 1)
+pub(crate) const ZRANDMEMBER_WEIGHTED: &str = r"
+local items = redis.call('ZRANGE', KEYS[1], 0, -1, 'WITHSCORES')
+local total = 0
+for i = 2, #items, 2 do
+    local w = tonumber(items[i])
+    if w > 0 then
+        total = total + w
+    end
+end
+if total <= 0 then
+    return false
+end
+local r = tonumber(ARGV[1]) * total
+local picked = false
+for i = 2, #items, 2 do
+    local w = tonumber(items[i])
+    if w > 0 then
+        picked = items[i - 1]
+        r = r - w
+        if r < 0 then
+            break
+        end
+    end
+end
+return picked
+";
+
+// KEYS[1]: key, ARGV[1]: value, ARGV[2..3]: IDLETIME or FREQ with its value
+#[cfg(feature = "debug-commands")]
+pub(crate) const SET_WITH_META: &str = r"
+redis.call('SET', KEYS[1], ARGV[1])
+local payload = redis.call('DUMP', KEYS[1])
+return redis.call('RESTORE', KEYS[1], 0, payload, 'REPLACE', ARGV[2], ARGV[3])
+";
+
+// KEYS[1]: leader key, ARGV[1]: ID of the leader, ARGV[2]: TTL in milliseconds
+pub(crate) const RENEW_LEADER: &str = r"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('PEXPIRE', KEYS[1], ARGV[2])
+end
+return 0
+";
+
+// KEYS[1]: leader key, ARGV[1]: ID of the leader, ARGV[2]: channel
+pub(crate) const RELEASE_LEADER: &str = r"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    redis.call('DEL', KEYS[1])
+    redis.call('PUBLISH', ARGV[2], '')
+    return 1
+end
+return 0
+";
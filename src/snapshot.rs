@@ -0,0 +1,58 @@
+use std::io::{self, Read, Write};
+
+/// A key dumped by `DUMP` with its `PTTL`.
+///
+/// A record is written as the following big-endian fields:
+///
+/// - `u32` length of the key and the key
+/// - `i64` TTL in milliseconds, or `0` for no expiration
+/// - `u32` length of the payload and the payload
+pub(crate) struct Record {
+    pub key: Vec<u8>,
+    pub ttl: i64,
+    pub payload: Vec<u8>,
+}
+
+fn write_bytes<W: Write>(w: &mut W, bytes: &[u8]) -> io::Result<()> {
+    w.write_all(&(bytes.len() as u32).to_be_bytes())?;
+    w.write_all(bytes)
+}
+
+fn read_bytes<R: Read>(r: &mut R, len: [u8; 4]) -> io::Result<Vec<u8>> {
+    let mut bytes = vec![0; u32::from_be_bytes(len) as usize];
+    r.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+impl Record {
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_bytes(w, &self.key)?;
+        w.write_all(&self.ttl.to_be_bytes())?;
+        write_bytes(w, &self.payload)
+    }
+
+    /// Reads a record, or returns `None` at the end of the input.
+    pub fn read<R: Read>(r: &mut R) -> io::Result<Option<Self>> {
+        let mut len = [0; 4];
+        let mut n = 0;
+        while n < len.len() {
+            match r.read(&mut len[n..]) {
+                Ok(0) if n == 0 => return Ok(None),
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(m) => n += m,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        let key = read_bytes(r, len)?;
+
+        let mut ttl = [0; 8];
+        r.read_exact(&mut ttl)?;
+        let ttl = i64::from_be_bytes(ttl);
+
+        r.read_exact(&mut len)?;
+        let payload = read_bytes(r, len)?;
+
+        Ok(Some(Self { key, ttl, payload }))
+    }
+}
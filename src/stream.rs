@@ -1,14 +1,31 @@
-use futures::{prelude::*, try_ready};
-use redis::{aio::ConnectionLike, Cmd, FromRedisValue, RedisError, RedisFuture};
-use std::collections::VecDeque;
+use futures::{future::Either, prelude::*, sync::oneshot, try_ready};
+use redis::{
+    aio::{ConnectionLike, SharedConnection},
+    Cmd, ErrorKind, FromRedisValue, RedisError, RedisFuture,
+};
+use std::{collections::VecDeque, sync::Arc};
+
+type ScanQuery<C, RV> = RedisFuture<(C, (u64, Vec<RV>))>;
+type ScanQueryFactory<C, RV> = Box<dyn Fn(C, u64) -> ScanQuery<C, RV> + Send>;
+type ScanItem<C, RV> = (Option<C>, Option<RV>);
 
 /// Stream over items of scan commands.
+///
+/// The next scan command is issued as soon as the reply of the previous one
+/// arrives, before its items are yielded, so the query overlaps the consumption
+/// of the items. Only one query is in flight at a time, as the cursor of the
+/// next query is only known from the reply of the previous one.
 pub struct RedisScanStream<C, RV> {
     cursor: u64,
     con: Option<C>,
-    factory: Box<dyn Fn(u64) -> Cmd + Send>,
-    pending: Option<RedisFuture<(C, (u64, Vec<RV>))>>,
+    query: ScanQueryFactory<C, RV>,
+    pending: Option<ScanQuery<C, RV>>,
     queue: VecDeque<RV>,
+    // The cursor of the query and the number of items left in `queue`
+    // for each batch.
+    batches: VecDeque<(u64, usize)>,
+    cancel: Option<oneshot::Receiver<()>>,
+    canceled: bool,
 }
 
 pub fn stream<F, C, RV>(con: C, factory: F) -> RedisScanStream<C, RV>
@@ -17,7 +34,124 @@ where
     RV: FromRedisValue + Send + 'static,
     F: Fn(u64) -> Cmd + Send + 'static,
 {
-    RedisScanStream::new(con, factory)
+    RedisScanStream::new(con, factory, 0)
+}
+
+/// Creates a stream which starts scanning from the given cursor instead of `0`.
+///
+/// This resumes a scan from a cursor saved with
+/// [`RedisScanStream::cursor`](./struct.RedisScanStream.html#method.cursor).
+/// `factory` creates the scan command for a cursor like the closures of
+/// [`Commands`](./trait.Commands.html), and needs to create the same command
+/// as the interrupted scan. Note that a cursor is only meaningful against the
+/// same logical dataset; resuming on another server or database, or after
+/// `FLUSHDB`, returns arbitrary items.
+///
+/// ```rust,no_run
+/// use futures::prelude::*;
+/// use redis_ac::stream_from;
+///
+/// # fn main() {
+/// let client = redis::Client::open("redis://127.0.0.1").unwrap();
+/// let connect = client.get_async_connection();
+/// let saved = 1234;
+///
+/// let f = connect.and_then(move |con| {
+///     stream_from(con, |cur| {
+///         let mut c = redis::cmd("SCAN");
+///         c.arg(cur).arg("MATCH").arg("key*");
+///         c
+///     }, saved)
+///     .filter_map(|(_, item)| item)
+///     .for_each(|item: String| {
+///         println!("{}", item);
+///         Ok(())
+///     })
+/// }).map_err(|e| eprintln!("{}", e));
+///
+/// tokio::run(f);
+/// # }
+/// ```
+pub fn stream_from<F, C, RV>(con: C, factory: F, cursor: u64) -> RedisScanStream<C, RV>
+where
+    C: ConnectionLike + Send + 'static,
+    RV: FromRedisValue + Send + 'static,
+    F: Fn(u64) -> Cmd + Send + 'static,
+{
+    RedisScanStream::new(con, factory, cursor)
+}
+
+/// Creates a stream which scans with a clone of the shared connection, and yields
+/// only the items.
+///
+/// As a [`SharedConnection`][1] is cheaply cloneable and can send other commands
+/// while scanning, the connection doesn't need to be given back by the stream.
+/// See [`RedisScanStream::items`](./struct.RedisScanStream.html#method.items)
+/// to do the same with the scan methods of [`Commands`](./trait.Commands.html).
+///
+/// [1]: https://docs.rs/redis/0.13.0/redis/aio/struct.SharedConnection.html
+///
+/// ```rust,no_run
+/// use futures::prelude::*;
+/// use redis_ac::stream_shared;
+///
+/// # fn main() {
+/// let client = redis::Client::open("redis://127.0.0.1").unwrap();
+///
+/// let f = client.get_shared_async_connection().and_then(|con| {
+///     stream_shared(&con, |cur| {
+///         let mut c = redis::cmd("SCAN");
+///         c.arg(cur).arg("MATCH").arg("key*");
+///         c
+///     })
+///     .for_each(|item: String| {
+///         println!("{}", item);
+///         Ok(())
+///     })
+/// }).map_err(|e| eprintln!("{}", e));
+///
+/// tokio::run(f);
+/// # }
+/// ```
+pub fn stream_shared<F, RV>(
+    con: &SharedConnection,
+    factory: F,
+) -> RedisScanItems<SharedConnection, RV>
+where
+    RV: FromRedisValue + Send + 'static,
+    F: Fn(u64) -> Cmd + Send + 'static,
+{
+    RedisScanStream::new(con.clone(), factory, 0).items()
+}
+
+/// Creates a stream which runs `then` on every batch of items returned by the scan
+/// command, and yields the results of `then` instead of the scanned items.
+///
+/// `then` is called with the same connection so that it can pipeline commands
+/// for the batch. It isn't called for empty batches.
+pub fn stream_then<F, G, C, K, RV>(con: C, factory: F, then: G) -> RedisScanStream<C, RV>
+where
+    C: ConnectionLike + Send + 'static,
+    K: FromRedisValue + Send + 'static,
+    RV: Send + 'static,
+    F: Fn(u64) -> Cmd + Send + 'static,
+    G: Fn(C, Vec<K>) -> RedisFuture<(C, Vec<RV>)> + Send + Sync + 'static,
+{
+    let then = Arc::new(then);
+
+    RedisScanStream::with_query(con, 0, move |con, cur| {
+        let then = then.clone();
+
+        Box::new(factory(cur).query_async(con).and_then(
+            move |(con, (cursor, keys)): (C, (u64, Vec<K>))| {
+                if keys.is_empty() {
+                    Either::A(Ok((con, (cursor, Vec::new()))).into_future())
+                } else {
+                    Either::B(then(con, keys).map(move |(con, rvs)| (con, (cursor, rvs))))
+                }
+            },
+        ))
+    })
 }
 
 impl<C, RV> RedisScanStream<C, RV>
@@ -25,35 +159,122 @@ where
     C: ConnectionLike + Send + 'static,
     RV: FromRedisValue + Send + 'static,
 {
-    pub(crate) fn new<F: Fn(u64) -> Cmd + Send + 'static>(con: C, factory: F) -> Self {
+    pub(crate) fn new<F: Fn(u64) -> Cmd + Send + 'static>(con: C, factory: F, cursor: u64) -> Self {
+        Self::with_query(con, cursor, move |con, cur| factory(cur).query_async(con))
+    }
+}
+
+impl<C, RV> RedisScanStream<C, RV>
+where
+    C: ConnectionLike + Send + 'static,
+    RV: Send + 'static,
+{
+    fn with_query<Q>(con: C, cursor: u64, query: Q) -> Self
+    where
+        Q: Fn(C, u64) -> ScanQuery<C, RV> + Send + 'static,
+    {
         // Create initial query
-        let pending = factory(0).query_async(con);
+        let pending = query(con, cursor);
 
         Self {
-            cursor: 0,
+            cursor,
             con: None,
-            factory: Box::new(factory),
+            query: Box::new(query),
             pending: Some(pending),
             queue: VecDeque::new(),
+            batches: VecDeque::new(),
+            cancel: None,
+            canceled: false,
+        }
+    }
+
+    /// Stops scanning when a value is sent to the given receiver.
+    ///
+    /// Once canceled, the stream doesn't issue further queries nor return the
+    /// remaining items. It waits for the reply of the query in flight, and then
+    /// finishes with `(Some(con), None)` so that the connection can be reused.
+    /// Dropping the sender doesn't cancel the stream.
+    ///
+    /// ```rust,no_run
+    /// use futures::{prelude::*, sync::oneshot};
+    /// use redis_ac::Commands;
+    ///
+    /// # fn main() {
+    /// let client = redis::Client::open("redis://127.0.0.1").unwrap();
+    /// let connect = client.get_async_connection();
+    /// let (tx, rx) = oneshot::channel();
+    ///
+    /// let f = connect.and_then(|con| {
+    ///     con.scan_match("key*")
+    ///         .with_cancel(rx)
+    ///         .filter_map(|(_, item)| item)
+    ///         .for_each(|item: String| {
+    ///             println!("{}", item);
+    ///             Ok(())
+    ///         })
+    /// }).map_err(|e| eprintln!("{}", e));
+    ///
+    /// // Stop scanning from somewhere else.
+    /// tx.send(()).unwrap();
+    ///
+    /// tokio::run(f);
+    /// # }
+    /// ```
+    pub fn with_cancel(mut self, signal: oneshot::Receiver<()>) -> Self {
+        self.cancel = Some(signal);
+        self
+    }
+
+    /// Returns the cursor to resume scanning from with [`stream_from`](./fn.stream_from.html).
+    ///
+    /// Resuming from the cursor returns all the items not yet returned by this
+    /// stream. Some of the returned items may be returned again, as the cursor
+    /// points to the start of the batch which the next item belongs to. This is
+    /// `0` once all the items are returned, which restarts the scan from the beginning.
+    pub fn cursor(&self) -> u64 {
+        self.batches
+            .front()
+            .map(|&(cursor, _)| cursor)
+            .unwrap_or(self.cursor)
+    }
+
+    fn poll_cancel(&mut self) -> bool {
+        match self.cancel.as_mut().map(|c| c.poll()) {
+            Some(Ok(Async::Ready(()))) => true,
+            Some(Err(_)) => {
+                // The sender is gone without canceling.
+                self.cancel = None;
+                false
+            }
+            _ => false,
         }
     }
 
     // This function actually never return Ok(Async::Ready(Some(_)))
-    fn poll_query(&mut self) -> Poll<Option<(Option<C>, Option<RV>)>, RedisError> {
+    fn poll_query(&mut self) -> Poll<Option<ScanItem<C, RV>>, RedisError> {
         loop {
             // Try polling
             let p = self.pending.as_mut().map(|p| p.poll());
 
             if let Some(p) = p {
                 let (con, (cursor, rvs)) = try_ready!(p);
-                self.cursor = cursor;
-                self.queue.extend(rvs);
+                let queried = std::mem::replace(&mut self.cursor, cursor);
                 self.con = Some(con);
 
+                if self.canceled {
+                    // Drop the reply which was in flight on cancel.
+                    self.pending = None;
+                    continue;
+                }
+
+                if !rvs.is_empty() {
+                    self.batches.push_back((queried, rvs.len()));
+                }
+                self.queue.extend(rvs);
+
                 if self.cursor != 0 {
                     // Query again
-                    self.pending =
-                        Some((self.factory)(self.cursor).query_async(self.con.take().unwrap()));
+                    self.pending = Some((self.query)(self.con.take().unwrap(), self.cursor));
                 } else {
                     self.pending = None;
                 }
@@ -89,20 +310,114 @@ where
     pub fn all(self) -> RedisScanAll<C, RV> {
         RedisScanAll::new(self)
     }
+
+    /// Yields only the items, dropping the connection at the end.
+    ///
+    /// This fits connections which don't need to be given back such as a clone of
+    /// [`SharedConnection`](https://docs.rs/redis/0.13.0/redis/aio/struct.SharedConnection.html).
+    pub fn items(self) -> RedisScanItems<C, RV> {
+        RedisScanItems {
+            inner: self,
+            done: None,
+        }
+    }
+
+    /// Yields only the items, and gives back the connection through the returned
+    /// future once all the items are yielded.
+    ///
+    /// The future fails with `IoError` if the stream fails or is dropped before
+    /// the end, in which case the connection is lost.
+    ///
+    /// ```rust,no_run
+    /// use futures::prelude::*;
+    /// use redis_ac::Commands;
+    ///
+    /// # fn main() {
+    /// let client = redis::Client::open("redis://127.0.0.1").unwrap();
+    /// let connect = client.get_async_connection();
+    ///
+    /// let f = connect.and_then(|con| {
+    ///     let (items, con) = con.scan_match("key*").items_with_connection();
+    ///     items
+    ///         .for_each(|item: String| {
+    ///             println!("{}", item);
+    ///             Ok(())
+    ///         })
+    ///         .and_then(|()| con)
+    ///         .and_then(|con| redis::cmd("PING").query_async(con))
+    ///         .map(|(_, pong): (_, String)| println!("{}", pong))
+    /// }).map_err(|e| eprintln!("{}", e));
+    ///
+    /// tokio::run(f);
+    /// # }
+    /// ```
+    pub fn items_with_connection(self) -> (RedisScanItems<C, RV>, RedisFuture<C>) {
+        let (tx, rx) = oneshot::channel();
+        let items = RedisScanItems {
+            inner: self,
+            done: Some(tx),
+        };
+        let con = rx.map_err(|_| {
+            RedisError::from((ErrorKind::IoError, "Scan didn't finish with the connection"))
+        });
+        (items, Box::new(con))
+    }
+
+    /// Counts the results of scanning without collecting them.
+    pub fn count(self) -> RedisFuture<(C, usize)> {
+        self.fold_items(0, |n, _| Ok(n + 1))
+    }
+
+    /// Folds the results of scanning into a value, and gives back the connection with it.
+    ///
+    /// The future fails with the first error of scanning or of `f`.
+    pub fn fold_items<T, F>(self, init: T, mut f: F) -> RedisFuture<(C, T)>
+    where
+        T: Send + 'static,
+        F: FnMut(T, RV) -> Result<T, RedisError> + Send + 'static,
+    {
+        Box::new(
+            self.fold((None, init), move |(_, acc), (con, item)| {
+                let acc = match item {
+                    Some(item) => f(acc, item)?,
+                    None => acc,
+                };
+                Ok::<_, RedisError>((con, acc))
+            })
+            .map(|(con, acc)| {
+                // RedisScanStream guarantees it returns `Some(con)` with last item.
+                (con.expect("RedisScanStream didn't return connection"), acc)
+            }),
+        )
+    }
 }
 
 impl<C, RV> Stream for RedisScanStream<C, RV>
 where
     C: ConnectionLike + Send + 'static,
-    RV: FromRedisValue + Send + 'static,
+    RV: Send + 'static,
 {
     type Item = (Option<C>, Option<RV>);
     type Error = RedisError;
 
     fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        if !self.canceled && self.poll_cancel() {
+            self.canceled = true;
+            self.cancel = None;
+            self.queue.clear();
+            self.batches.clear();
+        }
+
         let ready = self.poll_query();
 
         if let Some(item) = self.queue.pop_front() {
+            if let Some(batch) = self.batches.front_mut() {
+                batch.1 -= 1;
+                if batch.1 == 0 {
+                    self.batches.pop_front();
+                }
+            }
+
             let con = if self.queue.is_empty() {
                 // `self.con` becomes `Some(con)` only after all the query is done.
                 self.con.take()
@@ -123,6 +438,53 @@ where
     }
 }
 
+/// Stream over only the items of scan commands, created by
+/// [`RedisScanStream::items`](./struct.RedisScanStream.html#method.items) and
+/// [`RedisScanStream::items_with_connection`](./struct.RedisScanStream.html#method.items_with_connection).
+pub struct RedisScanItems<C, RV> {
+    inner: RedisScanStream<C, RV>,
+    done: Option<oneshot::Sender<C>>,
+}
+
+impl<C, RV> RedisScanItems<C, RV>
+where
+    C: ConnectionLike + Send + 'static,
+    RV: Send + 'static,
+{
+    /// Returns the cursor to resume scanning from, as
+    /// [`RedisScanStream::cursor`](./struct.RedisScanStream.html#method.cursor).
+    pub fn cursor(&self) -> u64 {
+        self.inner.cursor()
+    }
+}
+
+impl<C, RV> Stream for RedisScanItems<C, RV>
+where
+    C: ConnectionLike + Send + 'static,
+    RV: Send + 'static,
+{
+    type Item = RV;
+    type Error = RedisError;
+
+    fn poll(&mut self) -> Poll<Option<RV>, RedisError> {
+        loop {
+            let (con, item) = match try_ready!(self.inner.poll()) {
+                Some(v) => v,
+                None => return Ok(Async::Ready(None)),
+            };
+            if let Some(con) = con {
+                if let Some(done) = self.done.take() {
+                    let _ = done.send(con);
+                }
+            }
+            if let Some(item) = item {
+                return Ok(Async::Ready(Some(item)));
+            }
+            // Only the connection at the end.
+        }
+    }
+}
+
 /// Collects all the results from a scan command.
 pub struct RedisScanAll<C, RV> {
     items: Vec<RV>,
@@ -138,10 +500,27 @@ impl<C, RV> RedisScanAll<C, RV> {
     }
 }
 
+impl<C, K, V> RedisScanAll<C, (K, V)>
+where
+    C: ConnectionLike + Send + 'static,
+    K: Send + 'static,
+    V: Send + 'static,
+{
+    /// Collects only the first elements of the pairs, such as the fields of `hscan`.
+    pub fn keys_only(self) -> RedisFuture<(C, Vec<K>)> {
+        Box::new(self.map(|(con, items)| (con, items.into_iter().map(|(k, _)| k).collect())))
+    }
+
+    /// Collects only the second elements of the pairs, such as the values of `hscan`.
+    pub fn values_only(self) -> RedisFuture<(C, Vec<V>)> {
+        Box::new(self.map(|(con, items)| (con, items.into_iter().map(|(_, v)| v).collect())))
+    }
+}
+
 impl<C, RV> Future for RedisScanAll<C, RV>
 where
     C: ConnectionLike + Send + 'static,
-    RV: FromRedisValue + Send + 'static,
+    RV: Send + 'static,
 {
     type Item = (C, Vec<RV>);
     type Error = RedisError;
@@ -163,3 +542,86 @@ where
         }
     }
 }
+
+// Polls a futures 0.1 state machine from a futures 0.3 context, waking the task
+// of the context when the 0.1 task is notified.
+#[cfg(feature = "futures03")]
+fn poll_03<T, F>(cx: &mut std::task::Context<'_>, f: F) -> std::task::Poll<Result<T, RedisError>>
+where
+    F: FnOnce() -> Poll<T, RedisError>,
+{
+    struct WakerNotify(std::task::Waker);
+
+    impl futures::executor::Notify for WakerNotify {
+        fn notify(&self, _: usize) {
+            self.0.wake_by_ref();
+        }
+    }
+
+    let notify = Arc::new(WakerNotify(cx.waker().clone()));
+    match futures::executor::with_notify(&notify, 0, f) {
+        Ok(Async::Ready(v)) => std::task::Poll::Ready(Ok(v)),
+        Ok(Async::NotReady) => std::task::Poll::Pending,
+        Err(e) => std::task::Poll::Ready(Err(e)),
+    }
+}
+
+/// The same stream as the futures 0.1 `Stream`, for `async`/`await`.
+///
+/// This requires the `futures03` feature, which is enabled by `compat` as well.
+#[cfg(feature = "futures03")]
+impl<C, RV> futures03::Stream for RedisScanStream<C, RV>
+where
+    C: ConnectionLike + Send + Unpin + 'static,
+    RV: Send + Unpin + 'static,
+{
+    type Item = Result<ScanItem<C, RV>, RedisError>;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        poll_03(cx, || Stream::poll(this)).map(Result::transpose)
+    }
+}
+
+/// The same future as the futures 0.1 `Future`, for `async`/`await`.
+///
+/// This requires the `futures03` feature, which is enabled by `compat` as well.
+#[cfg(feature = "futures03")]
+impl<C, RV> futures03::Future for RedisScanAll<C, RV>
+where
+    C: ConnectionLike + Send + Unpin + 'static,
+    RV: Send + Unpin + 'static,
+{
+    type Output = Result<(C, Vec<RV>), RedisError>;
+
+    fn poll(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        let this = self.get_mut();
+        poll_03(cx, || Future::poll(this))
+    }
+}
+
+/// The same stream as the futures 0.1 `Stream`, for `async`/`await`.
+///
+/// This requires the `futures03` feature, which is enabled by `compat` as well.
+#[cfg(feature = "futures03")]
+impl<C, RV> futures03::Stream for RedisScanItems<C, RV>
+where
+    C: ConnectionLike + Send + Unpin + 'static,
+    RV: Send + Unpin + 'static,
+{
+    type Item = Result<RV, RedisError>;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        poll_03(cx, || Stream::poll(this)).map(Result::transpose)
+    }
+}
@@ -0,0 +1,106 @@
+use futures::prelude::*;
+use redis::{
+    aio::ConnectionLike, cmd, Cmd, ErrorKind, FromRedisValue, RedisError, RedisFuture, Value,
+};
+
+/// Transaction which runs the queued commands atomically with `MULTI` and `EXEC`.
+///
+/// Commands are kept in the transaction until [`exec`](#method.exec), which
+/// sends `MULTI`, the commands and `EXEC` in a single request, and returns the
+/// results of the commands. If the transaction is aborted because a key watched
+/// with `WATCH` was modified, the future fails with `ExecAbortError`.
+/// [`discard`](#method.discard) drops the commands instead, and releases the
+/// watched keys.
+///
+/// ```rust,no_run
+/// use futures::{future, prelude::*};
+/// use redis_ac::{Commands, Transaction};
+///
+/// # fn main() {
+/// let client = redis::Client::open("redis://127.0.0.1").unwrap();
+/// let connect = client.get_async_connection();
+///
+/// let f = connect.and_then(|con| {
+///     con.watch("balance")
+///         .and_then(|(con, ()): (_, ())| con.get("balance"))
+///         .and_then(|(con, balance): (_, i64)| {
+///             if balance < 100 {
+///                 return future::Either::A(Transaction::new().discard(con).map(|_| ()));
+///             }
+///             future::Either::B(
+///                 Transaction::new()
+///                     .add_command(redis::cmd("DECRBY").arg("balance").arg(100).clone())
+///                     .add_command(redis::cmd("INCRBY").arg("spent").arg(100).clone())
+///                     .exec(con)
+///                     .map(|(_, results)| println!("{:?}", results)),
+///             )
+///         })
+/// }).map_err(|e| eprintln!("{}", e));
+///
+/// tokio::run(f);
+/// # }
+/// ```
+#[derive(Default)]
+pub struct Transaction {
+    pipe: redis::Pipeline,
+    len: usize,
+}
+
+impl Transaction {
+    /// Creates an empty transaction.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues a command.
+    pub fn add_command(mut self, cmd: Cmd) -> Self {
+        self.pipe.add_command(cmd);
+        self.len += 1;
+        self
+    }
+
+    /// Returns the number of queued commands.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no command is queued.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Runs the queued commands atomically, and returns their results.
+    ///
+    /// An empty transaction returns an empty result without querying.
+    pub fn exec<C>(self, con: C) -> RedisFuture<(C, Vec<Value>)>
+    where
+        C: ConnectionLike + Send + 'static,
+    {
+        let mut pipe = self.pipe;
+        pipe.atomic();
+
+        Box::new(
+            pipe.query_async(con)
+                .and_then(|(con, value): (C, Value)| match value {
+                    // `EXEC` replies nil if a watched key is modified.
+                    Value::Nil => Err(RedisError::from((
+                        ErrorKind::ExecAbortError,
+                        "Transaction aborted by a watched key",
+                    ))),
+                    value => Ok((con, Vec::from_redis_value(&value)?)),
+                }),
+        )
+    }
+
+    /// Drops the queued commands, and releases the keys watched with `WATCH` by `UNWATCH`.
+    pub fn discard<C>(self, con: C) -> RedisFuture<C>
+    where
+        C: ConnectionLike + Send + 'static,
+    {
+        Box::new(
+            cmd("UNWATCH")
+                .query_async(con)
+                .map(|(con, ()): (C, ())| con),
+        )
+    }
+}
@@ -0,0 +1,303 @@
+use futures::{future::Either, prelude::*};
+use redis::{
+    aio::ConnectionLike, from_redis_value, ErrorKind, FromRedisValue, RedisError, RedisFuture,
+    RedisResult, Value,
+};
+use std::collections::HashMap;
+
+/// The type of a key returned by [`type_of`][1].
+///
+/// [1]: ./trait.Commands.html#method.type_of
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RedisKeyType {
+    /// A string.
+    String,
+    /// A list.
+    List,
+    /// A set.
+    Set,
+    /// A sorted set.
+    ZSet,
+    /// A hash.
+    Hash,
+    /// A stream.
+    Stream,
+    /// The key doesn't exist.
+    None,
+    /// A type unknown to this crate such as the ones of modules.
+    /// This holds the name of the type.
+    Other(String),
+}
+
+impl FromRedisValue for RedisKeyType {
+    fn from_redis_value(v: &Value) -> RedisResult<Self> {
+        let ty: String = from_redis_value(v)?;
+        Ok(match ty.as_str() {
+            "string" => RedisKeyType::String,
+            "list" => RedisKeyType::List,
+            "set" => RedisKeyType::Set,
+            "zset" => RedisKeyType::ZSet,
+            "hash" => RedisKeyType::Hash,
+            "stream" => RedisKeyType::Stream,
+            "none" => RedisKeyType::None,
+            _ => RedisKeyType::Other(ty),
+        })
+    }
+}
+
+/// A replica connected to a master, reported by [`role`][1].
+///
+/// [1]: ./trait.Commands.html#method.role
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplicaInfo {
+    /// The IP address of the replica.
+    pub host: String,
+    /// The listening port of the replica.
+    pub port: u16,
+    /// The replication offset acknowledged by the replica.
+    pub offset: u64,
+}
+
+/// The replication role of a server returned by [`role`][1].
+///
+/// [1]: ./trait.Commands.html#method.role
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Role {
+    /// A master.
+    Master {
+        /// The replication offset of the master.
+        offset: u64,
+        /// The connected replicas.
+        replicas: Vec<ReplicaInfo>,
+    },
+    /// A replica.
+    Replica {
+        /// The IP address of the master.
+        master_host: String,
+        /// The port of the master.
+        master_port: u16,
+        /// The state of the replication such as `connected` or `sync`.
+        state: String,
+        /// The replication offset received from the master, or `-1` while connecting.
+        offset: i64,
+    },
+    /// A sentinel.
+    Sentinel {
+        /// The names of the masters monitored.
+        masters: Vec<String>,
+    },
+}
+
+impl FromRedisValue for Role {
+    fn from_redis_value(v: &Value) -> RedisResult<Self> {
+        let items = match v {
+            Value::Bulk(items) if !items.is_empty() => items,
+            _ => return Err(role_error(v)),
+        };
+        let role: String = from_redis_value(&items[0])?;
+
+        Ok(match (role.as_str(), &items[1..]) {
+            ("master", [offset, replicas]) => {
+                let replicas: Vec<Value> = from_redis_value(replicas)?;
+                Role::Master {
+                    offset: from_redis_value(offset)?,
+                    replicas: replicas
+                        .iter()
+                        .map(|r| {
+                            let (host, port, offset) = from_redis_value(r)?;
+                            Ok(ReplicaInfo { host, port, offset })
+                        })
+                        .collect::<RedisResult<_>>()?,
+                }
+            }
+            ("slave", [host, port, state, offset]) => Role::Replica {
+                master_host: from_redis_value(host)?,
+                master_port: from_redis_value(port)?,
+                state: from_redis_value(state)?,
+                offset: from_redis_value(offset)?,
+            },
+            ("sentinel", [masters]) => Role::Sentinel {
+                masters: from_redis_value(masters)?,
+            },
+            _ => return Err(role_error(v)),
+        })
+    }
+}
+
+fn role_error(v: &Value) -> RedisError {
+    RedisError::from((
+        ErrorKind::TypeError,
+        "Response was of incompatible type",
+        format!("Not a role (response was {:?})", v),
+    ))
+}
+
+/// An entry of a stream returned by [`xrange`][1].
+///
+/// [1]: ./trait.Commands.html#method.xrange
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamEntry {
+    /// The ID of the entry.
+    pub id: String,
+    /// The fields of the entry and their values.
+    pub fields: HashMap<String, Value>,
+}
+
+impl StreamEntry {
+    /// Returns the value of the field converted to `T`, or `None` if the field is missing.
+    pub fn get<T: FromRedisValue>(&self, field: &str) -> RedisResult<Option<T>> {
+        self.fields.get(field).map(from_redis_value).transpose()
+    }
+}
+
+impl FromRedisValue for StreamEntry {
+    fn from_redis_value(v: &Value) -> RedisResult<Self> {
+        match v {
+            Value::Bulk(items) if items.len() == 2 => Ok(Self {
+                id: from_redis_value(&items[0])?,
+                fields: from_redis_value(&items[1])?,
+            }),
+            _ => Err(RedisError::from((
+                ErrorKind::TypeError,
+                "Response was of incompatible type",
+                format!("Not a stream entry (response was {:?})", v),
+            ))),
+        }
+    }
+}
+
+/// The entries read from a stream by [`xread_block`][1].
+///
+/// [1]: ./trait.Commands.html#method.xread_block
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamKey {
+    /// The key of the stream.
+    pub key: String,
+    /// The entries in the order of IDs.
+    pub entries: Vec<StreamEntry>,
+}
+
+impl FromRedisValue for StreamKey {
+    fn from_redis_value(v: &Value) -> RedisResult<Self> {
+        match v {
+            Value::Bulk(items) if items.len() == 2 => Ok(Self {
+                key: from_redis_value(&items[0])?,
+                entries: from_redis_value(&items[1])?,
+            }),
+            _ => Err(RedisError::from((
+                ErrorKind::TypeError,
+                "Response was of incompatible type",
+                format!("Not a stream (response was {:?})", v),
+            ))),
+        }
+    }
+}
+
+/// A value of a key decoded according to its type.
+///
+/// This is returned by [`scan_typed`][1]. Elements are kept as raw bytes
+/// so that values which are not valid UTF-8 can be read as well.
+///
+/// [1]: ./trait.Commands.html#method.scan_typed
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypedValue {
+    /// A string.
+    String(Vec<u8>),
+    /// A list, from the head to the tail.
+    List(Vec<Vec<u8>>),
+    /// A set, in no particular order.
+    Set(Vec<Vec<u8>>),
+    /// A sorted set, as pairs of a member and its score in the ascending order of scores.
+    ZSet(Vec<(Vec<u8>, f64)>),
+    /// A hash, as pairs of a field and its value.
+    Hash(Vec<(Vec<u8>, Vec<u8>)>),
+    /// A value of the other types such as streams, which is not read.
+    /// This holds the name of the type.
+    Other(String),
+}
+
+impl TypedValue {
+    // Queues the command to read the value of the type, if it's readable.
+    fn read(pipe: &mut redis::Pipeline, key: &str, ty: &str) -> bool {
+        match ty {
+            "string" => pipe.cmd("GET").arg(key),
+            "list" => pipe.cmd("LRANGE").arg(key).arg(0).arg(-1),
+            "set" => pipe.cmd("SMEMBERS").arg(key),
+            "zset" => pipe.cmd("ZRANGE").arg(key).arg(0).arg(-1).arg("WITHSCORES"),
+            "hash" => pipe.cmd("HGETALL").arg(key),
+            _ => return false,
+        };
+        true
+    }
+
+    fn decode(ty: &str, v: &Value) -> RedisResult<Option<Self>> {
+        Ok(Some(match ty {
+            "string" => match from_redis_value(v)? {
+                Some(s) => TypedValue::String(s),
+                // Removed after `TYPE`.
+                None => return Ok(None),
+            },
+            "list" => TypedValue::List(from_redis_value(v)?),
+            "set" => TypedValue::Set(from_redis_value(v)?),
+            "zset" => TypedValue::ZSet(from_redis_value(v)?),
+            "hash" => TypedValue::Hash(from_redis_value(v)?),
+            ty => TypedValue::Other(ty.into()),
+        }))
+    }
+}
+
+/// Reads the values of the keys by pipelining `TYPE` and then the commands for the types.
+///
+/// Keys which don't exist are skipped.
+pub(crate) fn read_typed<C>(
+    con: C,
+    keys: Vec<String>,
+) -> RedisFuture<(C, Vec<(String, TypedValue)>)>
+where
+    C: ConnectionLike + Send + 'static,
+{
+    let mut p = redis::pipe();
+    for key in &keys {
+        p.cmd("TYPE").arg(key);
+    }
+
+    Box::new(
+        p.query_async(con)
+            .and_then(move |(con, types): (_, Vec<String>)| {
+                let mut p = redis::pipe();
+                let keys: Vec<_> = keys
+                    .into_iter()
+                    .zip(types)
+                    .filter(|(_, ty)| ty != "none")
+                    .map(|(key, ty)| {
+                        let read = TypedValue::read(&mut p, &key, &ty);
+                        (key, ty, read)
+                    })
+                    .collect();
+
+                let values = if keys.iter().any(|(_, _, read)| *read) {
+                    Either::A(p.query_async(con))
+                } else {
+                    Either::B(Ok((con, Vec::new())).into_future())
+                };
+
+                values.and_then(move |(con, values): (_, Vec<Value>)| {
+                    let mut values = values.into_iter();
+                    let mut typed = Vec::with_capacity(keys.len());
+
+                    for (key, ty, read) in keys {
+                        let v = if read {
+                            values.next().unwrap_or(Value::Nil)
+                        } else {
+                            Value::Nil
+                        };
+                        if let Some(v) = TypedValue::decode(&ty, &v)? {
+                            typed.push((key, v));
+                        }
+                    }
+
+                    Ok((con, typed))
+                })
+            }),
+    )
+}
@@ -1,5 +1,17 @@
-use futures::prelude::*;
-use redis_ac::Commands;
+use futures::{
+    future::{self, Loop},
+    prelude::*,
+};
+use redis_ac::{
+    stream_from, stream_shared, BitFieldOps, BitFieldOverflow, BitPosOptions, Commands,
+    CopyOptions, Direction, ExpireOption, FromRedisHash, GetExOptions, MinMax, RedisHash,
+    RedisKeyType, RestoreOptions, SetOptions, SizeMetric, SortOptions, TypedValue, ZRangeOptions,
+    ZaddOptions,
+};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::timer::Delay;
 
 mod helper;
 
@@ -21,6 +33,259 @@ fn setget() {
     });
 }
 
+#[test]
+fn sadd_srem_count() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.sadd_count("set", "old")
+                .and_then(|(con, n)| {
+                    assert_eq!(n, 1);
+                    // One new member and one existing member.
+                    con.sadd_count("set", &["new", "old"])
+                })
+                .and_then(|(con, n)| {
+                    assert_eq!(n, 1);
+                    con.srem_count("set", &["old", "missing"])
+                })
+                .map(|(_, n)| {
+                    assert_eq!(n, 1);
+                })
+        })
+    });
+}
+
+#[test]
+fn store_count() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.sadd_count("a", &["x", "y", "z"])
+                .and_then(|(con, _)| con.sadd_count("b", &["y", "z", "w"]))
+                .and_then(|(con, _)| con.sunionstore_count(&["union"][..], &["a", "b"][..]))
+                .and_then(|(con, n)| {
+                    assert_eq!(n, 4);
+                    con.sinterstore_count(&["inter"][..], &["a", "b"][..])
+                })
+                .and_then(|(con, n)| {
+                    assert_eq!(n, 2);
+                    con.sdiffstore_count(&["diff"][..], &["a", "b"][..])
+                })
+                .and_then(|(con, n)| {
+                    assert_eq!(n, 1);
+                    con.scard("union")
+                })
+                .map(|(_, n): (_, usize)| {
+                    assert_eq!(n, 4);
+                })
+        })
+    });
+}
+
+#[test]
+fn type_of() {
+    test(|c| {
+        c.get_async_connection()
+            .and_then(|con| con.set("string", "v"))
+            .and_then(|(con, ()): (_, ())| con.rpush("list", "v"))
+            .and_then(|(con, _): (_, usize)| con.type_of("string"))
+            .and_then(|(con, ty): (_, RedisKeyType)| {
+                assert_eq!(ty, RedisKeyType::String);
+                con.type_of("list")
+            })
+            .and_then(|(con, ty): (_, String)| {
+                assert_eq!(ty, "list");
+                con.type_of("missing")
+            })
+            .map(|(_, ty): (_, RedisKeyType)| {
+                assert_eq!(ty, RedisKeyType::None);
+            })
+    })
+}
+
+#[test]
+fn hset_created() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.hset_created("hash", "field", 1)
+                .and_then(|(con, created)| {
+                    assert!(created);
+                    con.hset_created("hash", "field", 2)
+                })
+                .map(|(_, created)| {
+                    assert!(!created);
+                })
+        })
+    });
+}
+
+#[test]
+fn smembers_set() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.sadd_count("set", &["a", "b", "c"])
+                .and_then(|(con, _)| con.smembers_set("set"))
+                .map(|(_, set): (_, HashSet<String>)| {
+                    let exp: HashSet<String> =
+                        vec!["a", "b", "c"].into_iter().map(String::from).collect();
+                    assert_eq!(set, exp);
+                })
+        })
+    });
+}
+
+#[test]
+fn zdiffstore() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.zadd_multiple("a", &[(1, "x"), (2, "y"), (3, "z")])
+                .and_then(|(con, _): (_, usize)| con.zadd("b", "y", 2))
+                .and_then(|(con, _): (_, usize)| con.zdiffstore("diff", &["a", "b"]))
+                .and_then(|(con, n): (_, usize)| {
+                    assert_eq!(n, 2);
+                    con.zrange_withscores("diff", 0, -1)
+                })
+                .map(|(_, res): (_, Vec<(String, u32)>)| {
+                    assert_eq!(res, vec![("x".into(), 1), ("z".into(), 3)]);
+                })
+        })
+    });
+}
+
+#[test]
+fn push_len() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.lpush_len("list", "b")
+                .and_then(|(con, n)| {
+                    assert_eq!(n, 1);
+                    con.rpush_len("list", &["c", "d"])
+                })
+                .and_then(|(con, n)| {
+                    assert_eq!(n, 3);
+                    con.lrange("list", 0, -1)
+                })
+                .map(|(_, list): (_, Vec<String>)| {
+                    assert_eq!(list, vec!["b", "c", "d"]);
+                })
+        })
+    });
+}
+
+#[test]
+fn lpush_capped() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            futures::stream::iter_ok(1..=5)
+                .fold((con, Vec::new()), |(con, mut lens), i| {
+                    con.lpush_capped("recent", i, 3).map(move |(con, n)| {
+                        lens.push(n);
+                        (con, lens)
+                    })
+                })
+                .and_then(|(con, lens)| {
+                    assert_eq!(lens, vec![1, 2, 3, 3, 3]);
+                    con.lrange("recent", 0, -1)
+                })
+                .map(|(_, list): (_, Vec<u32>)| {
+                    assert_eq!(list, vec![5, 4, 3]);
+                })
+        })
+    });
+}
+
+fn lrem_count_case(count: isize, removed: usize, exp: Vec<&'static str>) {
+    test(move |c| {
+        c.get_async_connection().and_then(move |con| {
+            con.rpush_len("list", &["x", "a", "x", "b", "x"])
+                .and_then(move |(con, _)| con.lrem_count("list", count, "x"))
+                .and_then(move |(con, n)| {
+                    assert_eq!(n, removed);
+                    con.lrange("list", 0, -1)
+                })
+                .map(move |(_, list): (_, Vec<String>)| {
+                    assert_eq!(list, exp);
+                })
+        })
+    });
+}
+
+#[test]
+fn lrem_count_positive() {
+    // Removes from the head.
+    lrem_count_case(2, 2, vec!["a", "b", "x"]);
+}
+
+#[test]
+fn lrem_count_negative() {
+    // Removes from the tail.
+    lrem_count_case(-2, 2, vec!["x", "a", "b"]);
+}
+
+#[test]
+fn lrem_count_zero() {
+    // Removes all.
+    lrem_count_case(0, 3, vec!["a", "b"]);
+}
+
+#[test]
+fn lrotate() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.rpush_len("ring", &["a", "b", "c"])
+                .and_then(|(con, _)| con.lrotate("ring"))
+                .and_then(|(con, v)| {
+                    assert_eq!(v, Some("c".into()));
+                    con.lrange("ring", 0, -1)
+                })
+                .and_then(|(con, list): (_, Vec<String>)| {
+                    assert_eq!(list, vec!["c", "a", "b"]);
+                    con.lrotate("ring")
+                })
+                .and_then(|(con, v)| {
+                    assert_eq!(v, Some("b".into()));
+                    con.lrotate("ring")
+                })
+                .and_then(|(con, v)| {
+                    assert_eq!(v, Some("a".into()));
+                    con.lrange("ring", 0, -1)
+                })
+                .and_then(|(con, list): (_, Vec<String>)| {
+                    // Back to the original order after a full cycle.
+                    assert_eq!(list, vec!["a", "b", "c"]);
+                    con.lrotate("empty")
+                })
+                .map(|(_, v)| {
+                    assert_eq!(v, None);
+                })
+        })
+    });
+}
+
+#[test]
+fn blocking_pop_typed() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.rpush_len("list", &["a", "b"])
+                .and_then(|(con, _)| con.blpop_typed("list", 1))
+                .and_then(|(con, v)| {
+                    assert_eq!(v, Some(("list".into(), "a".into())));
+                    con.brpop_typed(&["empty", "list"], 1)
+                })
+                .and_then(|(con, v)| {
+                    assert_eq!(v, Some(("list".into(), "b".into())));
+                    con.blpop_typed("list", 1)
+                })
+                .and_then(|(con, v)| {
+                    // Timed out.
+                    assert_eq!(v, None);
+                    con.brpop_typed("list", 1)
+                })
+                .map(|(_, v)| {
+                    assert_eq!(v, None);
+                })
+        })
+    });
+}
+
 #[test]
 fn scan() {
     test(|c| {
@@ -51,144 +316,274 @@ fn scan_match() {
 }
 
 #[test]
-fn scan_match_all() {
+fn scan_sorted() {
     test(|c| {
         let exp = write_values("key");
         let _ = write_values("garbage");
 
         c.get_async_connection()
-            .and_then(|con| con.scan_match("key:*").all())
-            .map(|(_, mut res): (_, Vec<String>)| {
-                res.sort();
+            .and_then(|con| con.scan_sorted("key:*"))
+            .map(|(_, res)| {
+                // The keys are zero-padded, so written in the sorted order.
                 assert_eq!(res, keys(exp))
             })
     })
 }
 
 #[test]
-fn hscan() {
+fn scan_match_count() {
     test(|c| {
-        let exp = write_hash_values("hash", "key");
+        let exp = write_values("key");
+        let _ = write_values("garbage");
 
         c.get_async_connection()
             .and_then(|con| {
-                con.hscan("hash")
-                    .filter_map(|(c, v)| {
-                        assert!((c.is_some() && v.is_none()) || v.is_some());
-                        v
-                    })
+                con.scan_match_count("key:*", 1000)
+                    .filter_map(|(_, v)| v)
                     .collect()
             })
-            .map(move |mut res: Vec<(String, String)>| {
+            .map(|mut res: Vec<String>| {
                 res.sort();
-                assert_eq!(res, both(exp))
+                assert_eq!(res, keys(exp))
             })
     })
 }
 
 #[test]
-fn hscan_match() {
+fn scan_resume() {
     test(|c| {
-        let exp = write_hash_values("hash", "key");
-        let _ = write_hash_values("hash", "garbage");
+        let exp = write_values("key");
+        let factory = |cur| {
+            let mut c = redis::cmd("SCAN");
+            c.arg(cur).arg("COUNT").arg(10);
+            c
+        };
+
+        let first = c.get_async_connection().and_then(move |con| {
+            let s = stream_from(con, factory, 0);
+            future::loop_fn((s, HashSet::new()), |(s, mut items)| {
+                s.into_future().map_err(|(e, _)| e).map(move |(item, s)| {
+                    if let Some((_, Some(item))) = item {
+                        items.insert(item);
+                    }
+                    if items.len() == 25 {
+                        Loop::Break((s.cursor(), items))
+                    } else {
+                        Loop::Continue((s, items))
+                    }
+                })
+            })
+        });
+
+        // Interrupted, and then resumed with another connection.
+        first
+            .and_then(move |(cursor, mut items)| {
+                assert_ne!(cursor, 0);
+                c.get_async_connection()
+                    .and_then(move |con| {
+                        stream_from(con, factory, cursor)
+                            .filter_map(|(_, v)| v)
+                            .collect()
+                    })
+                    .map(move |rest: Vec<String>| {
+                        items.extend(rest);
+                        items
+                    })
+            })
+            .map(|items| {
+                let mut items: Vec<_> = items.into_iter().collect();
+                items.sort();
+                assert_eq!(items, keys(exp))
+            })
+    })
+}
+
+#[test]
+fn scan_type() {
+    test(|c| {
+        let _ = write_hash_values("hash", "key");
+        let _ = write_values("key");
+        let _ = write_set_values("set", "key");
+
+        c.get_async_connection()
+            .and_then(|con| con.scan_type("hash").filter_map(|(_, v)| v).collect())
+            .map(|res: Vec<String>| {
+                assert_eq!(res, vec!["hash"]);
+            })
+    })
+}
+
+#[test]
+fn scan_match_type() {
+    test(|c| {
+        let exp = write_values("key");
+        let _ = write_values("garbage");
+        let _ = write_hash_values("key:hash", "key");
 
         c.get_async_connection()
             .and_then(|con| {
-                con.hscan_match("hash", "key:*")
-                    .filter_map(|(c, v)| {
-                        assert!((c.is_some() && v.is_none()) || v.is_some());
-                        v
-                    })
+                con.scan_match_type("key:*", "string")
+                    .filter_map(|(_, v)| v)
                     .collect()
             })
-            .map(move |mut res: Vec<(String, String)>| {
+            .map(|mut res: Vec<String>| {
                 res.sort();
-                assert_eq!(res, both(exp))
+                assert_eq!(res, keys(exp))
             })
     })
 }
 
 #[test]
-fn hscan_match_all() {
+fn scan_match_all() {
     test(|c| {
-        let exp = write_hash_values("hash", "key");
-        let _ = write_hash_values("hash", "garbage");
+        let exp = write_values("key");
+        let _ = write_values("garbage");
 
         c.get_async_connection()
-            .and_then(|con| con.hscan_match("hash", "key:*").all())
-            .map(move |(_, mut res): (_, Vec<(String, String)>)| {
+            .and_then(|con| con.scan_match("key:*").all())
+            .map(|(_, mut res): (_, Vec<String>)| {
                 res.sort();
-                assert_eq!(res, both(exp))
+                assert_eq!(res, keys(exp))
             })
     })
 }
 
 #[test]
-fn sscan() {
+fn count_matching() {
     test(|c| {
-        let exp = write_set_values("set", "key");
+        let exp = write_values("key");
+        let _ = write_values("garbage");
+
+        c.get_async_connection()
+            .and_then(|con| con.count_matching("key:*"))
+            .map(move |(_, n)| assert_eq!(n, exp.len()))
+    })
+}
+
+#[cfg(feature = "bench-support")]
+#[test]
+fn count_matching_seeded() {
+    test(|c| {
+        let mut con = c.get_connection().unwrap();
+        let keys = redis_ac::bench_support::seed_keys(&mut con, "seed", 2500).unwrap();
+
+        c.get_async_connection()
+            .and_then(|con| con.count_matching("seed:*"))
+            .map(move |(_, n)| assert_eq!(n, keys.len()))
+    })
+}
+
+#[test]
+fn bitcount_scan() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.setbit("bits:a", 1, true)
+                .and_then(|(con, _): (_, bool)| con.setbit("bits:a", 7, true))
+                .and_then(|(con, _): (_, bool)| con.set("bits:b", b"\xff"))
+                .and_then(|(con, _): (_, String)| con.setbit("bits:c", 100, true))
+                .and_then(|(con, _): (_, bool)| con.set("other", b"\xff"))
+                .and_then(|(con, _): (_, String)| con.bitcount_scan("bits:*"))
+                .map(|(_, n)| {
+                    assert_eq!(n, 2 + 8 + 1);
+                })
+        })
+    });
+}
+
+#[test]
+fn scan_without_ttl() {
+    test(|c| {
+        let exp = write_values("key");
 
         c.get_async_connection()
             .and_then(|con| {
-                con.sscan("set")
-                    .filter_map(|(c, v)| {
-                        assert!((c.is_some() && v.is_none()) || v.is_some());
-                        v
-                    })
+                con.set_ex("key:volatile1", "v", 100)
+                    .and_then(|(con, ()): (_, ())| con.set_ex("key:volatile2", "v", 100))
+                    .and_then(|(con, ()): (_, ())| con.set_ex("garbage", "v", 100))
+            })
+            .and_then(|(con, ())| {
+                con.scan_without_ttl("key:*")
+                    .filter_map(|(_, v)| v)
                     .collect()
             })
-            .map(move |mut res: Vec<String>| {
+            .map(|mut res: Vec<String>| {
                 res.sort();
-                assert_eq!(res, keys(exp));
+                assert_eq!(res, keys(exp))
             })
     })
 }
 
 #[test]
-fn sscan_match() {
+fn sweep_expired() {
     test(|c| {
-        let exp = write_set_values("set", "key");
-        let _ = write_set_values("set", "garbage");
-
         c.get_async_connection()
             .and_then(|con| {
-                con.sscan_match("set", "key:*")
-                    .filter_map(|(c, v)| {
-                        assert!((c.is_some() && v.is_none()) || v.is_some());
-                        v
+                con.hset_multiple("session:expired1", &[("expires_at", "100")])
+                    .and_then(|(con, ()): (_, ())| {
+                        con.hset_multiple("session:expired2", &[("expires_at", "199")])
                     })
-                    .collect()
+                    .and_then(|(con, ()): (_, ())| {
+                        con.hset_multiple("session:live", &[("expires_at", "300")])
+                    })
+                    .and_then(|(con, ()): (_, ())| {
+                        con.hset_multiple("session:forever", &[("f", "v")])
+                    })
+                    .and_then(|(con, ()): (_, ())| con.set("session:string", "v"))
             })
-            .map(move |mut res: Vec<String>| {
+            .and_then(|(con, ()): (_, ())| con.sweep_expired("session:*", 200))
+            .and_then(|(con, n)| {
+                assert_eq!(n, 2);
+                con.keys("session:*")
+            })
+            .map(|(_, mut res): (_, Vec<String>)| {
                 res.sort();
-                assert_eq!(res, keys(exp));
+                assert_eq!(
+                    res,
+                    vec!["session:forever", "session:live", "session:string"]
+                );
             })
     })
 }
 
 #[test]
-fn sscan_match_all() {
+fn scan_typed() {
     test(|c| {
-        let exp = write_set_values("set", "key");
-        let _ = write_set_values("set", "garbage");
-
         c.get_async_connection()
-            .and_then(|con| con.sscan_match("set", "key:*").all())
-            .map(move |(_, mut res): (_, Vec<String>)| {
-                res.sort();
-                assert_eq!(res, keys(exp));
+            .and_then(|con| {
+                con.set("key:string", "v")
+                    .and_then(|(con, ()): (_, ())| con.rpush_len("key:list", &["a", "b"]))
+                    .and_then(|(con, _)| con.hset_multiple("key:hash", &[("f", "v")]))
+                    .and_then(|(con, ()): (_, ())| con.set("garbage", "v"))
+            })
+            .and_then(|(con, ()): (_, ())| con.scan_typed("key:*").filter_map(|(_, v)| v).collect())
+            .map(|mut res: Vec<(String, TypedValue)>| {
+                res.sort_by(|a, b| a.0.cmp(&b.0));
+                assert_eq!(
+                    res,
+                    vec![
+                        (
+                            "key:hash".into(),
+                            TypedValue::Hash(vec![(b"f".to_vec(), b"v".to_vec())])
+                        ),
+                        (
+                            "key:list".into(),
+                            TypedValue::List(vec![b"a".to_vec(), b"b".to_vec()])
+                        ),
+                        ("key:string".into(), TypedValue::String(b"v".to_vec())),
+                    ]
+                );
             })
     })
 }
 
 #[test]
-fn zscan() {
+fn hscan() {
     test(|c| {
-        let exp = write_zset_values("zset", "key");
+        let exp = write_hash_values("hash", "key");
 
         c.get_async_connection()
             .and_then(|con| {
-                con.zscan("zset")
+                con.hscan("hash")
                     .filter_map(|(c, v)| {
                         assert!((c.is_some() && v.is_none()) || v.is_some());
                         v
@@ -197,20 +592,212 @@ fn zscan() {
             })
             .map(move |mut res: Vec<(String, String)>| {
                 res.sort();
-                assert_eq!(res, both(exp));
+                assert_eq!(res, both(exp))
             })
     })
 }
 
 #[test]
-fn zscan_match() {
+fn hscan_count() {
     test(|c| {
-        let exp = write_zset_values("zset", "key");
-        let _ = write_zset_values("zset", "garbage");
+        let exp = write_hash_values("hash", "key");
 
         c.get_async_connection()
             .and_then(|con| {
-                con.zscan_match("zset", "key:*")
+                con.hscan_count("hash", 1000)
+                    .filter_map(|(_, v)| v)
+                    .collect()
+            })
+            .map(move |mut res: Vec<(String, String)>| {
+                res.sort();
+                assert_eq!(res, both(exp))
+            })
+    })
+}
+
+#[test]
+fn hscan_match() {
+    test(|c| {
+        let exp = write_hash_values("hash", "key");
+        let _ = write_hash_values("hash", "garbage");
+
+        c.get_async_connection()
+            .and_then(|con| {
+                con.hscan_match("hash", "key:*")
+                    .filter_map(|(c, v)| {
+                        assert!((c.is_some() && v.is_none()) || v.is_some());
+                        v
+                    })
+                    .collect()
+            })
+            .map(move |mut res: Vec<(String, String)>| {
+                res.sort();
+                assert_eq!(res, both(exp))
+            })
+    })
+}
+
+#[test]
+fn hscan_match_all() {
+    test(|c| {
+        let exp = write_hash_values("hash", "key");
+        let _ = write_hash_values("hash", "garbage");
+
+        c.get_async_connection()
+            .and_then(|con| con.hscan_match("hash", "key:*").all())
+            .map(move |(_, mut res): (_, Vec<(String, String)>)| {
+                res.sort();
+                assert_eq!(res, both(exp))
+            })
+    })
+}
+
+#[test]
+fn sscan() {
+    test(|c| {
+        let exp = write_set_values("set", "key");
+
+        c.get_async_connection()
+            .and_then(|con| {
+                con.sscan("set")
+                    .filter_map(|(c, v)| {
+                        assert!((c.is_some() && v.is_none()) || v.is_some());
+                        v
+                    })
+                    .collect()
+            })
+            .map(move |mut res: Vec<String>| {
+                res.sort();
+                assert_eq!(res, keys(exp));
+            })
+    })
+}
+
+#[test]
+fn sscan_count() {
+    test(|c| {
+        let exp = write_set_values("set", "key");
+
+        c.get_async_connection()
+            .and_then(|con| {
+                con.sscan_count("set", 1000)
+                    .filter_map(|(_, v)| v)
+                    .collect()
+            })
+            .map(move |mut res: Vec<String>| {
+                res.sort();
+                assert_eq!(res, keys(exp));
+            })
+    })
+}
+
+#[test]
+fn sscan_match() {
+    test(|c| {
+        let exp = write_set_values("set", "key");
+        let _ = write_set_values("set", "garbage");
+
+        c.get_async_connection()
+            .and_then(|con| {
+                con.sscan_match("set", "key:*")
+                    .filter_map(|(c, v)| {
+                        assert!((c.is_some() && v.is_none()) || v.is_some());
+                        v
+                    })
+                    .collect()
+            })
+            .map(move |mut res: Vec<String>| {
+                res.sort();
+                assert_eq!(res, keys(exp));
+            })
+    })
+}
+
+#[test]
+fn sscan_match_all() {
+    test(|c| {
+        let exp = write_set_values("set", "key");
+        let _ = write_set_values("set", "garbage");
+
+        c.get_async_connection()
+            .and_then(|con| con.sscan_match("set", "key:*").all())
+            .map(move |(_, mut res): (_, Vec<String>)| {
+                res.sort();
+                assert_eq!(res, keys(exp));
+            })
+    })
+}
+
+#[test]
+fn scan_orphans() {
+    test(|c| {
+        c.get_async_connection()
+            .and_then(|con| {
+                con.sadd_count("index", &["a", "b", "c", "d"])
+                    .and_then(|(con, _)| con.set("item:a", "v"))
+                    .and_then(|(con, ()): (_, ())| con.set("item:c", "v"))
+                    .and_then(|(con, ()): (_, ())| con.set("b", "v"))
+            })
+            .and_then(|(con, ()): (_, ())| {
+                con.scan_orphans("index", "item:")
+                    .filter_map(|(_, v)| v)
+                    .collect()
+            })
+            .map(|mut res: Vec<String>| {
+                res.sort();
+                assert_eq!(res, vec!["b", "d"]);
+            })
+    })
+}
+
+#[test]
+fn zscan() {
+    test(|c| {
+        let exp = write_zset_values("zset", "key");
+
+        c.get_async_connection()
+            .and_then(|con| {
+                con.zscan("zset")
+                    .filter_map(|(c, v)| {
+                        assert!((c.is_some() && v.is_none()) || v.is_some());
+                        v
+                    })
+                    .collect()
+            })
+            .map(move |mut res: Vec<(String, String)>| {
+                res.sort();
+                assert_eq!(res, both(exp));
+            })
+    })
+}
+
+#[test]
+fn zscan_count() {
+    test(|c| {
+        let exp = write_zset_values("zset", "key");
+
+        c.get_async_connection()
+            .and_then(|con| {
+                con.zscan_count("zset", 1000)
+                    .filter_map(|(_, v)| v)
+                    .collect()
+            })
+            .map(move |mut res: Vec<(String, String)>| {
+                res.sort();
+                assert_eq!(res, both(exp));
+            })
+    })
+}
+
+#[test]
+fn zscan_match() {
+    test(|c| {
+        let exp = write_zset_values("zset", "key");
+        let _ = write_zset_values("zset", "garbage");
+
+        c.get_async_connection()
+            .and_then(|con| {
+                con.zscan_match("zset", "key:*")
                     .filter_map(|(c, v)| {
                         assert!((c.is_some() && v.is_none()) || v.is_some());
                         v
@@ -238,3 +825,1078 @@ fn zscan_match_all() {
             })
     })
 }
+
+#[cfg(feature = "debug-commands")]
+#[test]
+fn passive_expire() {
+    test(|c| {
+        let _guard = write_expired("key", 100);
+
+        c.get_async_connection().and_then(|con| {
+            // The expired key is still in the keyspace as nobody accessed it.
+            redis::cmd("DBSIZE")
+                .query_async(con)
+                .and_then(|(con, n): (_, usize)| {
+                    assert_eq!(n, 1);
+                    // Accessing the key makes it expire.
+                    con.get("key")
+                })
+                .and_then(|(con, v): (_, Option<String>)| {
+                    assert_eq!(v, None);
+                    redis::cmd("DBSIZE").query_async(con)
+                })
+                .map(|(_, n): (_, usize)| {
+                    assert_eq!(n, 0);
+                })
+        })
+    });
+}
+
+#[test]
+fn scan_cancel() {
+    test(|c| {
+        let exp = write_values("key");
+        let (tx, rx) = futures::sync::oneshot::channel();
+        let mut tx = Some(tx);
+
+        c.get_async_connection()
+            .and_then(move |con| {
+                con.scan().with_cancel(rx).fold(
+                    (None, 0),
+                    move |(last, n), (con, item): (_, Option<String>)| {
+                        // Cancel on the first item.
+                        if let Some(tx) = tx.take() {
+                            let _ = tx.send(());
+                        }
+                        assert!(last.is_none());
+                        Ok::<_, redis::RedisError>((con, n + item.map_or(0, |_| 1)))
+                    },
+                )
+            })
+            .and_then(move |(con, n)| {
+                assert!(n <= exp.len());
+                if exp.len() > 100 {
+                    // Stopped long before scanning everything.
+                    assert!(n < exp.len() / 2);
+                }
+                // The connection is returned and reusable.
+                redis::cmd("PING").query_async(con.unwrap())
+            })
+            .map(|(_, pong): (_, String)| {
+                assert_eq!(pong, "PONG");
+            })
+    })
+}
+
+#[cfg(feature = "debug-commands")]
+#[test]
+fn set_with_meta() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.set_with_meta("key", "value", redis_ac::ObjectMeta::Idle(1000))
+                .and_then(|(con, ())| {
+                    redis::cmd("OBJECT")
+                        .arg("IDLETIME")
+                        .arg("key")
+                        .query_async(con)
+                })
+                .and_then(|(con, idle): (_, u64)| {
+                    assert!(idle >= 1000);
+                    con.get("key")
+                })
+                .map(|(_, v): (_, String)| {
+                    assert_eq!(v, "value");
+                })
+        })
+    });
+}
+
+#[test]
+fn read_frame() {
+    test(|c| {
+        let mut frames: Vec<u8> = Vec::new();
+        for body in &[&b"first"[..], &b""[..], &b"second frame"[..]] {
+            frames.extend(&(body.len() as u32).to_be_bytes());
+            frames.extend(*body);
+        }
+        frames.extend(&[0, 0, 0, 9, b'x']);
+
+        c.get_async_connection()
+            .and_then(move |con| con.set("frames", frames))
+            .and_then(|(con, ()): (_, ())| con.read_frame("frames", 0))
+            .and_then(|(con, (offset, body))| {
+                assert_eq!((offset, &body[..]), (9, &b"first"[..]));
+                con.read_frame("frames", offset)
+            })
+            .and_then(|(con, (offset, body))| {
+                assert_eq!((offset, &body[..]), (13, &b""[..]));
+                con.read_frame("frames", offset)
+            })
+            .and_then(|(con, (offset, body))| {
+                assert_eq!((offset, &body[..]), (29, &b"second frame"[..]));
+                con.read_frame("frames", offset)
+            })
+            .then(|res| {
+                // Truncated frame.
+                assert!(res.is_err());
+                Ok::<_, ()>(())
+            })
+    })
+}
+
+#[test]
+fn size_histogram() {
+    test(|c| {
+        c.get_async_connection()
+            .and_then(|con| con.set("key:small1", "v"))
+            .and_then(|(con, ()): (_, ())| con.set("key:small2", "v"))
+            .and_then(|(con, ()): (_, ())| con.set("key:medium", "v".repeat(1000)))
+            .and_then(|(con, ()): (_, ())| con.set("key:large", "v".repeat(100_000)))
+            .and_then(|(con, ()): (_, ())| con.set("garbage", "v".repeat(100_000)))
+            .and_then(|(con, ()): (_, ())| con.size_histogram("key:*", &[500, 10_000]))
+            .map(|(_, counts)| {
+                assert_eq!(counts, vec![2, 1, 1]);
+            })
+    })
+}
+
+#[test]
+fn size_histogram_strlen() {
+    test(|c| {
+        c.get_async_connection()
+            .and_then(|con| con.set("key:small", "v"))
+            .and_then(|(con, ()): (_, ())| con.set("key:medium", "v".repeat(500)))
+            .and_then(|(con, ()): (_, ())| con.set("key:large1", "v".repeat(501)))
+            .and_then(|(con, ()): (_, ())| con.set("key:large2", "v".repeat(10_000)))
+            .and_then(|(con, ()): (_, ())| {
+                con.size_histogram_by("key:*", &[1, 500], SizeMetric::Strlen)
+            })
+            .map(|(_, counts)| {
+                // Exact lengths unlike memory usage.
+                assert_eq!(counts, vec![1, 1, 2]);
+            })
+    })
+}
+
+#[derive(Debug, PartialEq)]
+struct Config {
+    name: String,
+    port: u16,
+    ratio: f64,
+}
+
+impl FromRedisHash for Config {
+    fn from_redis_hash(hash: &RedisHash) -> redis::RedisResult<Self> {
+        Ok(Self {
+            name: hash.required("name")?,
+            port: hash.required("port")?,
+            ratio: hash.required("ratio")?,
+        })
+    }
+}
+
+#[test]
+fn hget_struct() {
+    test(|c| {
+        c.get_async_connection()
+            .and_then(|con| {
+                con.hset_multiple(
+                    "config",
+                    &[("name", "server"), ("port", "6379"), ("ratio", "0.5")],
+                )
+            })
+            .and_then(|(con, ()): (_, ())| con.hget_struct("config"))
+            .and_then(|(con, config): (_, Config)| {
+                assert_eq!(
+                    config,
+                    Config {
+                        name: "server".into(),
+                        port: 6379,
+                        ratio: 0.5,
+                    }
+                );
+                con.hdel("config", "port")
+            })
+            .and_then(|(con, _): (_, usize)| con.hget_struct::<_, Config>("config"))
+            .then(|res| {
+                // A required field is missing.
+                assert!(res.is_err());
+                Ok::<_, ()>(())
+            })
+    })
+}
+
+#[test]
+fn cap_ttls() {
+    test(|c| {
+        c.get_async_connection()
+            .and_then(|con| con.set_ex("key:short", "v", 10))
+            .and_then(|(con, ()): (_, ())| con.set_ex("key:long", "v", 1000))
+            .and_then(|(con, ()): (_, ())| con.set("key:forever", "v"))
+            .and_then(|(con, ()): (_, ())| con.set("garbage", "v"))
+            .and_then(|(con, ()): (_, ())| con.cap_ttls("key:*", 100))
+            .and_then(|(con, n)| {
+                assert_eq!(n, 2);
+                let mut p = redis::pipe();
+                for key in &["key:short", "key:long", "key:forever", "garbage"] {
+                    p.cmd("TTL").arg(*key);
+                }
+                p.query_async(con)
+            })
+            .map(|(_, ttls): (_, Vec<i64>)| {
+                assert!(ttls[0] <= 10 && ttls[0] > 0);
+                assert!(ttls[1] <= 100 && ttls[1] > 10);
+                assert!(ttls[2] <= 100 && ttls[2] > 10);
+                assert_eq!(ttls[3], -1);
+            })
+    })
+}
+
+#[test]
+fn cap_ttls_zero() {
+    test(|c| {
+        let c2 = c.clone();
+
+        c.get_async_connection()
+            .and_then(|con| con.set("key", "v"))
+            .and_then(|(con, ()): (_, ())| con.cap_ttls("key*", 0))
+            .then(move |res| {
+                assert_eq!(
+                    res.err().unwrap().kind(),
+                    redis::ErrorKind::InvalidClientConfig
+                );
+                c2.get_async_connection()
+            })
+            .and_then(|con| con.exists("key"))
+            .map(|(_, exists): (_, bool)| assert!(exists))
+    })
+}
+
+#[test]
+fn mget_or_compute() {
+    test(|c| {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let calls2 = calls.clone();
+        let calls3 = calls.clone();
+
+        c.get_async_connection().and_then(move |con| {
+            con.set("a", "cached a")
+                .and_then(|(con, ()): (_, ())| con.set("c", "cached c"))
+                .and_then(move |(con, ()): (_, ())| {
+                    con.mget_or_compute(&["a", "b", "c", "d"], 100, move |keys: Vec<&str>| {
+                        calls.lock().unwrap().push(keys.clone());
+                        Ok(keys.iter().map(|k| format!("computed {}", k)).collect())
+                    })
+                })
+                .and_then(move |(con, values): (_, Vec<String>)| {
+                    assert_eq!(
+                        values,
+                        vec!["cached a", "computed b", "cached c", "computed d"]
+                    );
+                    assert_eq!(*calls2.lock().unwrap(), vec![vec!["b", "d"]]);
+                    con.ttl("b")
+                })
+                .and_then(|(con, ttl): (_, i64)| {
+                    assert!(ttl > 0 && ttl <= 100);
+                    // All cached now.
+                    con.mget_or_compute(
+                        &["a", "b", "c", "d"],
+                        100,
+                        |_: Vec<&str>| -> Result<Vec<String>, _> { panic!("computed again") },
+                    )
+                })
+                .map(move |(_, values): (_, Vec<String>)| {
+                    assert_eq!(values[1], "computed b");
+                    assert_eq!(calls3.lock().unwrap().len(), 1);
+                })
+        })
+    });
+}
+
+#[test]
+fn grid_add() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.grid_add("cells", 57.64911, 10.40744, "jutland", 11)
+                .and_then(|(con, key)| {
+                    assert_eq!(key, "cells:u4pruydqqvj");
+                    // About 14m apart.
+                    con.grid_add("cells", 35.6812, 139.7671, "a", 4)
+                })
+                .and_then(|(con, a)| {
+                    con.grid_add("cells", 35.6813, 139.7672, "b", 4)
+                        .map(move |(con, b)| (con, a, b))
+                })
+                .and_then(|(con, a, b)| {
+                    assert_eq!(a, b);
+                    con.smembers_set(a)
+                })
+                .and_then(|(con, members): (_, HashSet<String>)| {
+                    assert_eq!(members, vec!["a".into(), "b".into()].into_iter().collect());
+                    con.grid_add("cells", 35.6812, 139.7671, "a", 9)
+                })
+                .and_then(|(con, a)| {
+                    con.grid_add("cells", 35.6813, 139.7672, "b", 9)
+                        .map(move |(_, b)| (a, b))
+                })
+                .map(|(a, b)| assert_ne!(a, b))
+        })
+    });
+}
+
+#[test]
+fn prepare_delete() {
+    test(|c| {
+        let exp = write_values("key");
+        let garbage = write_values("garbage");
+        let n = exp.len();
+
+        c.get_async_connection()
+            .and_then(|con| con.prepare_delete("key:*"))
+            .and_then(move |prepared| {
+                assert_eq!(prepared.count(), n);
+                prepared.commit()
+            })
+            .and_then(move |(con, deleted)| {
+                assert_eq!(deleted, n);
+                con.scan_sorted("*")
+            })
+            .map(move |(_, left)| assert_eq!(left, keys(garbage)))
+    })
+}
+
+#[test]
+fn prepare_delete_grown() {
+    test(|c| {
+        let c2 = c.clone();
+        let c3 = c.clone();
+
+        c.get_async_connection()
+            .join(c.get_async_connection())
+            .and_then(|(con, other)| {
+                let other = other.set_multiple(&[("key:1", "1"), ("key:2", "2")]);
+                other.and_then(move |(other, ()): (_, ())| {
+                    con.prepare_delete("key:*")
+                        .map(move |prepared| (prepared, other))
+                })
+            })
+            .and_then(|(prepared, other)| {
+                assert_eq!(prepared.count(), 2);
+                other
+                    .set("key:3", "3")
+                    .and_then(move |(_, ()): (_, ())| prepared.commit())
+                    .then(|res| {
+                        // Nothing is deleted.
+                        assert_eq!(res.err().unwrap().kind(), redis::ErrorKind::ResponseError);
+                        Ok(())
+                    })
+            })
+            .and_then(move |()| c2.get_async_connection().join(c3.get_async_connection()))
+            .and_then(|(con, other)| {
+                con.prepare_delete("key:*")
+                    .map(move |prepared| (prepared, other))
+            })
+            .and_then(|(prepared, other)| {
+                assert_eq!(prepared.count(), 3);
+                other
+                    .set("key:4", "4")
+                    .and_then(move |(_, ()): (_, ())| prepared.allow_growth(1).commit())
+            })
+            .map(|(_, deleted)| assert_eq!(deleted, 4))
+    })
+}
+
+fn args<T: redis::ToRedisArgs>(v: &T) -> Vec<String> {
+    v.to_redis_args()
+        .into_iter()
+        .map(|a| String::from_utf8(a).unwrap())
+        .collect()
+}
+
+#[test]
+fn set_options_args() {
+    assert!(args(&SetOptions::default()).is_empty());
+    assert_eq!(
+        args(&SetOptions::default().px(30000).nx()),
+        vec!["NX", "PX", "30000"]
+    );
+    assert_eq!(
+        args(&SetOptions::default().ex(10).get().xx()),
+        vec!["XX", "GET", "EX", "10"]
+    );
+    // The last expiry wins.
+    assert_eq!(
+        args(&SetOptions::default().ex(10).keep_ttl()),
+        vec!["KEEPTTL"]
+    );
+}
+
+#[test]
+fn set_options() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.set_options("lock", "a", SetOptions::default().nx().px(30000))
+                .and_then(|(con, ok): (_, Option<String>)| {
+                    assert_eq!(ok.as_deref(), Some("OK"));
+                    con.set_options("lock", "b", SetOptions::default().nx().px(30000))
+                })
+                .and_then(|(con, ok): (_, Option<String>)| {
+                    // Already locked.
+                    assert!(ok.is_none());
+                    con.set_options("lock", "c", SetOptions::default().keep_ttl().get())
+                })
+                .and_then(|(con, old): (_, Option<String>)| {
+                    assert_eq!(old.as_deref(), Some("a"));
+                    con.ttl("lock")
+                })
+                .and_then(|(con, ttl): (_, i64)| {
+                    assert!(ttl > 0 && ttl <= 30);
+                    con.set_options("missing", "x", SetOptions::default().get())
+                })
+                .map(|(_, old): (_, Option<String>)| assert!(old.is_none()))
+        })
+    });
+}
+
+#[test]
+fn expire_opts() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.set("session", "1")
+                .and_then(|(con, ()): (_, ())| con.expire_opts("session", 100, ExpireOption::Xx))
+                .and_then(|(con, set): (_, bool)| {
+                    // No timeout yet.
+                    assert!(!set);
+                    con.expire_opts("session", 100, ExpireOption::Nx)
+                })
+                .and_then(|(con, set): (_, bool)| {
+                    assert!(set);
+                    con.expire_opts("session", 10, ExpireOption::Gt)
+                })
+                .and_then(|(con, set): (_, bool)| {
+                    // Never shortened.
+                    assert!(!set);
+                    con.pexpire_opts("session", 200_000, ExpireOption::Gt)
+                })
+                .and_then(|(con, set): (_, bool)| {
+                    assert!(set);
+                    con.ttl("session")
+                })
+                .map(|(_, ttl): (_, i64)| assert!(ttl > 100 && ttl <= 200))
+        })
+    });
+}
+
+#[test]
+fn getdel_getex() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.set_ex("token", "t", 100)
+                .and_then(|(con, ()): (_, ())| con.getex("token", GetExOptions::Persist))
+                .and_then(|(con, v): (_, Option<String>)| {
+                    assert_eq!(v.as_deref(), Some("t"));
+                    con.ttl("token")
+                })
+                .and_then(|(con, ttl): (_, i64)| {
+                    assert_eq!(ttl, -1);
+                    con.getex("token", GetExOptions::Ex(50))
+                })
+                .and_then(|(con, _): (_, Option<String>)| con.ttl("token"))
+                .and_then(|(con, ttl): (_, i64)| {
+                    assert!(ttl > 0 && ttl <= 50);
+                    con.getdel("token")
+                })
+                .and_then(|(con, v): (_, Option<String>)| {
+                    assert_eq!(v.as_deref(), Some("t"));
+                    // Consumed.
+                    con.getdel("token")
+                })
+                .and_then(|(con, v): (_, Option<String>)| {
+                    assert!(v.is_none());
+                    con.getex("token", GetExOptions::Px(100))
+                })
+                .map(|(_, v): (_, Option<String>)| assert!(v.is_none()))
+        })
+    });
+}
+
+#[test]
+fn copy() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.set_multiple(&[("src", "1"), ("dst", "2")])
+                .and_then(|(con, ()): (_, ())| con.copy("src", "dst"))
+                .and_then(|(con, copied): (_, bool)| {
+                    // The destination exists.
+                    assert!(!copied);
+                    con.copy_options("src", "dst", CopyOptions::default().replace())
+                })
+                .and_then(|(con, copied): (_, bool)| {
+                    assert!(copied);
+                    con.get("dst")
+                })
+                .and_then(|(con, v): (_, String)| {
+                    assert_eq!(v, "1");
+                    con.copy_options("src", "src", CopyOptions::default().db(1))
+                })
+                .and_then(|(con, copied): (_, bool)| {
+                    assert!(copied);
+                    redis::cmd("SELECT").arg(1).query_async(con)
+                })
+                .and_then(|(con, ()): (_, ())| con.getdel("src"))
+                .map(|(_, v): (_, Option<String>)| assert_eq!(v.as_deref(), Some("1")))
+        })
+    });
+}
+
+#[test]
+fn sort_options_args() {
+    let opts = SortOptions::default()
+        .store("dst")
+        .alpha()
+        .desc()
+        .get("#")
+        .get("name:*")
+        .limit(0, 10)
+        .by("weight:*");
+
+    assert_eq!(
+        args(&opts),
+        vec![
+            "BY", "weight:*", "LIMIT", "0", "10", "GET", "#", "GET", "name:*", "DESC", "ALPHA",
+            "STORE", "dst"
+        ]
+    );
+}
+
+#[test]
+fn sort() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.rpush("ids", &[2, 3, 1])
+                .and_then(|(con, _): (_, usize)| {
+                    con.set_multiple(&[
+                        ("weight:1", "30"),
+                        ("weight:2", "10"),
+                        ("weight:3", "20"),
+                        ("name:1", "a"),
+                        ("name:2", "b"),
+                        ("name:3", "c"),
+                    ])
+                })
+                .and_then(|(con, ()): (_, ())| con.sort("ids", SortOptions::default().desc()))
+                .and_then(|(con, ids): (_, Vec<u32>)| {
+                    assert_eq!(ids, vec![3, 2, 1]);
+                    let opts = SortOptions::default()
+                        .by("weight:*")
+                        .get("#")
+                        .get("name:*")
+                        .limit(0, 2);
+                    con.sort_ro("ids", opts)
+                })
+                .and_then(|(con, items): (_, Vec<String>)| {
+                    assert_eq!(items, vec!["2", "b", "3", "c"]);
+                    let opts = SortOptions::default().get("name:*").alpha().store("names");
+                    con.sort("ids", opts)
+                })
+                .and_then(|(con, n): (_, usize)| {
+                    assert_eq!(n, 3);
+                    con.lrange("names", 0, -1)
+                })
+                .map(|(_, names): (_, Vec<String>)| assert_eq!(names, vec!["a", "b", "c"]))
+        })
+    });
+}
+
+#[test]
+fn object_introspection() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.hset("small", "f", "v")
+                .and_then(|(con, _): (_, usize)| con.object_encoding("small"))
+                .and_then(|(con, enc): (_, String)| {
+                    // `ziplist` before Redis 7.
+                    assert!(enc == "listpack" || enc == "ziplist", "{}", enc);
+                    con.object_refcount("small")
+                })
+                .and_then(|(con, refs): (_, usize)| {
+                    assert_eq!(refs, 1);
+                    con.object_idletime("small")
+                })
+                .and_then(|(con, idle): (_, u64)| {
+                    assert!(idle < 10);
+                    con.object_encoding("missing")
+                })
+                .map(|(_, enc): (_, Option<String>)| assert!(enc.is_none()))
+        })
+    });
+}
+
+#[test]
+fn keyspace_utilities() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.randomkey()
+                .and_then(|(con, key): (_, Option<String>)| {
+                    assert!(key.is_none());
+                    con.set_multiple(&[("a", "1"), ("b", "2")])
+                })
+                .and_then(|(con, ()): (_, ())| con.randomkey())
+                .and_then(|(con, key): (_, Option<String>)| {
+                    let key = key.unwrap();
+                    assert!(key == "a" || key == "b");
+                    con.touch(&["a", "b", "c"])
+                })
+                .and_then(|(con, n): (_, usize)| {
+                    assert_eq!(n, 2);
+                    con.move_key("a", 1)
+                })
+                .and_then(|(con, moved): (_, bool)| {
+                    assert!(moved);
+                    con.unlink(&["a", "b"])
+                })
+                .map(|(_, n): (_, usize)| {
+                    // `a` is in the other database.
+                    assert_eq!(n, 1);
+                })
+        })
+    });
+}
+
+#[test]
+fn dump_restore() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.rpush("src", &[&b"\x00\xff"[..], &b"value"[..]])
+                .and_then(|(con, _): (_, usize)| con.dump("src"))
+                .and_then(|(con, dumped): (_, Option<Vec<u8>>)| {
+                    con.restore("dst", 0, &dumped.unwrap(), RestoreOptions::default())
+                })
+                .and_then(|(con, ()): (_, ())| con.lrange("dst", 0, -1))
+                .and_then(|(con, values): (_, Vec<Vec<u8>>)| {
+                    assert_eq!(values, vec![b"\x00\xff".to_vec(), b"value".to_vec()]);
+                    con.ttl("dst")
+                })
+                .and_then(|(con, ttl): (_, i64)| {
+                    assert_eq!(ttl, -1);
+                    con.dump("missing")
+                })
+                .map(|(_, dumped): (_, Option<Vec<u8>>)| assert!(dumped.is_none()))
+        })
+    });
+}
+
+#[test]
+fn restore_replace() {
+    test(|c| {
+        let c2 = c.clone();
+
+        c.get_async_connection().and_then(|con| {
+            con.set_multiple(&[("src", "1"), ("dst", "2")])
+                .and_then(|(con, ()): (_, ())| con.dump("src"))
+                .and_then(|(con, dumped): (_, Vec<u8>)| {
+                    con.restore("dst", 0, &dumped, RestoreOptions::default())
+                        .then(move |res: redis::RedisResult<(_, ())>| {
+                            // The key exists.
+                            assert!(res.is_err());
+                            Ok(dumped)
+                        })
+                })
+                .and_then(move |dumped| {
+                    // The failed command drops the connection.
+                    c2.get_async_connection().and_then(move |con| {
+                        let opts = RestoreOptions::default().replace().idletime(100);
+                        con.restore("dst", 10000, &dumped, opts)
+                    })
+                })
+                .and_then(|(con, ()): (_, ())| con.get("dst"))
+                .and_then(|(con, v): (_, String)| {
+                    assert_eq!(v, "1");
+                    con.ttl("dst")
+                })
+                .map(|(_, ttl): (_, i64)| assert!(ttl > 0 && ttl <= 10))
+        })
+    });
+}
+
+/// The key and the pairs of a member and its score.
+type ZPopped = Option<(String, Vec<Vec<String>>)>;
+
+#[test]
+fn lmpop_zmpop() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.rpush("q2", &["a", "b", "c"])
+                .and_then(|(con, _): (_, usize)| con.lmpop(&["q1", "q2"], Direction::Right, 2))
+                .and_then(|(con, popped): (_, Option<(String, Vec<String>)>)| {
+                    assert_eq!(popped, Some(("q2".into(), vec!["c".into(), "b".into()])));
+                    con.lmpop(&["q1"], Direction::Left, 1)
+                })
+                .and_then(|(con, popped): (_, Option<(String, Vec<String>)>)| {
+                    assert!(popped.is_none());
+                    con.zadd_multiple("z", &[(1, "x"), (2, "y")])
+                })
+                .and_then(|(con, _): (_, usize)| con.zmpop(&["z"], MinMax::Max, 1))
+                .and_then(|(con, popped): (_, ZPopped)| {
+                    let (key, members) = popped.unwrap();
+                    assert_eq!(key, "z");
+                    assert_eq!(members, vec![vec!["y".to_string(), "2".into()]]);
+                    con.zmpop(&["empty"], MinMax::Min, 1)
+                })
+                .map(|(_, popped): (_, ZPopped)| assert!(popped.is_none()))
+        })
+    });
+}
+
+#[test]
+fn blmpop_bzmpop() {
+    test(|c| {
+        let c2 = c.clone();
+
+        let push = c.get_async_connection().and_then(|con| {
+            Delay::new(Instant::now() + Duration::from_millis(500))
+                .map_err(|e| panic!("{}", e))
+                .and_then(move |_| con.rpush("q2", "a"))
+                .map(|(_, _): (_, usize)| ())
+        });
+
+        let pop = c
+            .get_async_connection()
+            .and_then(|con| con.blmpop(5, &["q1", "q2"], Direction::Left, 10))
+            .map(|(_, popped): (_, Option<(String, Vec<String>)>)| {
+                assert_eq!(popped, Some(("q2".into(), vec!["a".into()])));
+            });
+
+        pop.join(push)
+            .and_then(move |_| c2.get_async_connection())
+            .and_then(|con| con.bzmpop(1, &["z"], MinMax::Min, 1))
+            .map(|(_, popped): (_, ZPopped)| {
+                // Timed out.
+                assert!(popped.is_none());
+            })
+    });
+}
+
+#[test]
+fn smismember() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.sadd("roles", &["admin", "editor"])
+                .and_then(|(con, _): (_, usize)| {
+                    con.smismember("roles", &["viewer", "admin", "editor"])
+                })
+                .and_then(|(con, found): (_, Vec<bool>)| {
+                    assert_eq!(found, vec![false, true, true]);
+                    con.smismember("missing", "admin")
+                })
+                .map(|(_, found): (_, Vec<bool>)| assert_eq!(found, vec![false]))
+        })
+    });
+}
+
+#[test]
+fn zadd_options() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.zadd("board", "alice", 10)
+                .and_then(|(con, _): (_, usize)| {
+                    con.zadd_options("board", "alice", 5, ZaddOptions::default().gt().ch())
+                })
+                .and_then(|(con, changed): (_, usize)| {
+                    // Never lowered.
+                    assert_eq!(changed, 0);
+                    con.zadd_options("board", "alice", 20, ZaddOptions::default().gt().ch())
+                })
+                .and_then(|(con, changed): (_, usize)| {
+                    assert_eq!(changed, 1);
+                    con.zadd_options("board", "alice", 2, ZaddOptions::default().incr())
+                })
+                .and_then(|(con, score): (_, Option<f64>)| {
+                    assert_eq!(score, Some(22.0));
+                    con.zadd_options("board", "bob", 1, ZaddOptions::default().xx().incr())
+                })
+                .and_then(|(con, score): (_, Option<f64>)| {
+                    assert!(score.is_none());
+                    con.zadd_options("board", "bob", 1, ZaddOptions::default().nx())
+                })
+                .map(|(_, added): (_, usize)| assert_eq!(added, 1))
+        })
+    });
+}
+
+#[test]
+fn zrange_generic() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.zadd_multiple("z", &[(1, "a"), (2, "b"), (3, "c"), (4, "d")])
+                .and_then(|(con, _): (_, usize)| {
+                    let opts = ZRangeOptions::default().by_score().rev().limit(0, 2);
+                    con.zrange_generic("z", "(4", "-inf", opts)
+                })
+                .and_then(|(con, members): (_, Vec<String>)| {
+                    assert_eq!(members, vec!["c", "b"]);
+                    let opts = ZRangeOptions::default().with_scores();
+                    con.zrange_generic("z", 0, 0, opts)
+                })
+                .and_then(|(con, members): (_, Vec<(String, f64)>)| {
+                    assert_eq!(members, vec![("a".to_string(), 1.0)]);
+                    con.zadd_multiple("lex", &[(0, "a"), (0, "b"), (0, "c")])
+                })
+                .and_then(|(con, _): (_, usize)| {
+                    con.zrange_generic("lex", "(a", "+", ZRangeOptions::default().by_lex())
+                })
+                .map(|(_, members): (_, Vec<String>)| assert_eq!(members, vec!["b", "c"]))
+        })
+    });
+}
+
+#[test]
+fn bitpos_args() {
+    assert!(args(&BitPosOptions::default()).is_empty());
+    assert_eq!(args(&BitPosOptions::default().start(2)), vec!["2"]);
+    assert_eq!(args(&BitPosOptions::default().end(3)), vec!["0", "3"]);
+    assert_eq!(
+        args(&BitPosOptions::default().start(8).bit()),
+        vec!["8", "-1", "BIT"]
+    );
+}
+
+#[test]
+fn bitpos_bitfield() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.set("bits", b"\x00\x0f")
+                .and_then(|(con, ()): (_, ())| con.bitpos("bits", true, BitPosOptions::default()))
+                .and_then(|(con, pos): (_, isize)| {
+                    assert_eq!(pos, 12);
+                    con.bitpos("bits", true, BitPosOptions::default().end(0))
+                })
+                .and_then(|(con, pos): (_, isize)| {
+                    assert_eq!(pos, -1);
+                    let ops = BitFieldOps::default()
+                        .overflow(BitFieldOverflow::Sat)
+                        .set("u8", "#0", 250)
+                        .incrby("u8", "#0", 10)
+                        .incrby("i8", "#1", -3)
+                        .get("u8", 0);
+                    con.bitfield("counters", ops)
+                })
+                .and_then(|(con, values): (_, Vec<i64>)| {
+                    assert_eq!(values, vec![0, 255, -3, 255]);
+                    let ops = BitFieldOps::default()
+                        .overflow(BitFieldOverflow::Fail)
+                        .incrby("u8", "#0", 1);
+                    con.bitfield("counters", ops)
+                })
+                .map(|(_, values): (_, Vec<Option<i64>>)| assert_eq!(values, vec![None]))
+        })
+    });
+}
+
+#[test]
+fn ttl_duration() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.ttl_duration("key")
+                .and_then(|(con, ttl)| {
+                    // No key.
+                    assert!(ttl.is_none());
+                    con.set("key", "v")
+                })
+                .and_then(|(con, ()): (_, ())| con.pttl_duration("key"))
+                .and_then(|(con, ttl)| {
+                    // No timeout.
+                    assert!(ttl.is_none());
+                    con.pexpire("key", 10_000)
+                })
+                .and_then(|(con, _): (_, bool)| con.pttl_duration("key"))
+                .and_then(|(con, ttl)| {
+                    let ttl = ttl.unwrap();
+                    assert!(ttl > Duration::from_secs(9) && ttl <= Duration::from_secs(10));
+                    con.ttl_duration("key")
+                })
+                .map(|(_, ttl)| {
+                    let ttl = ttl.unwrap();
+                    assert!(ttl > Duration::from_secs(8) && ttl <= Duration::from_secs(10));
+                })
+        })
+    });
+}
+
+#[test]
+fn pttl() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.pttl("lock")
+                .and_then(|(con, ttl): (_, i64)| {
+                    assert_eq!(ttl, -2);
+                    con.set("lock", "1")
+                })
+                .and_then(|(con, ()): (_, ())| con.pttl("lock"))
+                .and_then(|(con, ttl): (_, i64)| {
+                    assert_eq!(ttl, -1);
+                    con.pexpire("lock", 1500)
+                })
+                .and_then(|(con, _): (_, bool)| con.pttl("lock"))
+                .map(|(_, ttl): (_, i64)| assert!(ttl > 1000 && ttl <= 1500))
+        })
+    });
+}
+
+#[test]
+fn scan_key_types() {
+    test(|c| {
+        c.get_async_connection()
+            .and_then(|con| {
+                con.set("key:string", "v")
+                    .and_then(|(con, ()): (_, ())| con.rpush("key:list", "a"))
+                    .and_then(|(con, _): (_, usize)| con.sadd("key:set", "a"))
+                    .and_then(|(con, _): (_, usize)| con.set("garbage", "v"))
+            })
+            .and_then(|(con, ()): (_, ())| {
+                con.scan_key_types("key:*").filter_map(|(_, v)| v).collect()
+            })
+            .map(|mut res: Vec<(String, RedisKeyType)>| {
+                res.sort_by(|a, b| a.0.cmp(&b.0));
+                assert_eq!(
+                    res,
+                    vec![
+                        ("key:list".to_string(), RedisKeyType::List),
+                        ("key:set".into(), RedisKeyType::Set),
+                        ("key:string".into(), RedisKeyType::String),
+                    ]
+                );
+            })
+    })
+}
+
+#[test]
+fn scan_items_shared() {
+    test(|c| {
+        let exp = write_values("key");
+        let exp_keys = keys(exp.clone());
+
+        c.get_shared_async_connection().and_then(move |con| {
+            let other = con.clone();
+
+            con.clone()
+                .scan_match("key*")
+                .items()
+                // Other commands can be sent while scanning.
+                .and_then(move |key: String| {
+                    other
+                        .clone()
+                        .get(key.clone())
+                        .map(|(_, v): (_, String)| (key, v))
+                })
+                .collect()
+                .and_then(move |mut items: Vec<(String, String)>| {
+                    items.sort();
+                    items.dedup();
+                    assert_eq!(items, exp);
+
+                    stream_shared(&con, |cur| {
+                        let mut c = redis::cmd("SCAN");
+                        c.arg(cur).arg("MATCH").arg("key*");
+                        c
+                    })
+                    .collect()
+                })
+                .map(move |mut res: Vec<String>| {
+                    res.sort();
+                    res.dedup();
+                    assert_eq!(res, exp_keys);
+                })
+        })
+    });
+}
+
+#[test]
+fn hscan_keys_values_only() {
+    test(|c| {
+        let exp = write_hash_values("hash", "key");
+        let exp_keys = keys(exp.clone());
+        let c2 = c.clone();
+
+        c.get_async_connection()
+            .and_then(|con| con.hscan::<_, (String, String)>("hash").all().keys_only())
+            .and_then(move |(_, mut fields): (_, Vec<String>)| {
+                fields.sort();
+                fields.dedup();
+                assert_eq!(fields, exp_keys);
+                c2.get_async_connection()
+            })
+            .and_then(|con| con.hscan::<_, (String, String)>("hash").all().values_only())
+            .map(move |(_, values): (_, Vec<String>)| {
+                assert!(values.len() >= exp.len());
+                assert!(exp.iter().all(|(_, v)| values.contains(v)));
+            })
+    })
+}
+
+#[test]
+fn scan_pairs_framing() {
+    // An odd number of elements can't be framed into pairs.
+    let odd = redis::Value::Bulk(vec![
+        redis::Value::Data(b"f1".to_vec()),
+        redis::Value::Data(b"v1".to_vec()),
+        redis::Value::Data(b"f2".to_vec()),
+    ]);
+    assert!(redis::from_redis_value::<Vec<(String, String)>>(&odd).is_err());
+}
+
+#[test]
+fn scan_pairs_small_count() {
+    test(|c| {
+        let hash = write_hash_values("hash", "key");
+        let zset = write_zset_values("zset", "key");
+        let c2 = c.clone();
+
+        c.get_async_connection()
+            .and_then(|con| {
+                // Many batches of a few pairs each.
+                con.hscan_count("hash", 3).filter_map(|(_, v)| v).collect()
+            })
+            .and_then(move |mut res: Vec<(String, String)>| {
+                res.sort();
+                res.dedup();
+                assert_eq!(res, hash);
+                c2.get_async_connection()
+            })
+            .and_then(|con| con.zscan_count("zset", 3).filter_map(|(_, v)| v).collect())
+            .map(move |mut res: Vec<(String, String)>| {
+                res.sort();
+                res.dedup();
+                assert_eq!(res, zset);
+            })
+    })
+}
+
+#[test]
+fn scan_items_with_connection() {
+    test(|c| {
+        let exp = write_values("key");
+
+        c.get_async_connection().and_then(move |con| {
+            let (items, con) = con.scan_match("key*").items_with_connection();
+            items
+                .collect()
+                .and_then(move |mut res: Vec<String>| {
+                    res.sort();
+                    res.dedup();
+                    assert_eq!(res, keys(exp));
+                    con
+                })
+                .and_then(|con| {
+                    // No items.
+                    let (items, con) = con.scan_match("missing*").items_with_connection();
+                    items.collect().join(con)
+                })
+                .and_then(|(res, con): (Vec<String>, _)| {
+                    assert!(res.is_empty());
+                    redis::cmd("PING").query_async(con)
+                })
+                .map(|(_, pong): (_, String)| assert_eq!(pong, "PONG"))
+        })
+    })
+}
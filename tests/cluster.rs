@@ -0,0 +1,56 @@
+use futures::prelude::*;
+use redis_ac::{ClusterNode, Commands};
+
+mod helper;
+
+use crate::helper::*;
+
+#[test]
+fn cluster_node_parse() {
+    let node: ClusterNode = "07c37dfeb235213a872192d90877d0cd55635b91 127.0.0.1:30004@31004,host replica,myself e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca 0 1426238317239 4 connected"
+        .parse()
+        .unwrap();
+    assert_eq!(node.addr, "127.0.0.1:30004");
+    assert_eq!(node.flags, vec!["replica", "myself"]);
+    assert_eq!(
+        node.master.as_deref(),
+        Some("e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca")
+    );
+    assert!(!node.is_master());
+    assert!(node.slots.is_empty());
+
+    let node: ClusterNode = "e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca 127.0.0.1:30001@31001 master - 0 1426238316232 1 connected 0-5460 5462 [5461->-292f8b365bb7edb5e285caf0b7e6ddc7265d2f4f]"
+        .parse()
+        .unwrap();
+    assert!(node.is_master());
+    assert!(node.connected);
+    assert_eq!(node.master, None);
+    assert_eq!(node.slots, vec![(0, 5460), (5462, 5462)]);
+
+    assert!("garbage".parse::<ClusterNode>().is_err());
+}
+
+/// Runs against a cluster node given by `CLUSTER_URL`, or skips the test.
+#[test]
+fn cluster_nodes() {
+    let url = match std::env::var("CLUSTER_URL") {
+        Ok(url) => url,
+        Err(_) => return,
+    };
+    let c = redis::Client::open(url.as_ref()).unwrap();
+
+    run(c.get_async_connection().and_then(|con| {
+        con.cluster_info()
+            .and_then(|(con, info)| {
+                assert_eq!(info.get("cluster_state"), Some("ok".to_string()));
+                con.cluster_nodes()
+            })
+            .and_then(|(con, nodes)| {
+                assert!(nodes.iter().any(|n| n.is_master() && !n.slots.is_empty()));
+                con.cluster_slots()
+            })
+            .map(|(_, slots): (_, Vec<redis::Value>)| {
+                assert!(!slots.is_empty());
+            })
+    }));
+}
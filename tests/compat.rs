@@ -0,0 +1,75 @@
+#![cfg(feature = "compat")]
+
+use futures03::{compat::Future01CompatExt, FutureExt, TryFutureExt};
+use redis::ErrorKind;
+use redis_ac::{AsyncConnection, Commands};
+
+mod helper;
+
+use crate::helper::*;
+
+#[test]
+fn async_connection() {
+    test(|c| {
+        async move {
+            let mut con = AsyncConnection::new(c.get_async_connection().compat().await?);
+
+            let () = con.run(|c| c.set("a", "1")).await?;
+            let n: i64 = con.run(|c| c.incr("a", 2)).await?;
+            assert_eq!(n, 3);
+
+            let v: String = con.query(redis::cmd("GET").arg("a")).await?;
+            assert_eq!(v, "3");
+
+            // Not an integer.
+            let () = con.run(|c| c.set("b", "x")).await?;
+            let err = con.run(|c| c.incr::<_, _, i64>("b", 1)).await.unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::ResponseError);
+            assert!(con.is_lost());
+
+            let err = con.run(|c| c.get::<_, String>("a")).await.unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::IoError);
+
+            Ok::<_, redis::RedisError>(())
+        }
+        .boxed()
+        .compat()
+    });
+}
+
+#[test]
+fn scan_stream_03() {
+    use futures03::{StreamExt, TryStreamExt};
+
+    test(|c| {
+        async move {
+            let con = c.get_async_connection().compat().await?;
+            let (con, ()) = con
+                .set_multiple(&[("key:1", "a"), ("key:2", "b"), ("other", "c")])
+                .compat()
+                .await?;
+
+            let mut keys: Vec<String> = con
+                .scan_match("key:*")
+                .try_filter_map(|(_, key)| futures03::future::ok(key))
+                .try_collect()
+                .await?;
+            keys.sort();
+            assert_eq!(keys, vec!["key:1", "key:2"]);
+
+            let con = c.get_async_connection().compat().await?;
+            let (_, mut all): (_, Vec<String>) = con.scan_match("*").all().await?;
+            all.sort();
+            assert_eq!(all, vec!["key:1", "key:2", "other"]);
+
+            // The connection is returned with the last item.
+            let con = c.get_async_connection().compat().await?;
+            let items: Vec<_> = con.scan_match::<_, String>("key:*").collect().await;
+            assert!(items.last().unwrap().as_ref().unwrap().0.is_some());
+
+            Ok::<_, redis::RedisError>(())
+        }
+        .boxed()
+        .compat()
+    });
+}
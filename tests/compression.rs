@@ -0,0 +1,71 @@
+#![cfg(feature = "compression")]
+
+use futures::prelude::*;
+use redis::ErrorKind;
+use redis_ac::{Codec, Commands, Compression};
+
+mod helper;
+
+use crate::helper::*;
+
+fn round_trip(codec: Codec) {
+    test(move |c| {
+        let compression = Compression::default().codec(codec);
+        let value: Vec<u8> = b"compressible "
+            .iter()
+            .cycle()
+            .take(100_000)
+            .cloned()
+            .collect();
+        let exp = value.clone();
+
+        c.get_async_connection().and_then(move |con| {
+            con.set_compressed("big", &value, &compression)
+                .and_then(|(con, ())| con.strlen("big"))
+                .and_then(move |(con, len): (_, usize)| {
+                    assert!(len < 10_000, "stored {} bytes", len);
+                    con.get_compressed("big", &compression)
+                })
+                .and_then(move |(con, v)| {
+                    assert_eq!(v, Some(exp));
+                    con.get_compressed("missing", &compression)
+                })
+                .and_then(|(con, v)| {
+                    assert_eq!(v, None);
+                    con.set("plain", "not compressed")
+                })
+                .and_then(move |(con, ()): (_, ())| con.get_compressed("plain", &compression))
+                .then(|res| {
+                    assert_eq!(res.err().unwrap().kind(), ErrorKind::TypeError);
+                    Ok::<_, redis::RedisError>(())
+                })
+        })
+    });
+}
+
+#[test]
+fn compressed_gzip() {
+    round_trip(Codec::Gzip);
+}
+
+#[test]
+fn compressed_zstd() {
+    round_trip(Codec::Zstd);
+}
+
+#[test]
+fn compressed_codec_mismatch() {
+    test(|c| {
+        let gzip = Compression::default();
+        let zstd = Compression::default().codec(Codec::Zstd);
+
+        c.get_async_connection().and_then(move |con| {
+            con.set_compressed("gz", b"value", &gzip)
+                .and_then(move |(con, ())| con.get_compressed("gz", &zstd))
+                .then(|res| {
+                    assert_eq!(res.err().unwrap().kind(), ErrorKind::TypeError);
+                    Ok::<_, redis::RedisError>(())
+                })
+        })
+    });
+}
@@ -0,0 +1,179 @@
+use futures::prelude::*;
+use redis::{aio::ConnectionLike, ErrorKind, RedisError, RedisFuture, Value};
+use redis_ac::{Commands, CorrelatedConnection};
+use std::io;
+
+mod helper;
+
+use crate::helper::*;
+
+#[test]
+fn correlated_error() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            CorrelatedConnection::new(con, "request-1")
+                .set("key", "not a number")
+                .and_then(|(con, ()): (_, ())| {
+                    assert_eq!(con.id(), "request-1");
+                    con.with_id("request-2").incr("key", 1)
+                })
+                .then(|res: Result<(_, i64), _>| {
+                    match res {
+                        Ok(_) => panic!("incr of a string succeeded"),
+                        // The error of the server as it is.
+                        Err(e) => assert_eq!(e.kind(), ErrorKind::ResponseError),
+                    }
+                    Ok::<_, RedisError>(())
+                })
+        })
+    });
+}
+
+/// Connection which always fails with a dropped connection when polled.
+struct Dropped;
+
+fn dropped<T: Send + 'static>() -> RedisFuture<(Dropped, T)> {
+    Box::new(futures::future::poll_fn(|| {
+        #[cfg(feature = "tracing")]
+        tracing::info!("polled");
+        Err(io::Error::from(io::ErrorKind::BrokenPipe).into())
+    }))
+}
+
+impl ConnectionLike for Dropped {
+    fn req_packed_command(self, _: Vec<u8>) -> RedisFuture<(Self, Value)> {
+        dropped()
+    }
+
+    fn req_packed_commands(
+        self,
+        _: Vec<u8>,
+        _: usize,
+        _: usize,
+    ) -> RedisFuture<(Self, Vec<Value>)> {
+        dropped()
+    }
+
+    fn get_db(&self) -> i64 {
+        0
+    }
+}
+
+#[test]
+fn correlated_io_error() {
+    let res: Result<(_, ()), _> = CorrelatedConnection::new(Dropped, "request-1")
+        .set("key", 1)
+        .wait();
+    match res {
+        Ok(_) => panic!("set succeeded"),
+        Err(e) => assert!(e.is_connection_dropped()),
+    }
+}
+
+#[cfg(feature = "tracing")]
+mod tracing_span {
+    use super::*;
+    use std::{
+        collections::HashMap,
+        fmt,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc, Mutex,
+        },
+    };
+    use tracing::{
+        field::{Field, Visit},
+        span, Event, Metadata, Subscriber,
+    };
+
+    /// Messages of events with the correlation id of the span they're emitted in.
+    type Events = Arc<Mutex<Vec<(String, Option<String>)>>>;
+
+    /// Records the correlation id of the span in which each event is emitted.
+    #[derive(Default)]
+    struct Recorder {
+        next: AtomicU64,
+        ids: Mutex<HashMap<u64, String>>,
+        stack: Mutex<Vec<u64>>,
+        events: Events,
+    }
+
+    #[derive(Default)]
+    struct Fields(HashMap<String, String>);
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.0
+                .insert(field.name().into(), format!("{:?}", value).replace('"', ""));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().into(), value.into());
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+            let id = self.next.fetch_add(1, Ordering::SeqCst) + 1;
+            let mut fields = Fields::default();
+            span.record(&mut fields);
+            if let Some(cid) = fields.0.remove("correlation_id") {
+                self.ids.lock().unwrap().insert(id, cid);
+            }
+            span::Id::from_u64(id)
+        }
+
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = Fields::default();
+            event.record(&mut fields);
+            let message = fields.0.remove("message").unwrap_or_default();
+            let cid = self
+                .stack
+                .lock()
+                .unwrap()
+                .last()
+                .and_then(|id| self.ids.lock().unwrap().get(id).cloned());
+            self.events.lock().unwrap().push((message, cid));
+        }
+
+        fn enter(&self, span: &span::Id) {
+            self.stack.lock().unwrap().push(span.into_u64());
+        }
+
+        fn exit(&self, _: &span::Id) {
+            self.stack.lock().unwrap().pop();
+        }
+    }
+
+    #[test]
+    fn correlated_span() {
+        let recorder = Recorder::default();
+        let events = recorder.events.clone();
+
+        let res: Result<(_, ()), _> = tracing::subscriber::with_default(recorder, || {
+            let con = CorrelatedConnection::new(Dropped, "request-1");
+            // Nothing is polled until waited.
+            let f = con.set("key", 1);
+            tracing::info!("built");
+            f.wait()
+        });
+        assert!(res.is_err());
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                ("built".into(), None),
+                ("polled".into(), Some("request-1".into())),
+                ("command failed".into(), Some("request-1".into())),
+            ]
+        );
+    }
+}
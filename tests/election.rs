@@ -0,0 +1,89 @@
+use futures::prelude::*;
+use redis_ac::{Commands, Election};
+use std::time::{Duration, Instant};
+use tokio::timer::Delay;
+
+mod helper;
+
+use crate::helper::*;
+
+fn delay(ms: u64) -> impl Future<Item = (), Error = redis::RedisError> {
+    Delay::new(Instant::now() + Duration::from_millis(ms)).map_err(|e| panic!("{}", e))
+}
+
+#[test]
+fn election_takeover() {
+    test(|c| {
+        let mut sync = c.get_connection().unwrap();
+        let _: () = redis::cmd("CONFIG")
+            .arg("SET")
+            .arg("notify-keyspace-events")
+            .arg("Kgx")
+            .query(&mut sync)
+            .unwrap();
+
+        let a = Election::new("leader", "a", Duration::from_millis(500));
+        let b = Election::new("leader", "b", Duration::from_millis(500));
+        let c2 = c.clone();
+
+        c.get_shared_async_connection()
+            .and_then(move |con| {
+                a.campaign(con.clone())
+                    .map(move |(handle, leadership)| (con, handle, leadership))
+            })
+            .and_then(|(con, handle, leadership)| {
+                // Stops renewing, so the leadership expires.
+                drop(leadership);
+                con.get("leader")
+                    .map(move |(con, leader): (_, String)| (con, handle, leader))
+            })
+            .and_then(move |(con, handle, leader)| {
+                assert_eq!(leader, "a");
+                let started = Instant::now();
+                c2.get_async_connection()
+                    .and_then(move |sub| b.observe(sub).map(move |_| b))
+                    .and_then(move |b| {
+                        assert!(started.elapsed() < Duration::from_secs(2));
+                        b.campaign(con.clone())
+                            .map(move |(_, leadership)| (con, handle, leadership))
+                    })
+            })
+            .and_then(|(con, _, _)| con.get("leader"))
+            .map(|(_, leader): (_, String)| assert_eq!(leader, "b"))
+    });
+}
+
+#[test]
+fn election_renew_release() {
+    test(|c| {
+        let a = Election::new("leader", "a", Duration::from_millis(300));
+        let b = Election::new("leader", "b", Duration::from_millis(300));
+        let c2 = c.clone();
+
+        c.get_shared_async_connection().and_then(move |con| {
+            a.campaign(con.clone())
+                .and_then(move |(handle, leadership)| {
+                    // Renewed several times meanwhile.
+                    let step_down = delay(1000).and_then(move |_| con.get("leader")).map(
+                        move |(con, leader): (_, String)| {
+                            assert_eq!(leader, "a");
+                            drop(handle);
+                            con
+                        },
+                    );
+
+                    let observe = c2
+                        .get_async_connection()
+                        .and_then(move |sub| b.observe(sub));
+
+                    leadership
+                        .join3(step_down, observe)
+                        .and_then(|(released, con, _)| {
+                            assert!(released);
+                            con.exists("leader")
+                        })
+                        .map(|(_, exists): (_, bool)| assert!(!exists))
+                })
+        })
+    });
+}
@@ -0,0 +1,177 @@
+#![cfg(feature = "geospatial")]
+
+use futures::prelude::*;
+use redis::{
+    geo::{Coord, RadiusOrder, Unit},
+    ToRedisArgs,
+};
+use redis_ac::{Commands, GeoSearchOptions};
+
+mod helper;
+
+use crate::helper::*;
+
+fn near(a: f64, b: f64) -> bool {
+    (a - b).abs() < 0.0001
+}
+
+#[test]
+fn geo_pos_typed() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.geo_add(
+                "gis",
+                &[
+                    (Coord::lon_lat(13.361389, 38.115556), "Palermo"),
+                    (Coord::lon_lat(15.087269, 37.502669), "Catania"),
+                ],
+            )
+            .and_then(|(con, n): (_, usize)| {
+                assert_eq!(n, 2);
+                con.geo_pos_typed("gis", &["Palermo", "Atlantis", "Catania"])
+            })
+            .map(|(_, pos)| {
+                assert_eq!(pos.len(), 3);
+
+                let palermo = pos[0].as_ref().unwrap();
+                assert!(near(palermo.longitude, 13.361389));
+                assert!(near(palermo.latitude, 38.115556));
+
+                assert!(pos[1].is_none());
+
+                let catania = pos[2].as_ref().unwrap();
+                assert!(near(catania.longitude, 15.087269));
+                assert!(near(catania.latitude, 37.502669));
+            })
+        })
+    });
+}
+
+fn args(opts: &GeoSearchOptions) -> Vec<String> {
+    opts.to_redis_args()
+        .into_iter()
+        .map(|a| String::from_utf8(a).unwrap())
+        .collect()
+}
+
+#[test]
+fn geo_search_count_args() {
+    let opts = || {
+        GeoSearchOptions::default()
+            .from_member("Palermo")
+            .by_radius(200.0, Unit::Kilometers)
+            .order(RadiusOrder::Asc)
+    };
+
+    assert_eq!(
+        args(&opts().count(1)),
+        vec![
+            "FROMMEMBER",
+            "Palermo",
+            "BYRADIUS",
+            "200",
+            "km",
+            "ASC",
+            "COUNT",
+            "1"
+        ]
+    );
+    assert_eq!(
+        args(&opts().count_any(1)),
+        vec![
+            "FROMMEMBER",
+            "Palermo",
+            "BYRADIUS",
+            "200",
+            "km",
+            "ASC",
+            "COUNT",
+            "1",
+            "ANY"
+        ]
+    );
+    assert!(!args(&opts()).contains(&"ANY".to_string()));
+}
+
+#[test]
+fn geo_search_validate() {
+    let opts = || {
+        GeoSearchOptions::default()
+            .from_lonlat(15.0, 37.0)
+            .by_box(400.0, 400.0, Unit::Kilometers)
+    };
+
+    assert!(opts().validate().is_ok());
+    assert!(opts().count_any(2).validate().is_ok());
+    assert!(opts().count_any(0).validate().is_err());
+    assert!(opts().count(0).validate().is_err());
+    assert!(GeoSearchOptions::default()
+        .by_radius(1.0, Unit::Meters)
+        .validate()
+        .is_err());
+    assert!(GeoSearchOptions::default()
+        .from_member("Palermo")
+        .validate()
+        .is_err());
+}
+
+#[test]
+fn geo_search() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.geo_add(
+                "gis",
+                &[
+                    (Coord::lon_lat(13.361389, 38.115556), "Palermo"),
+                    (Coord::lon_lat(15.087269, 37.502669), "Catania"),
+                ],
+            )
+            .and_then(|(con, _): (_, usize)| {
+                let opts = GeoSearchOptions::default()
+                    .from_lonlat(15.0, 37.0)
+                    .by_radius(200.0, Unit::Kilometers)
+                    .order(RadiusOrder::Asc)
+                    .count(1);
+                con.geo_search("gis", opts)
+            })
+            .and_then(|(con, res): (_, Vec<String>)| {
+                assert_eq!(res, vec!["Catania"]);
+                let opts = GeoSearchOptions::default()
+                    .from_lonlat(15.0, 37.0)
+                    .by_radius(200.0, Unit::Kilometers)
+                    .count_any(0);
+                con.geo_search::<_, Vec<String>>("gis", opts).then(Ok)
+            })
+            .map(|res| match res {
+                // Rejected without querying.
+                Err(e) => assert_eq!(e.kind(), redis::ErrorKind::InvalidClientConfig),
+                Ok(_) => panic!("invalid options were accepted"),
+            })
+        })
+    });
+}
+
+#[test]
+fn geo_nearest() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.geo_add(
+                "gis",
+                &[
+                    (Coord::lon_lat(13.361389, 38.115556), "Palermo"),
+                    (Coord::lon_lat(15.087269, 37.502669), "Catania"),
+                    (Coord::lon_lat(12.496366, 41.902782), "Rome"),
+                    (Coord::lon_lat(9.189982, 45.464204), "Milan"),
+                ],
+            )
+            .and_then(|(con, _): (_, usize)| con.geo_nearest("gis", "Palermo", 2, Unit::Kilometers))
+            .map(|(_, res)| {
+                let names: Vec<_> = res.iter().map(|(name, _)| name.as_str()).collect();
+                // Ordered by distance, without the origin.
+                assert_eq!(names, vec!["Catania", "Rome"]);
+                assert!(res[0].1 < res[1].1);
+                assert!((res[0].1 - 166.27).abs() < 1.0);
+            })
+        })
+    });
+}
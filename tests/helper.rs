@@ -109,15 +109,12 @@ pub fn flush() {
     })
 }
 
-fn write_kv<K, V, F>(data: Vec<(K, V)>, f: F) -> Vec<(K, V)>
+fn write_kv<K, V, F, R>(data: Vec<(K, V)>, f: F) -> Vec<(K, V)>
 where
     K: redis::ToRedisArgs + Send + Clone + 'static,
     V: redis::ToRedisArgs + Send + Clone + 'static,
-    F: Fn(
-            redis::aio::SharedConnection,
-            K,
-            V,
-        ) -> redis::RedisFuture<(redis::aio::SharedConnection, ())>
+    F: Fn(redis::aio::SharedConnection, K, V) -> R + Send + 'static,
+    R: Future<Item = (redis::aio::SharedConnection, ()), Error = redis::RedisError>
         + Send
         + 'static,
 {
@@ -199,3 +196,56 @@ where
 {
     data.into_iter().map(|(_, v)| v).collect()
 }
+
+/// Disables active expiration until dropped.
+///
+/// While this is alive, expired keys are only removed when they are accessed.
+#[cfg(feature = "debug-commands")]
+pub struct PassiveExpire;
+
+#[cfg(feature = "debug-commands")]
+fn set_active_expire(on: bool) {
+    with_cli(move |c| {
+        c.get_async_connection()
+            .and_then(move |con| con.debug_set_active_expire(on))
+            .map(|(_, ()): (_, ())| ())
+    })
+}
+
+/// Disables active expiration and returns a guard to enable it again.
+#[cfg(feature = "debug-commands")]
+pub fn passive_expire() -> PassiveExpire {
+    set_active_expire(false);
+    PassiveExpire
+}
+
+#[cfg(feature = "debug-commands")]
+impl Drop for PassiveExpire {
+    fn drop(&mut self) {
+        set_active_expire(true);
+    }
+}
+
+/// Sets a value which expires after `ms` milliseconds, and waits until it expires.
+///
+/// With active expiration disabled via [`PassiveExpire`], the key is guaranteed
+/// to stay in the keyspace until it's accessed.
+#[cfg(feature = "debug-commands")]
+pub fn write_expired(key: &str, ms: usize) -> PassiveExpire {
+    let guard = passive_expire();
+
+    let key = key.to_string();
+    with_cli(move |c| {
+        c.get_async_connection()
+            .and_then(move |con| {
+                con.set(key.clone(), "value")
+                    .map(|(con, ()): (_, ())| (con, key))
+            })
+            .and_then(move |(con, key)| con.pexpire(key, ms))
+            .map(|(_, b): (_, bool)| assert!(b))
+    });
+
+    std::thread::sleep(std::time::Duration::from_millis(ms as u64 * 2));
+
+    guard
+}
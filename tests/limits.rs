@@ -0,0 +1,47 @@
+use futures::prelude::*;
+use redis_ac::{ArgLimits, Commands};
+
+mod helper;
+
+use crate::helper::*;
+
+/// Returns the number of calls of the command from `INFO commandstats`.
+fn calls(info: &str, name: &str) -> usize {
+    let prefix = format!("cmdstat_{}:calls=", name);
+    info.lines()
+        .find(|l| l.starts_with(&prefix))
+        .and_then(|l| l[prefix.len()..].split(',').next())
+        .map_or(0, |n| n.parse().unwrap())
+}
+
+#[test]
+fn bulk_split() {
+    test(|c| {
+        // `SADD`, the key and 3 members per command.
+        let limits = ArgLimits::default().max_args(5);
+        let members: Vec<_> = (0..10).map(|i| format!("m{}", i)).collect();
+
+        c.get_async_connection().and_then(move |con| {
+            redis::cmd("CONFIG")
+                .arg("RESETSTAT")
+                .query_async(con)
+                .and_then(move |(con, ()): (_, ())| con.sadd_bulk_with("set", &members, &limits))
+                .and_then(|(con, n)| {
+                    assert_eq!(n, 10);
+                    redis::cmd("INFO").arg("commandstats").query_async(con)
+                })
+                .and_then(|(con, info): (_, String)| {
+                    // Split into 4 commands.
+                    assert_eq!(calls(&info, "sadd"), 4);
+                    con.scard("set")
+                })
+                .and_then(move |(con, n): (_, usize)| {
+                    assert_eq!(n, 10);
+                    con.del_bulk_with(&["set", "missing"], &limits)
+                })
+                .map(|(_, n)| {
+                    assert_eq!(n, 1);
+                })
+        })
+    });
+}
@@ -0,0 +1,145 @@
+use futures::prelude::*;
+use redis_ac::{Commands, Pipeline, TypedPipeline};
+use std::time::Duration;
+
+mod helper;
+
+use crate::helper::*;
+
+#[test]
+fn typed_pipeline() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            TypedPipeline::new()
+                .add_command::<String>(redis::cmd("SET").arg("key").arg(1).clone())
+                .add_command::<i64>(redis::cmd("INCR").arg("key").clone())
+                .add_command::<bool>(redis::cmd("EXISTS").arg("key").clone())
+                .execute(con)
+                .map(|(_, (ok, n, exists))| {
+                    assert_eq!(ok, "OK");
+                    assert_eq!(n, 2);
+                    assert!(exists);
+                })
+        })
+    });
+}
+
+#[test]
+fn typed_pipeline_atomic() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            TypedPipeline::new()
+                .atomic()
+                .add_command::<i64>(redis::cmd("INCR").arg("key").clone())
+                .add_command::<i64>(redis::cmd("INCR").arg("key").clone())
+                .execute(con)
+                .map(|(_, res)| {
+                    assert_eq!(res, (1, 2));
+                })
+        })
+    });
+}
+
+#[test]
+fn typed_pipeline_durable() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            TypedPipeline::new()
+                .add_command::<i64>(redis::cmd("INCR").arg("key").clone())
+                .add_command::<i64>(redis::cmd("INCR").arg("key").clone())
+                .execute_durable(con, 1, Duration::from_millis(100))
+                .map(|(_, (res, acked))| {
+                    assert_eq!(res, (1, 2));
+                    // No replica on a single node.
+                    assert_eq!(acked, 0);
+                })
+        })
+    });
+}
+
+#[test]
+fn incr_many() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.set("b", 10)
+                .and_then(|(con, ()): (_, ())| con.incr_many(&[("a", 1), ("b", -3), ("c", 5)]))
+                .and_then(|(con, values)| {
+                    assert_eq!(values, vec![1, 7, 5]);
+                    con.incr_many(&[("a", 2), ("a", 2)])
+                })
+                .map(|(_, values)| {
+                    assert_eq!(values, vec![3, 5]);
+                })
+        })
+    });
+}
+
+#[test]
+fn pipeline_auto_flush() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            let value = "x".repeat(100);
+            let p = (0..100).fold(Pipeline::new().auto_flush(1024), |p, i| {
+                p.add_command(redis::cmd("SET").arg(i).arg(&value).clone())
+            });
+            assert!(p.batches() > 1);
+            let batches = p.batches();
+
+            p.execute(con).filter_map(|(_, rvs)| rvs).collect().map(
+                move |results: Vec<Vec<String>>| {
+                    assert_eq!(results.len(), batches);
+                    assert!(results.iter().all(|rvs| rvs.len() < 100));
+                    let results: Vec<_> = results.into_iter().flatten().collect();
+                    assert_eq!(results.len(), 100);
+                    assert!(results.iter().all(|r| r == "OK"));
+                },
+            )
+        })
+    });
+}
+
+#[test]
+fn pipeline_single_batch() {
+    let p = (0..100).fold(Pipeline::new(), |p, i| {
+        p.add_command(redis::cmd("INCR").arg(i).clone())
+    });
+    assert_eq!(p.batches(), 1);
+    assert_eq!(Pipeline::new().cmd("PING").batches(), 1);
+    assert_eq!(Pipeline::new().batches(), 0);
+}
+
+#[test]
+fn pipeline_query_async() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            Pipeline::new()
+                .set("key", 1)
+                .cmd("INCR")
+                .arg("key")
+                .incr("key", 10)
+                .get("key")
+                .query_async(con)
+                .map(|(_, res): (_, (String, i64, i64, i64))| {
+                    assert_eq!(res, ("OK".into(), 2, 12, 12));
+                })
+        })
+    });
+}
+
+#[test]
+fn pipeline_atomic() {
+    test(|c| {
+        let p = (0..100).fold(Pipeline::atomic().auto_flush(16), |p, i| p.set(i, i));
+        // Never split.
+        assert_eq!(p.batches(), 1);
+
+        c.get_async_connection().and_then(|con| {
+            p.del(0)
+                .query_async(con)
+                .map(|(_, res): (_, Vec<redis::Value>)| {
+                    assert_eq!(res.len(), 101);
+                    assert_eq!(res[100], redis::Value::Int(1));
+                })
+        })
+    });
+}
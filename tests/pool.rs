@@ -0,0 +1,173 @@
+#![cfg(feature = "pool")]
+
+use futures::prelude::*;
+use redis_ac::{ClientKillFilter, Commands, ConnectionManager, OnReturn, Pool};
+
+mod helper;
+
+use crate::helper::*;
+
+fn kill(
+    c: &redis::Client,
+    con: redis::aio::Connection,
+) -> impl Future<Item = redis::aio::Connection, Error = redis::RedisError> {
+    redis::cmd("CLIENT")
+        .arg("ID")
+        .query_async(con)
+        .join(c.get_async_connection())
+        .and_then(|((con, id), killer): ((_, u64), _)| {
+            killer
+                .client_kill(ClientKillFilter::default().id(id))
+                .map(move |(_, n): (_, usize)| {
+                    assert_eq!(n, 1);
+                    con
+                })
+        })
+}
+
+#[test]
+fn is_valid() {
+    test(|c| {
+        let manager = ConnectionManager::new(c.clone());
+        let m = manager.clone();
+
+        manager
+            .connect()
+            .and_then(move |con| m.is_valid(con).map(|con| (m, con)))
+            .and_then(move |(m, con)| kill(&c, con).map(|con| (m, con)))
+            .and_then(|(m, con)| m.is_valid(con).then(Ok::<_, redis::RedisError>))
+            .map(|res| {
+                // The killed connection is detected.
+                assert!(res.is_err());
+            })
+    });
+}
+
+#[test]
+fn pool_replaces_broken() {
+    test(|c| {
+        let pool = Pool::new(ConnectionManager::new(c.clone()));
+        let p = pool.clone();
+
+        pool.get()
+            .and_then(move |con| kill(&c, con))
+            .and_then(move |con| {
+                p.put(con);
+                assert_eq!(p.idle(), 1);
+                // The broken idle connection is replaced with a new one.
+                p.get()
+            })
+            .and_then(|con| redis::cmd("PING").query_async(con))
+            .map(|(_, pong): (_, String)| {
+                assert_eq!(pong, "PONG");
+            })
+    });
+}
+
+fn client_id(
+    con: redis::aio::Connection,
+) -> impl Future<Item = (redis::aio::Connection, u64), Error = redis::RedisError> {
+    redis::cmd("CLIENT").arg("ID").query_async(con)
+}
+
+/// Checks out a connection, leaves some state on it and gives it back.
+///
+/// Returns the id of the connection.
+fn dirty(
+    pool: &Pool,
+    state: Vec<redis::Cmd>,
+) -> impl Future<Item = u64, Error = redis::RedisError> {
+    let p = pool.clone();
+
+    pool.get()
+        .and_then(client_id)
+        .and_then(|(con, id)| {
+            futures::stream::iter_ok(state)
+                .fold(con, |con, cmd| {
+                    cmd.query_async(con).map(|(con, ()): (_, ())| con)
+                })
+                .map(move |con| (con, id))
+        })
+        .map(move |(con, id)| {
+            p.put(con);
+            id
+        })
+}
+
+/// Checks out the connection of the id again, and returns the value of `key`.
+fn reuse(pool: &Pool, id: u64) -> impl Future<Item = Option<String>, Error = redis::RedisError> {
+    pool.get()
+        .and_then(client_id)
+        .and_then(move |(con, reused)| {
+            // The same connection, not a new one.
+            assert_eq!(reused, id);
+            con.get("key")
+        })
+        .map(|(_, v)| v)
+}
+
+fn select(db: i64) -> redis::Cmd {
+    let mut c = redis::cmd("SELECT");
+    c.arg(db);
+    c
+}
+
+#[test]
+fn on_return_keep() {
+    test(|c| {
+        let pool = Pool::new(ConnectionManager::new(c.clone()));
+        let p = pool.clone();
+
+        c.get_async_connection()
+            .and_then(|con| con.set("key", "value"))
+            .and_then(move |(_, ()): (_, ())| dirty(&pool, vec![select(1)]))
+            .and_then(move |id| reuse(&p, id))
+            .map(|v| {
+                // Still reads the database 1.
+                assert_eq!(v, None);
+            })
+    });
+}
+
+#[test]
+fn on_return_reset() {
+    test(|c| {
+        let pool = Pool::new(ConnectionManager::new(c.clone())).on_return(OnReturn::Reset);
+        let p = pool.clone();
+
+        let mut subscribe = redis::cmd("SUBSCRIBE");
+        subscribe.arg("ch");
+
+        c.get_async_connection()
+            .and_then(|con| con.set("key", "value"))
+            .and_then(move |(_, ()): (_, ())| dirty(&pool, vec![select(1), subscribe]))
+            .and_then(move |id| {
+                assert_eq!(p.idle(), 1);
+                reuse(&p, id)
+            })
+            .map(|v| {
+                // Reads the database 0.
+                assert_eq!(v, Some("value".into()));
+            })
+    });
+}
+
+#[test]
+fn on_return_cleanup() {
+    test(|c| {
+        let pool = Pool::new(ConnectionManager::new(c.clone())).on_return(OnReturn::Cleanup);
+        let p = pool.clone();
+
+        c.get_async_connection()
+            .and_then(|con| con.set("key", "value"))
+            .and_then(move |(_, ()): (_, ())| dirty(&pool, vec![select(1), redis::cmd("MULTI")]))
+            .and_then(move |id| {
+                assert_eq!(p.idle(), 1);
+                reuse(&p, id)
+            })
+            .map(|v| {
+                // Reads the database 0 out of the transaction.
+                assert_eq!(v, Some("value".into()));
+            })
+    });
+}
@@ -0,0 +1,378 @@
+use futures::{future, prelude::*};
+use redis::ControlFlow;
+use redis_ac::{Commands, MsgKind, PubSubCommands};
+use std::time::{Duration, Instant};
+use tokio::timer::Delay;
+
+mod helper;
+
+use crate::helper::*;
+
+/// Publishes messages after a while so that the subscriber is ready.
+fn publish_later(
+    c: &redis::Client,
+    msgs: Vec<(&'static str, &'static str)>,
+) -> impl Future<Item = (), Error = redis::RedisError> {
+    c.get_async_connection().and_then(|con| {
+        Delay::new(Instant::now() + Duration::from_millis(500))
+            .map_err(|e| panic!("{}", e))
+            .and_then(move |_| {
+                futures::stream::iter_ok(msgs).fold(con, |con, (ch, msg)| {
+                    con.publish(ch, msg).map(|(con, _): (_, usize)| con)
+                })
+            })
+            .map(|_| ())
+    })
+}
+
+#[test]
+fn subscribe_multi() {
+    test(|c| {
+        let publish = publish_later(
+            &c,
+            vec![("a", "1"), ("b", "1"), ("a", "2"), ("b", "2"), ("b", "3")],
+        );
+
+        let mut received = 0;
+        let subscribe = c.get_async_connection().and_then(move |con| {
+            con.subscribe_multi(&["a", "b"], move |_| {
+                received += 1;
+                if received == 5 {
+                    Ok(ControlFlow::Break(()))
+                } else {
+                    Ok(ControlFlow::Continue)
+                }
+            })
+        });
+
+        subscribe
+            .join(publish)
+            .map(|((_, res), ()): ((_, Result<_, ()>), _)| {
+                let ((), counts) = res.unwrap();
+                assert_eq!(counts.len(), 2);
+                assert_eq!(counts["a"], 2);
+                assert_eq!(counts["b"], 3);
+            })
+    });
+}
+
+#[test]
+fn publish_min_subscribers() {
+    test(|c| {
+        let mut received = 0;
+        let subscribe = c.get_async_connection().and_then(move |con| {
+            con.subscribe("ch", move |_| {
+                received += 1;
+                if received == 2 {
+                    Ok(ControlFlow::Break(()))
+                } else {
+                    Ok(ControlFlow::Continue)
+                }
+            })
+        });
+
+        let publish = c.get_async_connection().and_then(|con| {
+            Delay::new(Instant::now() + Duration::from_millis(500))
+                .map_err(|e| panic!("{}", e))
+                .and_then(move |_| con.publish_min_subscribers("ch", "1", 2))
+                .and_then(|(con, ok)| {
+                    assert!(!ok);
+                    con.publish_min_subscribers("ch", "2", 1)
+                })
+                .map(|(_, ok)| {
+                    assert!(ok);
+                })
+        });
+
+        subscribe
+            .join(publish)
+            .map(|((_, res), ()): ((_, Result<(), ()>), _)| {
+                res.unwrap();
+            })
+    });
+}
+
+/// Waits until no channel has subscribers, checking every 100ms up to 2s.
+fn wait_no_channels(c: &redis::Client) -> impl Future<Item = (), Error = redis::RedisError> {
+    c.get_async_connection().and_then(|con| {
+        future::loop_fn((con, 0), |(con, tries)| {
+            redis::cmd("PUBSUB")
+                .arg("CHANNELS")
+                .query_async(con)
+                .and_then(move |(con, chs): (_, Vec<String>)| {
+                    assert!(tries < 20, "channels not cleared: {:?}", chs);
+                    let cleared = chs.is_empty();
+                    Delay::new(Instant::now() + Duration::from_millis(100))
+                        .map_err(|e| panic!("{}", e))
+                        .map(move |_| {
+                            if cleared {
+                                future::Loop::Break(())
+                            } else {
+                                future::Loop::Continue((con, tries + 1))
+                            }
+                        })
+                })
+        })
+    })
+}
+
+#[test]
+fn subscribe_drop() {
+    test(|c| {
+        let subscribe = c
+            .get_async_connection()
+            .and_then(|con| con.subscribe("ch", |_| Ok::<_, ()>(ControlFlow::<()>::Continue)))
+            .map(|_| ());
+        let timeout = Delay::new(Instant::now() + Duration::from_millis(500))
+            .map_err(|e| -> redis::RedisError { panic!("{}", e) });
+
+        subscribe.select(timeout).then(move |res| {
+            // Drops the subscription in progress.
+            assert!(res.is_ok());
+            drop(res);
+            wait_no_channels(&c)
+        })
+    });
+}
+
+#[test]
+fn subscribe_callback_error() {
+    test(|c| {
+        let publish = publish_later(&c, vec![("ch", "1")]);
+
+        let subscribe = c.get_async_connection().and_then(|con| {
+            con.subscribe("ch", |_| Err::<ControlFlow<()>, _>("failed"))
+                .and_then(|(con, res)| {
+                    assert_eq!(res, Err("failed"));
+                    // Unsubscribed even on error.
+                    redis::cmd("PING").query_async(con)
+                })
+                .map(|(_, pong): (_, String)| {
+                    assert_eq!(pong, "PONG");
+                })
+        });
+
+        subscribe.join(publish).map(|_| ())
+    });
+}
+
+#[test]
+fn unsubscribe_now() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            redis::cmd("SUBSCRIBE")
+                .arg(&["a", "b"])
+                .query_async(con)
+                .and_then(|(con, ()): (_, ())| redis::cmd("PSUBSCRIBE").arg("p*").query_async(con))
+                .and_then(|(con, ()): (_, ())| con.unsubscribe_now())
+                .and_then(|con| redis::cmd("PING").query_async(con))
+                .map(|(_, pong): (_, String)| {
+                    assert_eq!(pong, "PONG");
+                })
+        })
+    });
+}
+
+#[test]
+fn request_reply() {
+    test(|c| {
+        // Echoes the first request back to the reply channel.
+        let responder = c.get_async_connection().and_then(|con| {
+            con.subscribe("requests", |msg| {
+                Ok::<_, ()>(ControlFlow::Break(msg.get_payload::<String>().unwrap()))
+            })
+            .and_then(|(con, res)| con.publish("replies", res.unwrap()))
+            .map(|(_, _): (_, usize)| ())
+        });
+
+        let request = c.get_async_connection().and_then(move |con| {
+            let c2 = c.clone();
+            Delay::new(Instant::now() + Duration::from_millis(500))
+                .map_err(|e| panic!("{}", e))
+                .and_then(move |_| {
+                    con.request_reply(&c, "requests", "ping", "replies", Duration::from_secs(5))
+                })
+                .and_then(move |(con, msg)| {
+                    let msg = msg.unwrap();
+                    assert_eq!(msg.get_channel_name(), "replies");
+                    assert_eq!(msg.get_payload(), Ok("ping".to_string()));
+                    // Nobody responds this time.
+                    con.request_reply(
+                        &c2,
+                        "requests",
+                        "ping",
+                        "replies",
+                        Duration::from_millis(200),
+                    )
+                })
+                .map(|(_, msg)| assert!(msg.is_none()))
+        });
+
+        responder.join(request)
+    });
+}
+
+#[test]
+fn subscribe_stream() {
+    test(|c| {
+        let publish = publish_later(&c, vec![("a", "1"), ("c", "x"), ("b", "2"), ("a", "3")]);
+
+        let subscribe = c
+            .get_async_connection()
+            .and_then(|con| con.subscribe_stream(&["a", "b"]))
+            .and_then(|msgs| msgs.take(3).collect())
+            .map(|msgs| {
+                assert!(msgs.iter().all(|m| m.kind() == MsgKind::Message));
+                let msgs: Vec<(String, String)> = msgs
+                    .iter()
+                    .map(|m| (m.get_channel_name().into(), m.get_payload().unwrap()))
+                    .collect();
+                assert_eq!(
+                    msgs,
+                    vec![
+                        ("a".into(), "1".into()),
+                        ("b".into(), "2".into()),
+                        ("a".into(), "3".into())
+                    ]
+                );
+            });
+
+        subscribe.join(publish).map(|_| ())
+    });
+}
+
+#[test]
+fn psubscribe_stream_closed() {
+    test(|c| {
+        let kill = c.get_async_connection().and_then(|con| {
+            Delay::new(Instant::now() + Duration::from_millis(500))
+                .map_err(|e| panic!("{}", e))
+                .and_then(move |_| con.publish("ch1", "1"))
+                .and_then(|(con, _): (_, usize)| {
+                    redis::cmd("CLIENT")
+                        .arg("KILL")
+                        .arg("TYPE")
+                        .arg("pubsub")
+                        .query_async(con)
+                })
+                .map(|(_, n): (_, usize)| assert_eq!(n, 1))
+        });
+
+        let subscribe = c
+            .get_async_connection()
+            .and_then(|con| con.psubscribe_stream("ch*"))
+            // Ends when the connection is closed.
+            .and_then(|msgs| msgs.collect())
+            .map(|msgs| {
+                assert_eq!(msgs.len(), 1);
+                assert!(msgs[0].from_pattern());
+                assert_eq!(msgs[0].kind(), MsgKind::PMessage);
+                assert_eq!(msgs[0].get_payload(), Ok("1".to_string()));
+            });
+
+        subscribe.join(kill).map(|_| ())
+    });
+}
+
+fn delay(ms: u64) -> impl Future<Item = (), Error = redis::RedisError> {
+    Delay::new(Instant::now() + Duration::from_millis(ms)).map_err(|e| panic!("{}", e))
+}
+
+#[test]
+fn subscribe_with_handle() {
+    test(|c| {
+        let c2 = c.clone();
+
+        c.get_shared_async_connection()
+            .join(c.get_async_connection())
+            .and_then(move |(notifier, con)| {
+                let mut received = Vec::new();
+                let (handle, subscribe) = con.subscribe_with_handle(notifier, "a", move |msg| {
+                    let payload: String = msg.get_payload().unwrap();
+                    received.push((msg.get_channel_name().to_string(), payload.clone()));
+                    Ok::<_, ()>(if payload == "end" {
+                        ControlFlow::Break(std::mem::take(&mut received))
+                    } else {
+                        ControlFlow::Continue
+                    })
+                });
+
+                let publish = c2.get_async_connection().and_then(move |con| {
+                    delay(500)
+                        .and_then(move |_| con.publish("a", "1"))
+                        .and_then(move |(con, _): (_, usize)| {
+                            handle
+                                .subscribe("b")
+                                .and_then(|_| delay(200))
+                                .and_then(move |_| con.publish("b", "2"))
+                                .and_then(move |(con, _): (_, usize)| {
+                                    handle.unsubscribe("a").map(move |_| (con, handle))
+                                })
+                        })
+                        .and_then(|(con, handle)| delay(200).map(move |_| (con, handle)))
+                        .and_then(|(con, handle)| {
+                            con.publish("a", "ignored")
+                                .map(move |(con, n): (_, usize)| (con, n, handle))
+                        })
+                        .and_then(|(con, n, handle)| {
+                            assert_eq!(n, 0);
+                            con.publish("b", "end").map(move |_: (_, usize)| handle)
+                        })
+                });
+
+                subscribe.join(publish)
+            })
+            .and_then(|((con, res), handle)| {
+                assert_eq!(
+                    res.unwrap(),
+                    vec![
+                        ("a".to_string(), "1".to_string()),
+                        ("b".into(), "2".into()),
+                        ("b".into(), "end".into())
+                    ]
+                );
+                // The future finished.
+                handle.subscribe("c").then(move |res| {
+                    assert!(res.is_err());
+                    redis::cmd("PING").query_async(con)
+                })
+            })
+            .map(|(_, pong): (_, String)| assert_eq!(pong, "PONG"))
+    });
+}
+
+#[test]
+fn ssubscribe() {
+    test(|c| {
+        let publish = c.get_async_connection().and_then(|con| {
+            delay(500)
+                .and_then(move |_| con.spublish("shard", "1"))
+                .and_then(|(con, n): (_, usize)| {
+                    assert_eq!(n, 1);
+                    con.spublish("shard", "2")
+                })
+                .map(|(_, _): (_, usize)| ())
+        });
+
+        let mut received = Vec::new();
+        let subscribe = c.get_async_connection().and_then(move |con| {
+            con.ssubscribe("shard", move |msg| {
+                assert_eq!(msg.kind(), MsgKind::SMessage);
+                received.push(msg.get_payload::<String>().unwrap());
+                Ok::<_, ()>(if received.len() == 2 {
+                    ControlFlow::Break(std::mem::take(&mut received))
+                } else {
+                    ControlFlow::Continue
+                })
+            })
+            .and_then(|(con, res)| {
+                assert_eq!(res.unwrap(), vec!["1", "2"]);
+                // Unsubscribed by `SUNSUBSCRIBE`.
+                redis::cmd("PING").query_async(con)
+            })
+            .map(|(_, pong): (_, String)| assert_eq!(pong, "PONG"))
+        });
+
+        subscribe.join(publish).map(|_| ())
+    });
+}
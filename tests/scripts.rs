@@ -0,0 +1,272 @@
+use futures::prelude::*;
+use redis::{ErrorKind, RedisError};
+use redis_ac::{Commands, Script};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::timer::Delay;
+
+mod helper;
+
+use crate::helper::*;
+
+#[test]
+fn hincr_and_getall() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.hset_multiple("hash", &[("count", "1"), ("name", "foo")])
+                .and_then(|(con, ()): (_, ())| con.hincr_and_getall("hash", "count", 5))
+                .map(|(_, map)| {
+                    let mut exp = HashMap::new();
+                    exp.insert("count".to_string(), "6".to_string());
+                    exp.insert("name".to_string(), "foo".to_string());
+                    assert_eq!(map, exp);
+                })
+        })
+    });
+}
+
+#[test]
+fn append_capped() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.append_capped("log", "line1\n", 16)
+                .and_then(|(con, n)| {
+                    assert_eq!(n, 6);
+                    con.append_capped("log", "line2\n", 16)
+                })
+                .and_then(|(con, n)| {
+                    assert_eq!(n, 12);
+                    // Goes past the cap.
+                    con.append_capped("log", "line3\n", 16)
+                })
+                .and_then(|(con, n)| {
+                    assert_eq!(n, 16);
+                    con.get("log")
+                })
+                .map(|(_, log): (_, String)| {
+                    assert_eq!(log, "ne1\nline2\nline3\n");
+                })
+        })
+    });
+}
+
+#[test]
+fn zincr_capped() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.zadd_multiple("board", &[(1, "a"), (2, "b"), (3, "c")])
+                .and_then(|(con, _): (_, usize)| con.zincr_capped("board", "d", 2.5, 3))
+                .and_then(|(con, score)| {
+                    assert_eq!(score, 2.5);
+                    con.zincr_capped("board", "b", 2, 2)
+                })
+                .and_then(|(con, score)| {
+                    assert_eq!(score, 4.0);
+                    con.zrange_withscores("board", 0, -1)
+                })
+                .map(|(_, res): (_, Vec<(String, f64)>)| {
+                    // The lowest members are trimmed.
+                    assert_eq!(res, vec![("c".into(), 3.0), ("b".into(), 4.0)]);
+                })
+        })
+    });
+}
+
+#[test]
+fn zadd_gt_rank() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.zadd_multiple("board", &[(10, "a"), (20, "b"), (30, "c")])
+                .and_then(|(con, _): (_, usize)| con.zadd_gt_rank("board", "a", 25))
+                .and_then(|(con, rank)| {
+                    assert_eq!(rank, Some(1));
+                    // Not updated with a lower score.
+                    con.zadd_gt_rank("board", "a", 5)
+                })
+                .and_then(|(con, rank)| {
+                    assert_eq!(rank, Some(1));
+                    con.zadd_gt_rank("board", "d", 40)
+                })
+                .and_then(|(con, rank)| {
+                    // Not added.
+                    assert_eq!(rank, None);
+                    con.zscore("board", "d")
+                })
+                .and_then(|(con, score): (_, Option<f64>)| {
+                    assert_eq!(score, None);
+                    con.zadd_gt_rank("board", "a", 35)
+                })
+                .and_then(|(con, rank)| {
+                    assert_eq!(rank, Some(0));
+                    con.zscore("board", "a")
+                })
+                .map(|(_, score): (_, f64)| {
+                    assert_eq!(score, 35.0);
+                })
+        })
+    });
+}
+
+#[test]
+fn zrandmember_weighted() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.zadd_multiple("weights", &[(1, "light"), (9, "heavy"), (0, "never")])
+                .and_then(|(con, _): (_, usize)| {
+                    futures::stream::iter_ok(0..200).fold(
+                        (con, HashMap::new()),
+                        |(con, mut counts), _| {
+                            con.zrandmember_weighted("weights").map(|(con, picked)| {
+                                *counts.entry(picked.unwrap()).or_insert(0) += 1;
+                                (con, counts)
+                            })
+                        },
+                    )
+                })
+                .and_then(|(con, counts): (_, HashMap<String, usize>)| {
+                    assert!(!counts.contains_key("never"));
+                    // 180 vs 20 on average.
+                    assert!(counts["heavy"] > counts.get("light").unwrap_or(&0) * 3);
+                    con.zrandmember_weighted("missing")
+                })
+                .map(|(_, picked)| {
+                    assert_eq!(picked, None);
+                })
+        })
+    });
+}
+
+#[test]
+fn zadd_next() {
+    test(|c| {
+        let appends = (0..5).map(move |i| {
+            c.get_async_connection().and_then(move |con| {
+                futures::stream::iter_ok(0..20).fold(
+                    (con, Vec::new()),
+                    move |(con, mut seqs), j| {
+                        con.zadd_next("seq", "events", format!("event:{}:{}", i, j))
+                            .map(|(con, seq)| {
+                                seqs.push(seq);
+                                (con, seqs)
+                            })
+                    },
+                )
+            })
+        });
+
+        futures::future::join_all(appends)
+            .and_then(|res| {
+                let mut cons = Vec::new();
+                let mut seqs = Vec::new();
+                for (con, s) in res {
+                    cons.push(con);
+                    seqs.extend(s);
+                }
+                seqs.sort();
+                assert_eq!(seqs, (1..=100).collect::<Vec<i64>>());
+                let con = cons.pop().unwrap();
+                con.zrange_withscores("events", 0, -1)
+            })
+            .map(|(_, res): (_, Vec<(String, i64)>)| {
+                let scores: Vec<_> = res.into_iter().map(|(_, score)| score).collect();
+                assert_eq!(scores, (1..=100).collect::<Vec<_>>());
+            })
+    });
+}
+
+#[test]
+fn token_bucket() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            futures::stream::iter_ok(0..4)
+                .fold((con, Vec::new()), |(con, mut taken), _| {
+                    con.token_bucket("bucket", 10.0, 3.0, 1.0).map(|(con, ok)| {
+                        taken.push(ok);
+                        (con, taken)
+                    })
+                })
+                .and_then(|(con, taken)| {
+                    // Exhausted.
+                    assert_eq!(taken, vec![true, true, true, false]);
+                    Delay::new(Instant::now() + Duration::from_millis(300))
+                        .map_err(|e| panic!("{}", e))
+                        .and_then(move |_| con.token_bucket("bucket", 10.0, 3.0, 2.0))
+                })
+                .and_then(|(con, ok)| {
+                    // Refilled over time.
+                    assert!(ok);
+                    con.token_bucket("bucket", 10.0, 3.0, 5.0)
+                })
+                .map(|(_, ok)| {
+                    // More than the capacity.
+                    assert!(!ok);
+                })
+        })
+    });
+}
+
+#[test]
+fn eval_evalsha() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.eval("return {KEYS[1], ARGV[1]}", 1, "key", "arg")
+                .and_then(|(con, res): (_, Vec<String>)| {
+                    assert_eq!(res, vec!["key", "arg"]);
+                    con.script_load("return ARGV[1] + ARGV[2]")
+                })
+                .and_then(|(con, sha): (_, String)| con.evalsha(sha, 0, &[] as &[&str], &[1, 2]))
+                .map(|(_, res): (_, i64)| {
+                    assert_eq!(res, 3);
+                })
+        })
+    });
+}
+
+#[test]
+fn script_fallback() {
+    test(|c| {
+        let script = Script::new("return redis.call('INCRBY', KEYS[1], ARGV[1])");
+        let s1 = script.clone();
+        let s2 = script.clone();
+        let s3 = script.clone();
+
+        c.get_shared_async_connection().and_then(move |con| {
+            // Sent by `EVAL` and then by `EVALSHA`.
+            script
+                .invoke(con, &["counter"], 1)
+                .and_then(move |(con, n): (_, i64)| {
+                    assert_eq!(n, 1);
+                    s1.invoke(con, &["counter"], 1)
+                })
+                .and_then(|(con, n): (_, i64)| {
+                    assert_eq!(n, 2);
+                    redis::cmd("SCRIPT").arg("FLUSH").query_async(con)
+                })
+                // Retried with `EVAL` after `NOSCRIPT`.
+                .and_then(move |(con, ()): (_, ())| s2.invoke_shared(con, &["counter"], 1))
+                .and_then(move |(con, n): (_, i64)| {
+                    assert_eq!(n, 3);
+                    redis::cmd("SCRIPT")
+                        .arg("FLUSH")
+                        .query_async(con)
+                        .map(move |(con, ()): (_, ())| (con, s3))
+                })
+                .and_then(|(con, script)| {
+                    let retry = script.clone();
+                    script.invoke(con.clone(), &["counter"], 1).then(
+                        move |res: Result<(_, i64), RedisError>| {
+                            match res {
+                                Ok(_) => panic!("EVALSHA succeeded after SCRIPT FLUSH"),
+                                Err(e) => assert_eq!(e.kind(), ErrorKind::NoScriptError),
+                            }
+                            // Sent by `EVAL` again.
+                            retry.invoke(con, &["counter"], 1)
+                        },
+                    )
+                })
+                .map(|(_, n): (_, i64)| {
+                    assert_eq!(n, 4);
+                })
+        })
+    });
+}
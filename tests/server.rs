@@ -0,0 +1,275 @@
+use futures::prelude::*;
+use redis::{FromRedisValue, Value};
+use redis_ac::{spawn_keepalive, ClientKillFilter, Commands, ReplicaInfo, Role, ServerInfo};
+use std::collections::HashMap;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::timer::Delay;
+
+mod helper;
+
+use crate::helper::*;
+
+#[test]
+fn client_kill_id() {
+    test(|c| {
+        c.get_async_connection()
+            .join(c.get_async_connection())
+            .and_then(|(con, victim)| {
+                redis::cmd("CLIENT").arg("ID").query_async(victim).and_then(
+                    move |(victim, id): (_, u64)| {
+                        con.client_kill(ClientKillFilter::default().id(id))
+                            .map(move |(con, n): (_, usize)| (con, victim, n))
+                    },
+                )
+            })
+            .and_then(|(con, victim, n)| {
+                assert_eq!(n, 1);
+                // The killed connection can't be used anymore.
+                redis::cmd("PING").query_async(victim).then(
+                    move |res: redis::RedisResult<(_, String)>| {
+                        assert!(res.is_err());
+                        redis::cmd("PING").query_async(con)
+                    },
+                )
+            })
+            .map(|(_, pong): (_, String)| {
+                assert_eq!(pong, "PONG");
+            })
+    });
+}
+
+#[test]
+fn keepalive() {
+    test(|c| {
+        c.get_shared_async_connection().and_then(|con| {
+            let interval = Duration::from_millis(200);
+            let (handle, keepalive) = spawn_keepalive(con.clone(), interval);
+
+            // Commands on the same connection work while the keepalive is running.
+            let get = Delay::new(Instant::now() + interval * 5 / 2)
+                .map_err(|e| panic!("{}", e))
+                .and_then(|_| con.set("key", "value"))
+                .and_then(|(con, ()): (_, ())| con.get("key"))
+                .map(move |(_, v): (_, String)| {
+                    assert_eq!(v, "value");
+                    drop(handle);
+                });
+
+            keepalive.join(get).map(|(count, ())| {
+                assert!(count >= 2);
+            })
+        })
+    });
+}
+
+#[test]
+fn time() {
+    test(|c| {
+        c.get_async_connection()
+            .and_then(|con| con.time())
+            .map(|(_, time)| {
+                let now = SystemTime::now();
+                let diff = now
+                    .duration_since(time)
+                    .or_else(|e| Ok::<_, ()>(e.duration()))
+                    .unwrap();
+                assert!(diff < Duration::from_secs(5));
+            })
+    });
+}
+
+#[test]
+fn get_with_age() {
+    test(|c| {
+        c.get_async_connection()
+            .and_then(|con| con.set_timestamped("cache", "value"))
+            .and_then(|(con, ())| {
+                Delay::new(Instant::now() + Duration::from_millis(100))
+                    .map_err(|e| panic!("{}", e))
+                    .and_then(move |_| con.get_with_age("cache"))
+            })
+            .and_then(|(con, res)| {
+                let (value, age) = res.unwrap();
+                assert_eq!(value, "value");
+                assert!(age >= Duration::from_millis(100), "{:?}", age);
+                assert!(age < Duration::from_secs(5), "{:?}", age);
+                con.get_with_age("missing")
+            })
+            .map(|(_, res)| {
+                assert_eq!(res, None);
+            })
+    });
+}
+
+#[test]
+fn role() {
+    test(|c| {
+        c.get_async_connection()
+            .and_then(|con| con.role())
+            .map(|(_, role)| match role {
+                Role::Master { replicas, .. } => assert!(replicas.is_empty()),
+                role => panic!("not a master: {:?}", role),
+            })
+    });
+}
+
+fn data(s: &str) -> Value {
+    Value::Data(s.as_bytes().to_vec())
+}
+
+#[test]
+fn role_parse() {
+    let master = Value::Bulk(vec![
+        data("master"),
+        Value::Int(3129659),
+        Value::Bulk(vec![Value::Bulk(vec![
+            data("127.0.0.1"),
+            data("9001"),
+            data("3129242"),
+        ])]),
+    ]);
+    assert_eq!(
+        Role::from_redis_value(&master).unwrap(),
+        Role::Master {
+            offset: 3129659,
+            replicas: vec![ReplicaInfo {
+                host: "127.0.0.1".into(),
+                port: 9001,
+                offset: 3129242,
+            }],
+        }
+    );
+
+    let replica = Value::Bulk(vec![
+        data("slave"),
+        data("127.0.0.1"),
+        Value::Int(9000),
+        data("connected"),
+        Value::Int(3167038),
+    ]);
+    assert_eq!(
+        Role::from_redis_value(&replica).unwrap(),
+        Role::Replica {
+            master_host: "127.0.0.1".into(),
+            master_port: 9000,
+            state: "connected".into(),
+            offset: 3167038,
+        }
+    );
+
+    let sentinel = Value::Bulk(vec![
+        data("sentinel"),
+        Value::Bulk(vec![data("resque-master"), data("html-fragments-master")]),
+    ]);
+    assert_eq!(
+        Role::from_redis_value(&sentinel).unwrap(),
+        Role::Sentinel {
+            masters: vec!["resque-master".into(), "html-fragments-master".into()],
+        }
+    );
+
+    assert!(Role::from_redis_value(&Value::Bulk(vec![data("master")])).is_err());
+    assert!(Role::from_redis_value(&Value::Nil).is_err());
+}
+
+#[test]
+fn admin_commands() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.set_multiple(&[("a", "1"), ("b", "2")])
+                .and_then(|(con, ()): (_, ())| con.dbsize())
+                .and_then(|(con, n): (_, usize)| {
+                    assert_eq!(n, 2);
+                    con.flushdb()
+                })
+                .and_then(|(con, ()): (_, ())| con.dbsize())
+                .and_then(|(con, n): (_, usize)| {
+                    assert_eq!(n, 0);
+                    con.set("c", "3")
+                })
+                .and_then(|(con, ()): (_, ())| con.flushall_async())
+                .and_then(|(con, ()): (_, ())| con.exists("c"))
+                .and_then(|(con, exists): (_, bool)| {
+                    assert!(!exists);
+                    con.info_section("server")
+                })
+                .and_then(|(con, info): (_, String)| {
+                    assert!(info.contains("redis_version:"));
+                    assert!(!info.contains("used_memory:"));
+                    con.info()
+                })
+                .and_then(|(con, info): (_, String)| {
+                    assert!(info.contains("used_memory:"));
+                    con.config_set("maxmemory-samples", 7)
+                })
+                .and_then(|(con, ()): (_, ())| con.config_get("maxmemory-samples"))
+                .and_then(|(con, config): (_, HashMap<String, String>)| {
+                    assert_eq!(config["maxmemory-samples"], "7");
+                    con.config_set("maxmemory-samples", 5)
+                })
+                .map(|(_, ()): (_, ())| ())
+        })
+    });
+}
+
+#[test]
+fn info_parse() {
+    let text = "# Server\r\nredis_version:7.2.4\r\n\r\n# Replication\r\nrole:master\r\nconnected_slaves:0\r\n# Keyspace\r\ndb0:keys=1,expires=0\r\n";
+    let info: ServerInfo = text.parse().unwrap();
+    assert_eq!(info.sections().len(), 3);
+    assert_eq!(info.get::<String>("redis_version").unwrap(), "7.2.4");
+
+    let replication = info.section("replication").unwrap();
+    assert_eq!(replication.name(), "Replication");
+    assert_eq!(replication.get::<String>("role").unwrap(), "master");
+    assert_eq!(replication.get::<u32>("connected_slaves"), Some(0));
+    assert_eq!(replication.get::<u32>("role"), None);
+    assert!(replication.get::<String>("redis_version").is_none());
+
+    assert_eq!(
+        info.section("Keyspace").unwrap().fields()["db0"],
+        "keys=1,expires=0"
+    );
+    assert!(info.section("Memory").is_none());
+
+    // Without headers.
+    let info: ServerInfo = "cluster_state:ok\r\n".parse().unwrap();
+    assert_eq!(info.sections()[0].name(), "");
+    assert_eq!(info.get::<String>("cluster_state").unwrap(), "ok");
+}
+
+#[test]
+fn info_parsed() {
+    test(|c| {
+        c.get_async_connection()
+            .and_then(|con| con.info_parsed())
+            .map(|(_, info)| {
+                assert!(info.get::<u64>("used_memory").unwrap() > 0);
+                let role: String = info.section("Replication").unwrap().get("role").unwrap();
+                assert_eq!(role, "master");
+            })
+    });
+}
+
+#[test]
+fn client_name_id() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.client_getname()
+                .and_then(|(con, name): (_, Option<String>)| {
+                    assert!(name.is_none());
+                    con.client_setname("worker-1")
+                })
+                .and_then(|(con, ()): (_, ())| con.client_getname())
+                .and_then(|(con, name): (_, Option<String>)| {
+                    assert_eq!(name.as_deref(), Some("worker-1"));
+                    con.client_id()
+                })
+                .and_then(|(con, id): (_, i64)| {
+                    assert!(id > 0);
+                    con.client_no_evict(true)
+                })
+                .map(|(_, ()): (_, ())| ())
+        })
+    });
+}
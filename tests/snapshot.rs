@@ -0,0 +1,54 @@
+use futures::prelude::*;
+use redis_ac::Commands;
+use std::{collections::HashMap, fs::File};
+
+mod helper;
+
+use crate::helper::*;
+
+#[test]
+fn snapshot_roundtrip() {
+    let path = std::env::temp_dir().join(format!("redis-ac-snapshot-{}", std::process::id()));
+    let (p1, p2) = (path.clone(), path.clone());
+
+    test(|c| {
+        c.get_async_connection().and_then(move |con| {
+            con.set("string", "value")
+                .and_then(|(con, ()): (_, ())| con.rpush_len("list", &["a", "b"]))
+                .and_then(|(con, _)| con.hset_multiple("hash", &[("f", "v")]))
+                .and_then(|(con, ()): (_, ())| con.expire("list", 1000))
+                .and_then(move |(con, _): (_, bool)| con.snapshot_to(File::create(p1).unwrap()))
+                .and_then(|(con, n)| {
+                    assert_eq!(n, 3);
+                    redis::cmd("FLUSHALL").query_async(con)
+                })
+                .and_then(move |(con, ()): (_, ())| con.restore_from(File::open(p2).unwrap()))
+                .and_then(|(con, n)| {
+                    assert_eq!(n, 3);
+                    con.get("string")
+                })
+                .and_then(|(con, v): (_, String)| {
+                    assert_eq!(v, "value");
+                    con.lrange("list", 0, -1)
+                })
+                .and_then(|(con, v): (_, Vec<String>)| {
+                    assert_eq!(v, vec!["a", "b"]);
+                    con.hgetall("hash")
+                })
+                .and_then(|(con, v): (_, HashMap<String, String>)| {
+                    assert_eq!(v["f"], "v");
+                    con.ttl("list")
+                })
+                .and_then(|(con, ttl): (_, i64)| {
+                    // The expiration is restored.
+                    assert!(ttl > 0 && ttl <= 1000);
+                    con.ttl("string")
+                })
+                .map(|(_, ttl): (_, i64)| {
+                    assert_eq!(ttl, -1);
+                })
+        })
+    });
+
+    let _ = std::fs::remove_file(path);
+}
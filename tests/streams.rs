@@ -0,0 +1,123 @@
+use futures::prelude::*;
+use redis_ac::{Commands, StreamEntry, StreamKey};
+use std::time::{Duration, Instant};
+use tokio::timer::Delay;
+
+mod helper;
+
+use crate::helper::*;
+
+#[test]
+fn xadd_xrange() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            con.xadd("s", "*", &[("name", "a"), ("n", "1")])
+                .and_then(|(con, id1): (_, String)| {
+                    con.xadd("s", "*", &[("name", "b"), ("n", "2")])
+                        .map(move |(con, id2): (_, String)| (con, id1, id2))
+                })
+                .and_then(|(con, id1, id2)| {
+                    con.xlen("s")
+                        .map(move |(con, n): (_, usize)| (con, n, id1, id2))
+                })
+                .and_then(|(con, n, id1, id2)| {
+                    assert_eq!(n, 2);
+                    con.xrange("s", "-", "+")
+                        .map(move |(con, entries): (_, Vec<StreamEntry>)| (con, entries, id1, id2))
+                })
+                .and_then(|(con, entries, id1, id2)| {
+                    assert_eq!(entries.len(), 2);
+                    assert_eq!(entries[0].id, id1);
+                    assert_eq!(entries[1].id, id2);
+                    assert_eq!(entries[0].get::<String>("name").unwrap().unwrap(), "a");
+                    assert_eq!(entries[1].get::<usize>("n").unwrap().unwrap(), 2);
+                    assert_eq!(entries[1].get::<String>("missing").unwrap(), None);
+
+                    con.xrange("s", &id2[..], "+")
+                })
+                .map(|(_, entries): (_, Vec<StreamEntry>)| {
+                    assert_eq!(entries.len(), 1);
+                    assert_eq!(entries[0].get::<String>("name").unwrap().unwrap(), "b");
+                })
+        })
+    });
+}
+
+#[test]
+fn xread_block() {
+    test(|c| {
+        let add = c.get_async_connection().and_then(|con| {
+            Delay::new(Instant::now() + Duration::from_millis(500))
+                .map_err(|e| panic!("{}", e))
+                .and_then(move |_| con.xadd("s2", "*", &[("v", "x")]))
+                .map(|(_, _): (_, String)| ())
+        });
+
+        let read = c.get_async_connection().and_then(|con| {
+            con.xadd("s1", "1-1", &[("v", "old")])
+                .and_then(|(con, _): (_, String)| {
+                    con.xread_block(&["s1", "s2"], &["$", "$"], 5000, 10)
+                })
+                .and_then(|(con, keys): (_, Vec<StreamKey>)| {
+                    assert_eq!(keys.len(), 1);
+                    assert_eq!(keys[0].key, "s2");
+                    assert_eq!(keys[0].entries.len(), 1);
+                    assert_eq!(keys[0].entries[0].get::<String>("v").unwrap().unwrap(), "x");
+
+                    // Times out.
+                    con.xread_block(&["s1"], &["$"], 100, 10)
+                })
+                .map(|(_, keys): (_, Vec<StreamKey>)| assert!(keys.is_empty()))
+        });
+
+        read.join(add).map(|_| ())
+    });
+}
+
+#[test]
+fn xread_group_stream() {
+    test(|c| {
+        let c2 = c.clone();
+
+        c.get_async_connection()
+            .and_then(|con| con.xgroup_create("s", "g", "$"))
+            .and_then(move |(_, ()): (_, ())| {
+                // Added after some reads timed out.
+                let add = c2.get_async_connection().and_then(|con| {
+                    Delay::new(Instant::now() + Duration::from_millis(500))
+                        .map_err(|e| panic!("{}", e))
+                        .and_then(move |_| {
+                            futures::stream::iter_ok(1..=3).fold(con, |con, i| {
+                                con.xadd("s", "*", &[("n", i)])
+                                    .map(|(con, _): (_, String)| con)
+                            })
+                        })
+                });
+
+                let consume = c2.get_async_connection().and_then(|con| {
+                    con.xread_group_stream("s", "g", "alice", 100)
+                        .count(2)
+                        .take(3)
+                        .collect()
+                });
+
+                consume.join(add)
+            })
+            .and_then(|(entries, con)| {
+                let ns: Vec<usize> = entries
+                    .iter()
+                    .map(|e| e.get("n").unwrap().unwrap())
+                    .collect();
+                assert_eq!(ns, vec![1, 2, 3]);
+
+                let ids: Vec<_> = entries.into_iter().map(|e| e.id).collect();
+                con.xack("s", "g", &ids[..])
+                    .and_then(move |(con, n): (_, usize)| {
+                        assert_eq!(n, 3);
+                        // Already acknowledged.
+                        con.xack("s", "g", &ids[..])
+                    })
+            })
+            .map(|(_, n): (_, usize)| assert_eq!(n, 0))
+    });
+}
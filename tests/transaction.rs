@@ -0,0 +1,70 @@
+use futures::prelude::*;
+use redis::{ErrorKind, RedisError, Value};
+use redis_ac::{Commands, Transaction};
+
+mod helper;
+
+use crate::helper::*;
+
+fn incr(key: &str) -> redis::Cmd {
+    redis::cmd("INCR").arg(key).clone()
+}
+
+#[test]
+fn transaction_exec() {
+    test(|c| {
+        c.get_async_connection().and_then(|con| {
+            Transaction::new()
+                .add_command(incr("a"))
+                .add_command(incr("a"))
+                .add_command(redis::cmd("SET").arg("b").arg("v").clone())
+                .exec(con)
+                .and_then(|(con, res)| {
+                    assert_eq!(res, vec![Value::Int(1), Value::Int(2), Value::Okay]);
+                    Transaction::new().exec(con)
+                })
+                .map(|(_, res)| assert!(res.is_empty()))
+        })
+    });
+}
+
+#[test]
+fn transaction_watch() {
+    test(|c| {
+        let other = c.get_async_connection();
+
+        c.get_async_connection()
+            .and_then(|con| con.watch("a"))
+            .join(other)
+            // Modified by another connection.
+            .and_then(|((con, ()), other): ((_, ()), _)| {
+                other.incr("a", 1).map(|(_, _): (_, i64)| con)
+            })
+            .and_then(|con| Transaction::new().add_command(incr("a")).exec(con))
+            .then(|res| {
+                match res {
+                    Ok(_) => panic!("transaction wasn't aborted"),
+                    Err(e) => assert_eq!(e.kind(), ErrorKind::ExecAbortError),
+                }
+                Ok::<_, RedisError>(())
+            })
+    });
+}
+
+#[test]
+fn transaction_discard() {
+    test(|c| {
+        let other = c.get_async_connection();
+
+        c.get_async_connection()
+            .and_then(|con| con.watch("a"))
+            .and_then(|(con, ()): (_, ())| Transaction::new().add_command(incr("a")).discard(con))
+            .join(other)
+            .and_then(|(con, other)| other.incr("a", 1).map(|(_, _): (_, i64)| con))
+            .and_then(|con| Transaction::new().add_command(incr("a")).exec(con))
+            .map(|(_, res)| {
+                // Not watched anymore, and the discarded command isn't run.
+                assert_eq!(res, vec![Value::Int(2)]);
+            })
+    });
+}